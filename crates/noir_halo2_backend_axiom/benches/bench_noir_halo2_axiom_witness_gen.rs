@@ -0,0 +1,37 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use noir_halo2_backend_common::test_helpers::{
+    install_nargo, run_nargo_prove, test_program_dir_path,
+};
+use std::env;
+
+/// Environment variable the backend run by nargo reads
+/// to generate the witnesses of black box functions in parallel
+const PARALLEL_WITNESS_GEN_VAR: &str = "NOIR_HALO2_PARALLEL_WITNESS_GEN";
+
+fn benchmark_tests_witness_gen(c: &mut Criterion) {
+    install_nargo("axiom_halo2_backend");
+
+    for program in ["13_sha256", "14_keccak256", "15_ecdsa_secp256k1"] {
+        let path = test_program_dir_path(program);
+
+        // serial witness generation
+        env::remove_var(PARALLEL_WITNESS_GEN_VAR);
+        c.bench_function(&format!("{program}_axiom_prove"), |b| {
+            b.iter(|| run_nargo_prove(path.clone()))
+        });
+
+        // parallel witness generation
+        env::set_var(PARALLEL_WITNESS_GEN_VAR, "1");
+        c.bench_function(&format!("{program}_axiom_prove_parallel"), |b| {
+            b.iter(|| run_nargo_prove(path.clone()))
+        });
+    }
+    env::remove_var(PARALLEL_WITNESS_GEN_VAR);
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets =  benchmark_tests_witness_gen
+}
+criterion_main!(benches);
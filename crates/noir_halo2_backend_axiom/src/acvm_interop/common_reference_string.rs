@@ -1,6 +1,6 @@
 use crate::{
-    circuit_translator::NoirHalo2Translator, halo2_params::constuct_halo2_params_from_aztec_crs,
-    AxiomHalo2,
+    circuit_translator::NoirHalo2Translator, dimension_measure::DimensionMeasurement,
+    halo2_params::constuct_halo2_params_from_aztec_crs, AxiomHalo2,
 };
 use acvm::{
     acir::{circuit::Circuit, native_types::WitnessMap},
@@ -26,7 +26,8 @@ impl CommonReferenceString for AxiomHalo2 {
             witness_values: WitnessMap::new(),
             _marker: PhantomData::<Fr>,
         };
-        constuct_halo2_params_from_aztec_crs(translator).await?.write_custom(
+        let k = DimensionMeasurement::measure(&translator, self.parallel_witness_gen)?.k();
+        constuct_halo2_params_from_aztec_crs(k).await?.write_custom(
             &mut common_reference_string,
             halo2_base::halo2_proofs::SerdeFormat::RawBytes,
         );
@@ -47,7 +48,8 @@ impl CommonReferenceString for AxiomHalo2 {
             witness_values: WitnessMap::new(),
             _marker: PhantomData::<Fr>,
         };
-        constuct_halo2_params_from_aztec_crs(translator).await?.write_custom(
+        let k = DimensionMeasurement::measure(&translator, self.parallel_witness_gen)?.k();
+        constuct_halo2_params_from_aztec_crs(k).await?.write_custom(
             &mut common_reference_string,
             halo2_base::halo2_proofs::SerdeFormat::RawBytes,
         );
@@ -1,6 +1,13 @@
+use super::pwg::halo2_field_to_noir_field;
 use crate::{
     circuit_translator::NoirHalo2Translator,
-    halo2_plonk_api::{halo2_keygen, halo2_prove, halo2_verify},
+    dimension_measure::DimensionMeasurement,
+    encoding::{elements_to_fields, proof_elements, vk_to_fields},
+    halo2_params::read_halo2_params,
+    halo2_plonk_api::{
+        halo2_keygen, halo2_pk_from_bytes, halo2_pk_to_bytes, halo2_prove, halo2_verify,
+        halo2_vk_from_bytes, halo2_vk_to_bytes,
+    },
     AxiomHalo2,
 };
 use acvm::{
@@ -11,56 +18,65 @@ use acvm::{
     },
     FieldElement, Language, ProofSystemCompiler,
 };
-use halo2_base::halo2_proofs::{
-    halo2curves::bn256::{Bn256, Fr, G1Affine},
-    plonk::{ProvingKey, VerifyingKey},
-    poly::kzg::commitment::ParamsKZG,
-    SerdeFormat,
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+use noir_halo2_backend_common::{
+    errors::{BackendError, Error},
+    noir_field_to_halo2_field,
+    utils::ensure_opcodes_supported,
 };
-use noir_halo2_backend_common::errors::BackendError;
 use std::marker::PhantomData;
 
 impl ProofSystemCompiler for AxiomHalo2 {
     type Error = BackendError;
 
+    /// Get the size of the circuit
     fn get_exact_circuit_size(&self, circuit: &NoirCircuit) -> Result<u32, BackendError> {
-        Ok(circuit.opcodes.len() as u32)
+        let translator = NoirHalo2Translator::<Fr> {
+            circuit: circuit.clone(),
+            witness_values: WitnessMap::new(),
+            _marker: PhantomData::<Fr>,
+        };
+
+        let dimension = DimensionMeasurement::measure(&translator, self.parallel_witness_gen)?;
+        let k = dimension.k();
+
+        Ok(1 << k)
     }
 
     fn preprocess(
         &self,
-        mut common_reference_string: &[u8],
+        common_reference_string: &[u8],
         circuit: &NoirCircuit,
     ) -> Result<(Vec<u8>, Vec<u8>), BackendError> {
+        ensure_opcodes_supported(circuit, |opcode| self.supports_opcode(opcode))?;
+
         let translator = NoirHalo2Translator::<Fr> {
             circuit: circuit.clone(),
             witness_values: WitnessMap::new(),
             _marker: PhantomData::<Fr>,
         };
 
-        let params =
-            ParamsKZG::<Bn256>::read_custom(&mut common_reference_string, SerdeFormat::RawBytes);
-        let (pk, vk) = halo2_keygen(&translator, &params);
+        let params = read_halo2_params(common_reference_string)?;
+        let (pk, vk, circuit_params) =
+            halo2_keygen(&translator, &params, self.parallel_witness_gen)?;
 
-        Ok((pk.to_bytes(SerdeFormat::RawBytes), vk.to_bytes(SerdeFormat::RawBytes)))
+        Ok((halo2_pk_to_bytes(&pk, &circuit_params), halo2_vk_to_bytes(&vk, &circuit_params)))
     }
 
     fn prove_with_pk(
         &self,
-        mut common_reference_string: &[u8],
+        common_reference_string: &[u8],
         circuit: &NoirCircuit,
         witness_values: WitnessMap,
         proving_key: &[u8],
         _is_recursive: bool,
     ) -> Result<Vec<u8>, BackendError> {
-        let params =
-            ParamsKZG::<Bn256>::read_custom(&mut common_reference_string, SerdeFormat::RawBytes);
+        ensure_opcodes_supported(circuit, |opcode| self.supports_opcode(opcode))?;
 
-        let pk = ProvingKey::<G1Affine>::from_bytes::<NoirHalo2Translator<Fr>>(
-            proving_key,
-            SerdeFormat::RawBytes,
-        )
-        .unwrap();
+        let params = read_halo2_params(common_reference_string)?;
+
+        let (pk, circuit_params) =
+            halo2_pk_from_bytes(proving_key).map_err(Error::ReadProvingKey)?;
 
         let translator = NoirHalo2Translator::<Fr> {
             circuit: circuit.clone(),
@@ -68,30 +84,38 @@ impl ProofSystemCompiler for AxiomHalo2 {
             _marker: PhantomData::<Fr>,
         };
 
-        let proof = halo2_prove(translator, &params, &pk);
+        let instance: Vec<Fr> = circuit
+            .public_inputs()
+            .indices()
+            .iter()
+            .map(|index| match translator.witness_values.get_index(*index) {
+                Some(val) => noir_field_to_halo2_field(*val),
+                None => noir_field_to_halo2_field(FieldElement::zero()),
+            })
+            .collect();
+
+        let proof = halo2_prove(translator, &params, &pk, &circuit_params, &instance[..])?;
 
         Ok(proof)
     }
 
     fn verify_with_vk(
         &self,
-        mut common_reference_string: &[u8],
+        common_reference_string: &[u8],
         proof: &[u8],
-        _public_inputs: WitnessMap,
+        public_inputs: WitnessMap,
         _circuit: &NoirCircuit,
         verification_key: &[u8],
         _is_recursive: bool,
     ) -> Result<bool, BackendError> {
-        let params =
-            ParamsKZG::<Bn256>::read_custom(&mut common_reference_string, SerdeFormat::RawBytes);
+        let params = read_halo2_params(common_reference_string)?;
+
+        let (vk, _) = halo2_vk_from_bytes(verification_key).map_err(Error::ReadVerificationKey)?;
 
-        let vk = VerifyingKey::<G1Affine>::from_bytes::<NoirHalo2Translator<Fr>>(
-            verification_key,
-            SerdeFormat::RawBytes,
-        )
-        .unwrap();
+        let instance: Vec<Fr> =
+            public_inputs.into_iter().map(|(_, el)| noir_field_to_halo2_field(el)).collect();
 
-        Ok(halo2_verify(&params, &vk, proof).is_ok())
+        Ok(halo2_verify(&params, &vk, proof, &instance[..]).is_ok())
     }
 
     fn np_language(&self) -> Language {
@@ -103,16 +127,17 @@ impl ProofSystemCompiler for AxiomHalo2 {
             Opcode::Arithmetic(_) => true,
             Opcode::Directive(_) | Opcode::Brillig(_) => true,
             Opcode::BlackBoxFuncCall(func) => match func.get_black_box_func() {
-                BlackBoxFunc::AND | BlackBoxFunc::RANGE => true,
+                BlackBoxFunc::AND
+                | BlackBoxFunc::RANGE
+                | BlackBoxFunc::SHA256
+                | BlackBoxFunc::Keccak256
+                | BlackBoxFunc::EcdsaSecp256k1
+                | BlackBoxFunc::EcdsaSecp256r1 => true,
 
                 BlackBoxFunc::XOR
-                | BlackBoxFunc::SHA256
                 | BlackBoxFunc::Blake2s
                 | BlackBoxFunc::Pedersen
                 | BlackBoxFunc::HashToField128Security
-                | BlackBoxFunc::EcdsaSecp256k1
-                | BlackBoxFunc::EcdsaSecp256r1
-                | BlackBoxFunc::Keccak256
                 | BlackBoxFunc::FixedBaseScalarMul
                 | BlackBoxFunc::RecursiveAggregation
                 | BlackBoxFunc::SchnorrVerify => false,
@@ -121,19 +146,34 @@ impl ProofSystemCompiler for AxiomHalo2 {
         }
     }
 
+    /// Encode a proof as the fields of its transcript: the public inputs,
+    /// then its points as the limbs of their coordinates and its scalars
     fn proof_as_fields(
         &self,
-        _proof: &[u8],
-        _public_inputs: WitnessMap,
+        proof: &[u8],
+        public_inputs: WitnessMap,
     ) -> Result<Vec<FieldElement>, Self::Error> {
-        panic!("vk_as_fields not supported in this backend");
+        let elements = proof_elements(proof).ok_or(BackendError::from(Error::MalformedProof))?;
+
+        let mut fields: Vec<FieldElement> = public_inputs.into_iter().map(|(_, el)| el).collect();
+        fields.extend(elements_to_fields(&elements).into_iter().map(halo2_field_to_noir_field));
+        Ok(fields)
     }
 
+    /// Encode a Verification Key as its size and commitments,
+    /// along with the hash it initializes transcripts with
     fn vk_as_fields(
         &self,
         _common_reference_string: &[u8],
-        _verification_key: &[u8],
+        verification_key: &[u8],
     ) -> Result<(Vec<FieldElement>, FieldElement), Self::Error> {
-        panic!("vk_as_fields not supported in this backend");
+        let (vk, _) = halo2_vk_from_bytes(verification_key).map_err(Error::ReadVerificationKey)?;
+
+        Ok((
+            vk_to_fields(&vk).into_iter().map(halo2_field_to_noir_field).collect(),
+            halo2_field_to_noir_field(vk.transcript_repr()),
+        ))
     }
 }
+
+noir_field_to_halo2_field!(Fr);
@@ -1,4 +1,5 @@
 use acvm::{acir::FieldElement, BlackBoxFunctionSolver, BlackBoxResolutionError};
+use halo2_base::halo2_proofs::halo2curves::{bn256::Fr, group::ff::PrimeField};
 
 use crate::AxiomHalo2;
 
@@ -28,3 +29,9 @@ impl BlackBoxFunctionSolver for AxiomHalo2 {
         Err(BlackBoxResolutionError::Unsupported(acvm::acir::BlackBoxFunc::FixedBaseScalarMul))
     }
 }
+
+pub(crate) fn halo2_field_to_noir_field(halo2_ele: Fr) -> FieldElement {
+    let mut bytes = halo2_ele.to_repr();
+    bytes.as_mut().reverse();
+    FieldElement::from_be_bytes_reduce(bytes.as_ref())
+}
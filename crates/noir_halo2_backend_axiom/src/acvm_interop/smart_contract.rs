@@ -1,12 +1,11 @@
-use crate::{circuit_translator::NoirHalo2Translator, AxiomHalo2};
+use crate::{halo2_params::read_halo2_params, halo2_plonk_api::halo2_vk_from_bytes, AxiomHalo2};
 use acvm::{acir::circuit::Circuit, SmartContract};
 use halo2_base::halo2_proofs::{
     halo2curves::bn256::{Bn256, Fq, Fr, G1Affine},
     plonk::VerifyingKey,
     poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
-    SerdeFormat,
 };
-use noir_halo2_backend_common::errors::BackendError;
+use noir_halo2_backend_common::errors::{BackendError, Error};
 use snark_verifier::{
     loader::evm::EvmLoader,
     pcs::kzg::{Gwc19, KzgAs},
@@ -21,7 +20,7 @@ fn gen_evm_verifier(
     params: &ParamsKZG<Bn256>,
     vk: &VerifyingKey<G1Affine>,
     num_instance: Vec<usize>,
-) -> String {
+) -> Result<String, Error> {
     let protocol = compile(params, vk, Config::kzg().with_num_instance(num_instance.clone()));
     let vk = (params.get_g()[0], params.g2(), params.s_g2()).into();
 
@@ -30,9 +29,10 @@ fn gen_evm_verifier(
     let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
 
     let instances = transcript.load_instances(num_instance);
-    let proof = PlonkVerifier::read_proof(&vk, &protocol, &instances, &mut transcript).unwrap();
-    PlonkVerifier::verify(&vk, &protocol, &instances, &proof).unwrap();
-    loader.yul_code()
+    let proof = PlonkVerifier::read_proof(&vk, &protocol, &instances, &mut transcript)
+        .map_err(|_| Error::MalformedProof)?;
+    PlonkVerifier::verify(&vk, &protocol, &instances, &proof).map_err(|_| Error::MalformedProof)?;
+    Ok(loader.yul_code())
 }
 
 impl SmartContract for AxiomHalo2 {
@@ -43,21 +43,14 @@ impl SmartContract for AxiomHalo2 {
         circuit: &Circuit,
         verification_key: &[u8],
     ) -> Result<String, Self::Error> {
-        let params = ParamsKZG::<Bn256>::read_custom(
-            &mut &(*common_reference_string),
-            SerdeFormat::RawBytes,
-        );
+        let params = read_halo2_params(common_reference_string)?;
 
         // Deserialize verification key
-        let vk = VerifyingKey::<G1Affine>::from_bytes::<NoirHalo2Translator<Fr>>(
-            verification_key,
-            SerdeFormat::RawBytes,
-        )
-        .unwrap();
+        let (vk, _) = halo2_vk_from_bytes(verification_key).map_err(Error::ReadVerificationKey)?;
 
         // get number of public inputs used in circuit
         let num_instance = circuit.public_inputs().0.len();
 
-        Ok(gen_evm_verifier(&params, &vk, vec![num_instance]))
+        Ok(gen_evm_verifier(&params, &vk, vec![num_instance])?)
     }
 }
@@ -35,6 +35,21 @@ impl<F: ScalarField> AssignedMap<F> {
         };
     }
 
+    /// Merge the assignments of another context, copy constraining the cells of
+    /// the witnesses assigned in both to the last cell assigned in this map
+    pub fn merge(&mut self, ctx: &mut Context<F>, other: AssignedMap<F>) {
+        for (witness, cells) in other {
+            if let Some(assigned) = self.get(&witness).and_then(|cells| cells.last()).copied() {
+                for cell in cells.iter() {
+                    ctx.constrain_equal(&assigned, cell);
+                }
+            }
+            for cell in cells {
+                self.insert(witness, cell);
+            }
+        }
+    }
+
     /**
      * Check if a given witness is stored as an assigned value.
      *   - if the witness key exists, return the last assigned value
@@ -0,0 +1,350 @@
+use super::{bits_to_num, xor};
+use halo2_base::{
+    gates::{GateChip, GateInstructions, RangeChip, RangeInstructions},
+    halo2_proofs::halo2curves::bn256::Fr,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// Number of message bytes absorbed per Keccak-f[1600] permutation
+const RATE: usize = 136;
+
+/// Keccak-f[1600] round constants
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rho rotation offsets of the lane at `(x, y)`, indexed by `x + 5 * y`
+const ROTATION_OFFSETS: [usize; 25] =
+    [0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14];
+
+/// A 64-bit lane as its little-endian boolean cells
+type Lane = Vec<AssignedValue<Fr>>;
+
+/// Keccak256 chip on the gates of a range chip.
+///
+/// Every 64-bit lane of the state is kept as its 64 boolean cells, so that the
+/// rotations of theta and rho are free and the steps of the permutation are
+/// xors and ands of bits.
+///
+/// The message is padded in circuit against a length cell, so that fixed and
+/// variable length inputs share the same constraints: the input is absorbed up
+/// to its maximal number of blocks and the digest is selected from the state
+/// after the block holding the end of the message.
+pub(crate) struct Keccak256Chip<'a> {
+    range: &'a RangeChip<Fr>,
+}
+
+impl<'a> Keccak256Chip<'a> {
+    pub(crate) fn new(range: &'a RangeChip<Fr>) -> Self {
+        Keccak256Chip { range }
+    }
+
+    fn gate(&self) -> &GateChip<Fr> {
+        self.range.gate()
+    }
+
+    /// Constrain the Keccak256 digest of the first `length` bytes of `message`,
+    /// returning the cells of its 32 bytes. `length` is constrained to be at
+    /// most the length of `message`, whose bytes up to `length` are range checked.
+    pub(crate) fn digest(
+        &self,
+        ctx: &mut Context<Fr>,
+        message: &[AssignedValue<Fr>],
+        length: AssignedValue<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let num_blocks = message.len() / RATE + 1;
+        let (blocks, last_block) = self.pad(ctx, message, length, num_blocks);
+
+        let zero = ctx.load_zero();
+        let mut state: Vec<Lane> = vec![vec![zero; 64]; 25];
+        let mut squeezed = Vec::with_capacity(num_blocks);
+        for (i, block) in blocks.chunks(RATE * 8).enumerate() {
+            for (k, lane) in block.chunks(64).enumerate() {
+                state[k] =
+                    if i == 0 { lane.to_vec() } else { self.xor_lanes(ctx, &state[k], lane) };
+            }
+            state = self.permute(ctx, state);
+
+            let digest: Vec<AssignedValue<Fr>> = state[..4]
+                .iter()
+                .flat_map(|lane| lane.chunks(8))
+                .map(|bits| bits_to_num(self.gate(), ctx, bits))
+                .collect();
+            squeezed.push(digest);
+        }
+
+        (0..32)
+            .map(|j| {
+                self.gate().select_by_indicator(
+                    ctx,
+                    squeezed.iter().map(|digest| digest[j]),
+                    last_block.clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// Pad the first `length` bytes of `message` with `0x01 0x00 .. 0x80` up to the end
+    /// of their last block, and zeros after it up to `num_blocks` blocks. Returns the
+    /// little-endian bits of the padded bytes and the indicator of the last block.
+    fn pad(
+        &self,
+        ctx: &mut Context<Fr>,
+        message: &[AssignedValue<Fr>],
+        length: AssignedValue<Fr>,
+        num_blocks: usize,
+    ) -> (Vec<AssignedValue<Fr>>, Vec<AssignedValue<Fr>>) {
+        let gate = self.gate();
+        let length_bits = (usize::BITS - message.len().leading_zeros()).max(1) as usize;
+        self.range.check_less_than_safe(ctx, length, message.len() as u64 + 1);
+        let (last_block, _) = self.range.div_mod(ctx, length, RATE as u64, length_bits);
+        let last_block = gate.idx_to_indicator(ctx, last_block, num_blocks);
+
+        let zero = ctx.load_zero();
+        let mut bits = Vec::with_capacity(num_blocks * RATE * 8);
+        for i in 0..num_blocks * RATE {
+            let index = QuantumCell::Constant(Fr::from(i as u64));
+            let mut byte = zero;
+            if i < message.len() {
+                let is_message = self.range.is_less_than(ctx, index, length, length_bits);
+                byte = gate.mul(ctx, message[i], is_message);
+            }
+            if i <= message.len() {
+                let is_end = gate.is_equal(ctx, index, length);
+                byte = gate.add(ctx, byte, is_end);
+            }
+            if i % RATE == RATE - 1 {
+                byte = gate.mul_add(
+                    ctx,
+                    last_block[i / RATE],
+                    QuantumCell::Constant(Fr::from(0x80)),
+                    byte,
+                );
+            }
+            bits.extend(gate.num_to_bits(ctx, byte, 8));
+        }
+
+        (bits, last_block)
+    }
+
+    fn xor_lanes(
+        &self,
+        ctx: &mut Context<Fr>,
+        a: &[AssignedValue<Fr>],
+        b: &[AssignedValue<Fr>],
+    ) -> Lane {
+        a.iter().zip(b).map(|(a, b)| xor(self.gate(), ctx, *a, *b)).collect()
+    }
+
+    /// Keccak-f[1600] permutation of the state
+    fn permute(&self, ctx: &mut Context<Fr>, mut state: Vec<Lane>) -> Vec<Lane> {
+        let gate = self.gate();
+        for round_constant in ROUND_CONSTANTS {
+            // theta
+            let c: Vec<Lane> = (0..5)
+                .map(|x| {
+                    (1..5).fold(state[x].clone(), |acc, y| {
+                        self.xor_lanes(ctx, &acc, &state[x + 5 * y])
+                    })
+                })
+                .collect();
+            let d: Vec<Lane> = (0..5)
+                .map(|x| self.xor_lanes(ctx, &c[(x + 4) % 5], &rotate_left(&c[(x + 1) % 5], 1)))
+                .collect();
+            for (i, lane) in state.iter_mut().enumerate() {
+                *lane = self.xor_lanes(ctx, lane, &d[i % 5]);
+            }
+
+            // rho and pi
+            let mut b = vec![Vec::new(); 25];
+            for x in 0..5 {
+                for y in 0..5 {
+                    b[y + 5 * ((2 * x + 3 * y) % 5)] =
+                        rotate_left(&state[x + 5 * y], ROTATION_OFFSETS[x + 5 * y]);
+                }
+            }
+
+            // chi
+            for x in 0..5 {
+                for y in 0..5 {
+                    state[x + 5 * y] = (0..64)
+                        .map(|z| {
+                            let not_and = gate.mul_not(
+                                ctx,
+                                b[(x + 1) % 5 + 5 * y][z],
+                                b[(x + 2) % 5 + 5 * y][z],
+                            );
+                            xor(gate, ctx, b[x + 5 * y][z], not_and)
+                        })
+                        .collect();
+                }
+            }
+
+            // iota
+            for z in 0..64 {
+                if round_constant >> z & 1 == 1 {
+                    state[0][z] = gate.not(ctx, state[0][z]);
+                }
+            }
+        }
+        state
+    }
+}
+
+/// Rotate a lane left by `n` bits, which moves its little-endian bits up
+fn rotate_left(lane: &[AssignedValue<Fr>], n: usize) -> Lane {
+    (0..64).map(|z| lane[(z + 64 - n) % 64]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Keccak256Chip;
+    use crate::halo2_plonk_api::{lookup_bits, MINIMUM_ROWS};
+    use halo2_base::{
+        gates::{
+            builder::{GateThreadBuilder, RangeWithInstanceCircuitBuilder},
+            GateInstructions, RangeChip,
+        },
+        halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr},
+    };
+    use std::env;
+
+    const K: u32 = 18;
+
+    fn run_test_vector(message: &[u8], length: usize, digest: &str) -> Result<(), Vec<String>> {
+        let range = RangeChip::<Fr>::default(lookup_bits(K));
+        let mut builder = GateThreadBuilder::mock();
+        let ctx = builder.main(0);
+        let message = ctx.assign_witnesses(message.iter().map(|byte| Fr::from(*byte as u64)));
+        let length = ctx.load_witness(Fr::from(length as u64));
+        let digest_cells = Keccak256Chip::new(&range).digest(ctx, &message, length);
+        // constrain the digest to the expected test vector
+        for (cell, byte) in digest_cells.iter().zip(hex::decode(digest).unwrap()) {
+            range.gate().assert_is_const(ctx, cell, &Fr::from(byte as u64));
+        }
+
+        builder.config(K as usize, Some(MINIMUM_ROWS));
+        env::set_var("LOOKUP_BITS", lookup_bits(K).to_string());
+        let circuit = RangeWithInstanceCircuitBuilder::mock(builder, vec![]);
+        let prover = MockProver::run(K, &circuit, vec![circuit.instance()]).unwrap();
+        prover.verify().map_err(|failures| failures.iter().map(|f| f.to_string()).collect())
+    }
+
+    #[test]
+    fn test_keccak256_empty_message() {
+        assert_eq!(
+            run_test_vector(
+                b"",
+                0,
+                "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_keccak256_abc() {
+        assert_eq!(
+            run_test_vector(
+                b"abc",
+                3,
+                "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_keccak256_two_blocks() {
+        assert_eq!(
+            run_test_vector(
+                &[b'a'; 200],
+                200,
+                "96ea54061def936c4be90b518992fdc6f12f535068a256229aca54267b4d084d"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_keccak256_variable_length() {
+        assert_eq!(
+            run_test_vector(
+                b"abcdefgh",
+                3,
+                "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_keccak256_variable_length_padding_block() {
+        // a full block of message, whose padding takes a block of its own
+        assert_eq!(
+            run_test_vector(
+                &[b'a'; 200],
+                136,
+                "a6c4d403279fe3e0af03729caada8374b5ca54d8065329a3ebcaeb4b60aa386e"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_keccak256_variable_length_second_block() {
+        // a message ending past the padding boundary of the first block
+        assert_eq!(
+            run_test_vector(
+                &[b'a'; 200],
+                140,
+                "371ae90cc19c78be5030630dae375dfa104146b9c4e752cabfda5c0b16448b2b"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_keccak256_wrong_digest() {
+        assert!(run_test_vector(
+            b"abc",
+            3,
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c46"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_keccak256_wrong_length() {
+        // the digest of the full message doesn't match that of its first bytes
+        assert!(run_test_vector(
+            &[b'a'; 200],
+            140,
+            "96ea54061def936c4be90b518992fdc6f12f535068a256229aca54267b4d084d"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,33 @@
+pub(crate) mod keccak256;
+pub(crate) mod sha256;
+
+use halo2_base::{
+    gates::{GateChip, GateInstructions},
+    halo2_proofs::halo2curves::bn256::Fr,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// Xor of two boolean cells, `(1 - 2a) * b + a`
+pub(crate) fn xor(
+    gate: &GateChip<Fr>,
+    ctx: &mut Context<Fr>,
+    a: AssignedValue<Fr>,
+    b: AssignedValue<Fr>,
+) -> AssignedValue<Fr> {
+    let one_minus_two_a =
+        gate.mul_add(ctx, a, QuantumCell::Constant(-Fr::from(2)), QuantumCell::Constant(Fr::one()));
+    gate.mul_add(ctx, one_minus_two_a, b, a)
+}
+
+/// Compose little-endian boolean cells into the number they encode
+pub(crate) fn bits_to_num(
+    gate: &GateChip<Fr>,
+    ctx: &mut Context<Fr>,
+    bits: &[AssignedValue<Fr>],
+) -> AssignedValue<Fr> {
+    gate.inner_product(
+        ctx,
+        bits.to_vec(),
+        (0..bits.len()).map(|i| QuantumCell::Constant(Fr::from(1 << i))),
+    )
+}
@@ -0,0 +1,278 @@
+use super::{bits_to_num, xor};
+use halo2_base::{
+    gates::{GateChip, GateInstructions},
+    halo2_proofs::halo2curves::bn256::Fr,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// SHA256 round constants
+const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA256 initial hash values
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Bit length of a sum of at most 5 words and a constant, whose bits above
+/// the first 32 are the carry of the addition modulo `2^32`
+const SUM_BITS: usize = 35;
+
+/// Shifts xor-ed together by the sigma functions
+#[derive(Clone, Copy, Debug)]
+enum Shift {
+    Rotr(usize),
+    Shr(usize),
+}
+
+const BIG_SIGMA0: [Shift; 3] = [Shift::Rotr(2), Shift::Rotr(13), Shift::Rotr(22)];
+const BIG_SIGMA1: [Shift; 3] = [Shift::Rotr(6), Shift::Rotr(11), Shift::Rotr(25)];
+const SMALL_SIGMA0: [Shift; 3] = [Shift::Rotr(7), Shift::Rotr(18), Shift::Shr(3)];
+const SMALL_SIGMA1: [Shift; 3] = [Shift::Rotr(17), Shift::Rotr(19), Shift::Shr(10)];
+
+/// A 32-bit word as its little-endian boolean cells
+type Word = Vec<AssignedValue<Fr>>;
+
+/// SHA256 chip on the gates of a gate chip.
+///
+/// Every 32-bit word is kept as its 32 boolean cells, so that rotations and
+/// shifts are free and the sigma, choose and majority functions are xors and
+/// selections of bits. Modular additions compose the words into numbers and
+/// decompose their sum back into bits, dropping the carry.
+pub(crate) struct Sha256Chip<'a> {
+    gate: &'a GateChip<Fr>,
+}
+
+impl<'a> Sha256Chip<'a> {
+    pub(crate) fn new(gate: &'a GateChip<Fr>) -> Self {
+        Sha256Chip { gate }
+    }
+
+    /// Constrain the SHA256 digest of `message`, returning the cells of its 32
+    /// bytes. The bytes of `message` are range checked.
+    pub(crate) fn digest(
+        &self,
+        ctx: &mut Context<Fr>,
+        message: &[AssignedValue<Fr>],
+    ) -> Vec<AssignedValue<Fr>> {
+        let zero = ctx.load_zero();
+        let one = ctx.load_constant(Fr::one());
+
+        // the length is known, so the padding is made of constants
+        let mut bytes: Vec<Vec<AssignedValue<Fr>>> =
+            message.iter().map(|byte| self.gate.num_to_bits(ctx, *byte, 8)).collect();
+        let mut padding = vec![0x80u8];
+        padding.resize((119 - message.len() % 64) % 64 + 1, 0);
+        padding.extend((message.len() as u64 * 8).to_be_bytes());
+        bytes.extend(
+            padding
+                .into_iter()
+                .map(|byte| (0..8).map(|i| if byte >> i & 1 == 1 { one } else { zero }).collect()),
+        );
+
+        let mut hash: Vec<Word> =
+            IV.iter().map(|value| self.constant_word(*value, zero, one)).collect();
+        for block in bytes.chunks(64) {
+            // big-endian words, whose bits are those of their last byte first
+            let words: Vec<Word> = block
+                .chunks(4)
+                .map(|word| word.iter().rev().flatten().copied().collect())
+                .collect();
+            hash = self.compress(ctx, &hash, words, zero);
+        }
+
+        hash.iter()
+            .flat_map(|word| word.chunks(8).rev())
+            .map(|bits| bits_to_num(self.gate, ctx, bits))
+            .collect()
+    }
+
+    /// Compression of a block of 16 words into the hash
+    fn compress(
+        &self,
+        ctx: &mut Context<Fr>,
+        hash: &[Word],
+        mut schedule: Vec<Word>,
+        zero: AssignedValue<Fr>,
+    ) -> Vec<Word> {
+        for t in 16..64 {
+            let s0 = self.sigma(ctx, &SMALL_SIGMA0, &schedule[t - 15], zero);
+            let s1 = self.sigma(ctx, &SMALL_SIGMA1, &schedule[t - 2], zero);
+            let word = self.add(ctx, &[&s1, &schedule[t - 7], &s0, &schedule[t - 16]], 0);
+            schedule.push(word);
+        }
+
+        let mut vars = hash.to_vec();
+        for t in 0..64 {
+            let (a, b, c, d, e, f, g, h) =
+                (&vars[0], &vars[1], &vars[2], &vars[3], &vars[4], &vars[5], &vars[6], &vars[7]);
+            let s1 = self.sigma(ctx, &BIG_SIGMA1, e, zero);
+            let ch: Word = (0..32).map(|i| self.gate.select(ctx, f[i], g[i], e[i])).collect();
+            let t1 = self.add(ctx, &[h, &s1, &ch, &schedule[t]], ROUND_CONSTANTS[t]);
+            let s0 = self.sigma(ctx, &BIG_SIGMA0, a, zero);
+            let maj: Word = (0..32)
+                .map(|i| {
+                    let or = self.gate.or(ctx, b[i], c[i]);
+                    let and = self.gate.and(ctx, b[i], c[i]);
+                    self.gate.select(ctx, or, and, a[i])
+                })
+                .collect();
+
+            let new_e = self.add(ctx, &[d, &t1], 0);
+            let new_a = self.add(ctx, &[&t1, &s0, &maj], 0);
+            vars = vec![
+                new_a,
+                a.clone(),
+                b.clone(),
+                c.clone(),
+                new_e,
+                e.clone(),
+                f.clone(),
+                g.clone(),
+            ];
+        }
+
+        hash.iter().zip(&vars).map(|(h, v)| self.add(ctx, &[h, v], 0)).collect()
+    }
+
+    /// Xor of the three shifts of a word
+    fn sigma(
+        &self,
+        ctx: &mut Context<Fr>,
+        shifts: &[Shift; 3],
+        word: &[AssignedValue<Fr>],
+        zero: AssignedValue<Fr>,
+    ) -> Word {
+        let shifted: Vec<Word> = shifts
+            .iter()
+            .map(|shift| {
+                (0..32)
+                    .map(|i| match *shift {
+                        Shift::Rotr(n) => word[(i + n) % 32],
+                        Shift::Shr(n) if i + n < 32 => word[i + n],
+                        Shift::Shr(_) => zero,
+                    })
+                    .collect()
+            })
+            .collect();
+        (0..32)
+            .map(|i| {
+                let x = xor(self.gate, ctx, shifted[0][i], shifted[1][i]);
+                xor(self.gate, ctx, x, shifted[2][i])
+            })
+            .collect()
+    }
+
+    /// Sum of words and a constant modulo `2^32`
+    fn add(&self, ctx: &mut Context<Fr>, words: &[&Word], constant: u32) -> Word {
+        let bits: Vec<AssignedValue<Fr>> =
+            words.iter().flat_map(|word| word.iter()).copied().collect();
+        let powers =
+            words.iter().flat_map(|_| (0..32).map(|i| QuantumCell::Constant(Fr::from(1 << i))));
+        let sum = self.gate.inner_product(ctx, bits, powers);
+        let sum = self.gate.add(ctx, sum, QuantumCell::Constant(Fr::from(constant as u64)));
+        let mut bits = self.gate.num_to_bits(ctx, sum, SUM_BITS);
+        bits.truncate(32);
+        bits
+    }
+
+    fn constant_word(&self, value: u32, zero: AssignedValue<Fr>, one: AssignedValue<Fr>) -> Word {
+        (0..32).map(|i| if value >> i & 1 == 1 { one } else { zero }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sha256Chip;
+    use crate::halo2_plonk_api::{lookup_bits, MINIMUM_ROWS};
+    use halo2_base::{
+        gates::{
+            builder::{GateThreadBuilder, RangeWithInstanceCircuitBuilder},
+            GateChip, GateInstructions,
+        },
+        halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr},
+    };
+    use std::env;
+
+    const K: u32 = 17;
+
+    fn run_test_vector(message: &[u8], digest: &str) -> Result<(), Vec<String>> {
+        let gate = GateChip::<Fr>::default();
+        let mut builder = GateThreadBuilder::mock();
+        let ctx = builder.main(0);
+        let message = ctx.assign_witnesses(message.iter().map(|byte| Fr::from(*byte as u64)));
+        let digest_cells = Sha256Chip::new(&gate).digest(ctx, &message);
+        // constrain the digest to the expected test vector
+        for (cell, byte) in digest_cells.iter().zip(hex::decode(digest).unwrap()) {
+            gate.assert_is_const(ctx, cell, &Fr::from(byte as u64));
+        }
+
+        builder.config(K as usize, Some(MINIMUM_ROWS));
+        env::set_var("LOOKUP_BITS", lookup_bits(K).to_string());
+        let circuit = RangeWithInstanceCircuitBuilder::mock(builder, vec![]);
+        let prover = MockProver::run(K, &circuit, vec![circuit.instance()]).unwrap();
+        prover.verify().map_err(|failures| failures.iter().map(|f| f.to_string()).collect())
+    }
+
+    #[test]
+    fn test_sha256_empty_message() {
+        assert_eq!(
+            run_test_vector(
+                b"",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_sha256_abc() {
+        assert_eq!(
+            run_test_vector(
+                b"abc",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_sha256_padding_block() {
+        // the length doesn't fit after the message, so the padding takes a block of its own
+        assert_eq!(
+            run_test_vector(
+                &[b'a'; 56],
+                "b35439a4ac6f0948b6d6f9e3c6af0f5f590ce20f1bde7090ef7970686ec6738a"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_sha256_two_blocks() {
+        assert_eq!(
+            run_test_vector(
+                &[b'a'; 100],
+                "2816597888e4a0d3a36b82b83316ab32680eb8f00f8cd3b904d681246d285a0e"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_sha256_wrong_digest() {
+        assert!(run_test_vector(
+            b"abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ae"
+        )
+        .is_err());
+    }
+}
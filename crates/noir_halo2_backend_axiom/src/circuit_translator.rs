@@ -5,12 +5,15 @@ use acvm::acir::{
     BlackBoxFunc,
 };
 use core::panic;
-use halo2_base::halo2_proofs::{
-    circuit::SimpleFloorPlanner,
-    halo2curves::bn256::Fr,
-    plonk::{Circuit as Halo2PlonkCircuit, ConstraintSystem},
+use halo2_base::{
+    gates::builder::GateThreadBuilder,
+    halo2_proofs::halo2curves::{
+        bn256::Fr, secp256k1::Secp256k1Affine, secp256r1::Secp256r1Affine,
+    },
+    AssignedValue, Context,
 };
 use noir_halo2_backend_common::errors::Error;
+use rayon::prelude::*;
 use std::marker::PhantomData;
 
 #[derive(Clone, Default)]
@@ -20,174 +23,215 @@ pub struct NoirHalo2Translator<Fr> {
     pub _marker: PhantomData<Fr>,
 }
 
-impl Halo2PlonkCircuit<Fr> for NoirHalo2Translator<Fr> {
-    type Config = PlonkConfig;
-    type FloorPlanner = SimpleFloorPlanner;
-
-    fn without_witnesses(&self) -> Self {
-        Self::default()
-    }
-
-    fn configure(meta: &mut ConstraintSystem<Fr>) -> PlonkConfig {
-        meta.set_minimum_degree(5);
-
-        PlonkConfig::configure(meta)
-    }
-
-    fn synthesize(
+impl NoirHalo2Translator<Fr> {
+    /// Constrain the opcodes in the main context of the thread builder, so that
+    /// they share the assignments of their witnesses and are laid out together.
+    /// Returns the cells of the public inputs, to be exposed as instances.
+    ///
+    /// With `parallel_witness_gen`, the black box functions which dominate the
+    /// witness generation are instead constrained in a thread of their own each,
+    /// generated in parallel. Their assignments are merged into those of the main
+    /// context, which copy constrains the witnesses they share.
+    pub(crate) fn build(
         &self,
-        config: Self::Config,
-        _layouter: impl halo2_base::halo2_proofs::circuit::Layouter<Fr>,
-    ) -> Result<(), halo2_base::halo2_proofs::plonk::Error> {
-        let mut witness_assignments = AssignedMap::<Fr>::new();
+        builder: &mut GateThreadBuilder<Fr>,
+        config: &PlonkConfig,
+        parallel_witness_gen: bool,
+    ) -> Result<Vec<AssignedValue<Fr>>, Error> {
+        let (threaded, serial): (Vec<&Opcode>, Vec<&Opcode>) = self
+            .circuit
+            .opcodes
+            .iter()
+            .partition(|opcode| parallel_witness_gen && is_threaded(opcode));
 
-        // let cs: StandardPlonk<Fr> = StandardPlonk::new(config.clone());
-        for gate in self.circuit.opcodes.iter() {
-            match gate {
-                Opcode::Arithmetic(expression) => {
-                    self.add_arithmetic_constrains(expression, &config, &mut witness_assignments);
-                }
-                Opcode::BlackBoxFuncCall(gadget_call) => {
-                    match gadget_call {
-                        BlackBoxFuncCall::RANGE { input } => self.add_range_constrain(
-                            input.witness,
-                            input.num_bits,
-                            &config,
-                            &mut witness_assignments,
-                        ),
-                        BlackBoxFuncCall::AND { lhs, rhs, output }
-                        | BlackBoxFuncCall::XOR { lhs, rhs, output } => {
-                            assert_eq!(lhs.num_bits, rhs.num_bits);
+        let mut witness_assignments = AssignedMap::<Fr>::new();
+        let ctx = builder.main(0);
+        for opcode in serial {
+            self.add_opcode_constrains(opcode, config, ctx, &mut witness_assignments)?;
+        }
 
-                            match gadget_call {
-                                BlackBoxFuncCall::AND { .. } => self.add_and_constrain(
-                                    lhs.witness,
-                                    rhs.witness,
-                                    *output,
-                                    &config,
-                                    &mut witness_assignments,
-                                ),
-                                BlackBoxFuncCall::XOR { .. } => self.add_xor_constrain(
-                                    lhs.witness,
-                                    rhs.witness,
-                                    *output,
-                                    &config,
-                                    &mut witness_assignments,
-                                ),
-                                _ => unreachable!("expected either an AND or XOR opcode"),
-                            }
-                        }
-                        BlackBoxFuncCall::SHA256 { .. } => {
-                            panic!("sha256 has not yet been implemented")
-                        }
-                        BlackBoxFuncCall::Blake2s { .. } => {
-                            panic!("blake2s has not yet been implemented")
-                        }
-                        BlackBoxFuncCall::SchnorrVerify { .. } => {
-                            panic!("schnorrverify has not yet been implemented")
-                        }
-                        BlackBoxFuncCall::Pedersen { .. } => {
-                            panic!("pedersen has not yet been implemented")
-                        }
-                        BlackBoxFuncCall::HashToField128Security { .. } => {
-                            panic!("hash to field has not yet been implemented")
-                        }
-                        BlackBoxFuncCall::EcdsaSecp256k1 {
-                            public_key_x: public_key_x_inputs,
-                            public_key_y: public_key_y_inputs,
-                            signature: signature_inputs,
-                            hashed_message: hashed_message_inputs,
-                            output,
-                        } => {
-                            // public key x
-                            let mut public_key_x_inputs = public_key_x_inputs.iter();
-                            let mut public_key_x = Vec::new();
-                            for (i, pkx) in public_key_x.iter_mut().enumerate() {
-                                let x_byte = public_key_x_inputs
-                                    .next()
-                                    .ok_or_else(|| Error::MalformedBlackBoxFunc(
-                                        BlackBoxFunc::EcdsaSecp256k1,
-                                        format!("Missing rest of `x` component for public key. Tried to get byte {i} but failed"),
-                                    )).unwrap();
-                                let x_byte_index = x_byte.witness;
-                                *pkx = x_byte_index;
-                            }
+        // the thread ids are taken in the order of the opcodes, and the threads
+        // collected in that order, so that keygen and proving lay them out alike
+        let witness_gen_only = builder.witness_gen_only();
+        let thread_ids: Vec<usize> = threaded.iter().map(|_| builder.get_new_thread_id()).collect();
+        let threads = threaded
+            .into_par_iter()
+            .zip(thread_ids)
+            .map(|(opcode, thread_id)| {
+                let mut ctx = Context::new(witness_gen_only, thread_id);
+                let mut thread_assignments = AssignedMap::<Fr>::new();
+                self.add_opcode_constrains(opcode, config, &mut ctx, &mut thread_assignments)?;
+                Ok((ctx, thread_assignments))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
 
-                            // public key y
-                            let mut public_key_y_inputs = public_key_y_inputs.iter();
-                            let mut public_key_y = Vec::new();
-                            for (i, pky) in public_key_y.iter_mut().enumerate() {
-                                let y_byte = public_key_y_inputs
-                                    .next()
-                                    .ok_or_else(|| Error::MalformedBlackBoxFunc(
-                                        BlackBoxFunc::EcdsaSecp256k1,
-                                        format!("Missing rest of `y` component for public key. Tried to get byte {i} but failed"),
-                                    )).unwrap();
-                                let y_byte_index = y_byte.witness;
-                                *pky = y_byte_index;
-                            }
+        let mut thread_maps = Vec::with_capacity(threads.len());
+        for (thread, thread_assignments) in threads {
+            builder.threads[0].push(thread);
+            thread_maps.push(thread_assignments);
+        }
+        let ctx = builder.main(0);
+        for thread_assignments in thread_maps {
+            witness_assignments.merge(ctx, thread_assignments);
+        }
 
-                            // signature
-                            let mut signature_inputs = signature_inputs.iter();
-                            let mut signature = Vec::new();
-                            for (i, sig) in signature.iter_mut().enumerate() {
-                                let sig_byte =
-                                    signature_inputs.next().ok_or_else(|| Error::MalformedBlackBoxFunc(
-                                        BlackBoxFunc::EcdsaSecp256k1,
-                                        format!("Missing rest of signature. Tried to get byte {i} but failed"),
-                                    )).unwrap();
-                                let sig_byte_index = sig_byte.witness;
-                                *sig = sig_byte_index;
-                            }
+        Ok(self.expose_public(ctx, &mut witness_assignments))
+    }
 
-                            // The rest of the input is the message
-                            let mut hashed_message = Vec::new();
-                            for msg in hashed_message_inputs.iter() {
-                                let msg_byte_index = msg.witness;
-                                hashed_message.push(msg_byte_index);
-                            }
+    fn add_opcode_constrains(
+        &self,
+        opcode: &Opcode,
+        config: &PlonkConfig,
+        ctx: &mut Context<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), Error> {
+        match opcode {
+            Opcode::Arithmetic(expression) => {
+                self.add_arithmetic_constrains(expression, config, ctx, witness_assignments);
+            }
+            Opcode::BlackBoxFuncCall(gadget_call) => {
+                match gadget_call {
+                    BlackBoxFuncCall::RANGE { input } => self.add_range_constrain(
+                        input.witness,
+                        input.num_bits,
+                        config,
+                        ctx,
+                        witness_assignments,
+                    ),
+                    BlackBoxFuncCall::AND { lhs, rhs, output }
+                    | BlackBoxFuncCall::XOR { lhs, rhs, output } => {
+                        assert_eq!(lhs.num_bits, rhs.num_bits);
 
-                            self.add_ecdsa_secp256k1_constrain(
-                                hashed_message,
-                                signature,
-                                public_key_x,
-                                public_key_y,
+                        match gadget_call {
+                            BlackBoxFuncCall::AND { .. } => self.add_and_constrain(
+                                lhs,
+                                rhs,
                                 *output,
-                                &config,
-                            );
-                        }
-                        BlackBoxFuncCall::EcdsaSecp256r1 { .. } => {
-                            todo!()
-                        }
-                        BlackBoxFuncCall::FixedBaseScalarMul { .. } => {
-                            todo!()
-                        }
-                        BlackBoxFuncCall::Keccak256 { inputs: _, outputs: _ } => {
-                            panic!("keccak256 has not yet been implemented")
+                                config,
+                                ctx,
+                                witness_assignments,
+                            ),
+                            BlackBoxFuncCall::XOR { .. } => self.add_xor_constrain(
+                                lhs,
+                                rhs,
+                                *output,
+                                config,
+                                ctx,
+                                witness_assignments,
+                            ),
+                            _ => unreachable!("expected either an AND or XOR opcode"),
                         }
-                        BlackBoxFuncCall::Keccak256VariableLength {
-                            inputs: _,
-                            var_message_size: _,
-                            outputs: _,
-                        } => todo!(),
-                        BlackBoxFuncCall::RecursiveAggregation {
-                            verification_key: _,
-                            proof: _,
-                            public_inputs: _,
-                            key_hash: _,
-                            input_aggregation_object: _,
-                            output_aggregation_object: _,
-                        } => todo!(),
-                    };
-                }
-                Opcode::Directive(_) | Opcode::Brillig(_) => {
-                    // Directives are only needed by the pwg
-                }
-                Opcode::Block(_) | Opcode::ROM(_) | Opcode::RAM(_) => {
-                    todo!()
-                }
+                    }
+                    BlackBoxFuncCall::SHA256 { inputs, outputs } => self.add_sha256_constrain(
+                        inputs,
+                        outputs,
+                        config,
+                        ctx,
+                        witness_assignments,
+                    )?,
+                    BlackBoxFuncCall::Blake2s { .. } => {
+                        panic!("blake2s has not yet been implemented")
+                    }
+                    BlackBoxFuncCall::SchnorrVerify { .. } => {
+                        panic!("schnorrverify has not yet been implemented")
+                    }
+                    BlackBoxFuncCall::Pedersen { .. } => {
+                        panic!("pedersen has not yet been implemented")
+                    }
+                    BlackBoxFuncCall::HashToField128Security { .. } => {
+                        panic!("hash to field has not yet been implemented")
+                    }
+                    BlackBoxFuncCall::EcdsaSecp256k1 {
+                        public_key_x,
+                        public_key_y,
+                        signature,
+                        hashed_message,
+                        output,
+                    } => self.add_ecdsa_constrain::<Secp256k1Affine>(
+                        BlackBoxFunc::EcdsaSecp256k1,
+                        public_key_x,
+                        public_key_y,
+                        signature,
+                        hashed_message,
+                        *output,
+                        config,
+                        ctx,
+                        witness_assignments,
+                    )?,
+                    BlackBoxFuncCall::EcdsaSecp256r1 {
+                        public_key_x,
+                        public_key_y,
+                        signature,
+                        hashed_message,
+                        output,
+                    } => self.add_ecdsa_constrain::<Secp256r1Affine>(
+                        BlackBoxFunc::EcdsaSecp256r1,
+                        public_key_x,
+                        public_key_y,
+                        signature,
+                        hashed_message,
+                        *output,
+                        config,
+                        ctx,
+                        witness_assignments,
+                    )?,
+                    BlackBoxFuncCall::FixedBaseScalarMul { .. } => {
+                        todo!()
+                    }
+                    BlackBoxFuncCall::Keccak256 { inputs, outputs } => self
+                        .add_keccak256_constrain(
+                            inputs,
+                            None,
+                            outputs,
+                            config,
+                            ctx,
+                            witness_assignments,
+                        )?,
+                    BlackBoxFuncCall::Keccak256VariableLength {
+                        inputs,
+                        var_message_size,
+                        outputs,
+                    } => self.add_keccak256_constrain(
+                        inputs,
+                        Some(var_message_size),
+                        outputs,
+                        config,
+                        ctx,
+                        witness_assignments,
+                    )?,
+                    BlackBoxFuncCall::RecursiveAggregation {
+                        verification_key: _,
+                        proof: _,
+                        public_inputs: _,
+                        key_hash: _,
+                        input_aggregation_object: _,
+                        output_aggregation_object: _,
+                    } => todo!(),
+                };
+            }
+            Opcode::Directive(_) | Opcode::Brillig(_) => {
+                // Directives are only needed by the pwg
+            }
+            Opcode::Block(_) | Opcode::ROM(_) | Opcode::RAM(_) => {
+                todo!()
             }
         }
         Ok(())
     }
 }
+
+/// Whether an opcode is constrained in a thread of its own when generating
+/// witnesses in parallel: the black box functions of many constraints, which
+/// only share their inputs and outputs with the rest of the circuit
+fn is_threaded(opcode: &Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::BlackBoxFuncCall(
+            BlackBoxFuncCall::SHA256 { .. }
+                | BlackBoxFuncCall::Keccak256 { .. }
+                | BlackBoxFuncCall::Keccak256VariableLength { .. }
+                | BlackBoxFuncCall::EcdsaSecp256k1 { .. }
+                | BlackBoxFuncCall::EcdsaSecp256r1 { .. }
+        )
+    )
+}
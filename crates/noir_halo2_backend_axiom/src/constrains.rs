@@ -1,93 +1,90 @@
 use super::{
-    assigned_map::AssignedMap, circuit_translator::NoirHalo2Translator,
+    assigned_map::AssignedMap,
+    chips::{keccak256::Keccak256Chip, sha256::Sha256Chip},
+    circuit_translator::NoirHalo2Translator,
     halo2_plonk_api::PlonkConfig,
 };
 use acvm::{
-    acir::native_types::{Expression, Witness},
+    acir::{
+        circuit::opcodes::FunctionInput,
+        native_types::{Expression, Witness},
+        BlackBoxFunc,
+    },
     FieldElement,
 };
 use halo2_base::{
-    gates::{GateInstructions, RangeChip, RangeInstructions},
-    halo2_proofs::halo2curves::{
-        bn256::Fr,
-        secp256k1::{Fp, Fq, Secp256k1Affine},
-        CurveAffine,
-    },
+    gates::{GateInstructions, RangeInstructions},
+    halo2_proofs::halo2curves::{bn256::Fr, group::ff::Field, CurveAffineExt},
     AssignedValue, Context, QuantumCell,
 };
 use halo2_ecc::{
-    ecc::{ecdsa::ecdsa_verify_no_pubkey_check, EccChip},
-    fields::FieldChip,
-    secp256k1::{FpChip, FqChip},
-};
-use noir_halo2_backend_common::{
-    impl_noir_field_to_secp255k1_field_conversion, noir_field_to_halo2_field,
+    bigint::{CRTInteger, OverflowInteger, ProperCrtUint},
+    ecc::{ecdsa::ecdsa_verify_no_pubkey_check, EcPoint, EccChip},
+    fields::{fp::FpChip, FieldChip, PrimeField},
 };
-use std::slice::Iter;
+use noir_halo2_backend_common::{errors::Error, noir_field_to_halo2_field};
+use num_bigint::{BigInt, Sign};
+
+/// Bit length of the limbs of the non-native field elements of the ECDSA chips
+const ECDSA_LIMB_BITS: usize = 88;
+/// Number of limbs of the non-native field elements of the ECDSA chips
+const ECDSA_NUM_LIMBS: usize = 3;
+/// Number of bytes of a limb, which are composed into it
+const BYTES_PER_LIMB: usize = ECDSA_LIMB_BITS / 8;
+/// Window bit length of the scalar multiplications of the ECDSA verification
+const ECDSA_WINDOW_BITS: usize = 4;
 
 impl NoirHalo2Translator<Fr> {
     pub(crate) fn add_arithmetic_constrains(
         &self,
         gate: &Expression,
         config: &PlonkConfig,
+        ctx: &mut Context<Fr>,
         witness_assignments: &mut AssignedMap<Fr>,
     ) {
-        let mut ctx: Context<Fr> = Context::<Fr>::new(false, 0);
-
-        // store the constrained output of multiplied terms
-        let mut solution: AssignedValue<Fr>;
-
-        if !gate.mul_terms.is_empty() {
-            // if there is a mul gate, take mul term selector, left, right, and constant then
-            // compute with gates: mul gate: selector * left = intermediate
-            // mul_add gate: intermediate * right + constant = output
-            let mul_term = &gate.mul_terms[0];
+        // coefficients are constants, fixed by the circuit rather than the prover
+        let mut solution = ctx.load_constant(noir_field_to_halo2_field(gate.q_c));
 
+        for mul_term in &gate.mul_terms {
             // assign terms or get existing assignnments
-            let w_l = &witness_assignments.get_or_assign(
-                &mut ctx,
+            let w_l = witness_assignments.get_or_assign(
+                ctx,
                 &mul_term.1,
                 noir_field_to_halo2_field(
                     *self.witness_values.get(&mul_term.1).unwrap_or(&FieldElement::zero()),
                 ),
             );
-            let w_r = &witness_assignments.get_or_assign(
-                &mut ctx,
+            let w_r = witness_assignments.get_or_assign(
+                ctx,
                 &mul_term.2,
                 noir_field_to_halo2_field(
-                    *self.witness_values.get(&mul_term.1).unwrap_or(&FieldElement::zero()),
+                    *self.witness_values.get(&mul_term.2).unwrap_or(&FieldElement::zero()),
                 ),
             );
 
-            // get coefficient/ selector term
-            let coefficient = QuantumCell::Witness(noir_field_to_halo2_field(mul_term.0));
-            // multiply coefficient / selector by left term
-            let intermediate = config.gate_chip.mul(&mut ctx, coefficient, *w_l);
-            // multiply product of coefficient and left term by right term, then add constant term
-            let c = QuantumCell::Witness(noir_field_to_halo2_field(gate.q_c));
-
-            solution = config.gate_chip.mul_add(&mut ctx, intermediate, *w_r, c);
-        } else {
-            // otherwise just assign the constant term
-            solution = ctx.load_witness(noir_field_to_halo2_field(gate.q_c));
+            // multiply coefficient by left term
+            let coefficient = QuantumCell::Constant(noir_field_to_halo2_field(mul_term.0));
+            let intermediate = config.gate_chip.mul(ctx, coefficient, w_l);
+            // multiply by right term & add to existing solution value
+            solution = config.gate_chip.mul_add(ctx, intermediate, w_r, solution);
         }
 
         for term in &gate.linear_combinations {
             // get term selector and witness
-            let coefficient = QuantumCell::Witness(noir_field_to_halo2_field(term.0));
-            let variable = &witness_assignments.get_or_assign(
-                &mut ctx,
+            let coefficient = QuantumCell::Constant(noir_field_to_halo2_field(term.0));
+            let variable = witness_assignments.get_or_assign(
+                ctx,
                 &term.1,
                 noir_field_to_halo2_field(
                     *self.witness_values.get(&term.1).unwrap_or(&FieldElement::zero()),
                 ),
             );
             // multiply to get term value & add to existing solution value
-            solution = config.gate_chip.mul_add(&mut ctx, coefficient, *variable, solution);
+            solution = config.gate_chip.mul_add(ctx, coefficient, variable, solution);
         }
 
-        // constrain the solution to the output to be equal to 0
-        config.gate_chip.is_zero(&mut ctx, solution);
+        // constrain the solution to be equal to 0
+        config.gate_chip.assert_is_const(ctx, &solution, &Fr::zero());
     }
 
     pub(crate) fn add_range_constrain(
@@ -95,148 +92,384 @@ impl NoirHalo2Translator<Fr> {
         witness: Witness,
         num_bits: u32,
         config: &PlonkConfig,
+        ctx: &mut Context<Fr>,
         witness_assignments: &mut AssignedMap<Fr>,
     ) {
-        let mut ctx = Context::<Fr>::new(false, 0);
-
         // assign x or get existing assignnment
         let x = &witness_assignments.get_or_assign(
-            &mut ctx,
+            ctx,
             &witness,
             noir_field_to_halo2_field(
                 *self.witness_values.get(&witness).unwrap_or(&FieldElement::zero()),
             ),
         );
 
-        config.range_chip.range_check(&mut ctx, *x, num_bits as usize);
+        config.range_chip.range_check(ctx, *x, num_bits as usize);
     }
 
     pub(crate) fn add_and_constrain(
         &self,
-        lhs: Witness,
-        rhs: Witness,
+        lhs: &FunctionInput,
+        rhs: &FunctionInput,
         output: Witness,
         config: &PlonkConfig,
+        ctx: &mut Context<Fr>,
         witness_assignments: &mut AssignedMap<Fr>,
     ) {
-        let mut ctx = Context::<Fr>::new(false, 0);
+        let (lhs_bits, rhs_bits) =
+            self.assign_bitwise_operands(lhs, rhs, config, ctx, witness_assignments);
 
-        // assign lhs, rhs, output or get existing assignnments
-        let lhs_v = &witness_assignments.get_or_assign(
-            &mut ctx,
-            &lhs,
-            noir_field_to_halo2_field(
-                *self.witness_values.get(&lhs).unwrap_or(&FieldElement::zero()),
-            ),
-        );
-        let rhs_v = &witness_assignments.get_or_assign(
-            &mut ctx,
-            &rhs,
-            noir_field_to_halo2_field(
-                *self.witness_values.get(&rhs).unwrap_or(&FieldElement::zero()),
-            ),
-        );
-        let output_v = &witness_assignments.get_or_assign(
-            &mut ctx,
+        // a & b = a * b on bits
+        let output_bits: Vec<_> = lhs_bits
+            .into_iter()
+            .zip(rhs_bits)
+            .map(|(lhs_bit, rhs_bit)| config.gate_chip.and(ctx, lhs_bit, rhs_bit))
+            .collect();
+
+        self.constrain_bitwise_output(output, output_bits, config, ctx, witness_assignments);
+    }
+
+    pub(crate) fn add_xor_constrain(
+        &self,
+        lhs: &FunctionInput,
+        rhs: &FunctionInput,
+        output: Witness,
+        config: &PlonkConfig,
+        ctx: &mut Context<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) {
+        let (lhs_bits, rhs_bits) =
+            self.assign_bitwise_operands(lhs, rhs, config, ctx, witness_assignments);
+
+        // a ^ b = a + b - 2 * a * b on bits
+        let minus_two = QuantumCell::Constant(-Fr::from(2));
+        let output_bits: Vec<_> = lhs_bits
+            .into_iter()
+            .zip(rhs_bits)
+            .map(|(lhs_bit, rhs_bit)| {
+                let sum = config.gate_chip.add(ctx, lhs_bit, rhs_bit);
+                let product = config.gate_chip.mul(ctx, lhs_bit, rhs_bit);
+                config.gate_chip.mul_add(ctx, product, minus_two, sum)
+            })
+            .collect();
+
+        self.constrain_bitwise_output(output, output_bits, config, ctx, witness_assignments);
+    }
+
+    /// Assign the operands of a bitwise opcode or get their existing
+    /// assignments, returning their little-endian bits. The decomposition
+    /// constrains the bits to be boolean and to compose the operands, which
+    /// range checks them to their bit size.
+    fn assign_bitwise_operands(
+        &self,
+        lhs: &FunctionInput,
+        rhs: &FunctionInput,
+        config: &PlonkConfig,
+        ctx: &mut Context<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> (Vec<AssignedValue<Fr>>, Vec<AssignedValue<Fr>>) {
+        let [lhs_bits, rhs_bits] = [lhs, rhs].map(|input| {
+            let value = witness_assignments.get_or_assign(
+                ctx,
+                &input.witness,
+                noir_field_to_halo2_field(
+                    *self.witness_values.get(&input.witness).unwrap_or(&FieldElement::zero()),
+                ),
+            );
+            config.gate_chip.num_to_bits(ctx, value, input.num_bits as usize)
+        });
+        (lhs_bits, rhs_bits)
+    }
+
+    /// Constrain the output of a bitwise opcode to the composition of its
+    /// little-endian bits
+    fn constrain_bitwise_output(
+        &self,
+        output: Witness,
+        output_bits: Vec<AssignedValue<Fr>>,
+        config: &PlonkConfig,
+        ctx: &mut Context<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) {
+        let output_v = witness_assignments.get_or_assign(
+            ctx,
             &output,
             noir_field_to_halo2_field(
                 *self.witness_values.get(&output).unwrap_or(&FieldElement::zero()),
             ),
         );
 
-        let and_out = config.gate_chip.and(&mut ctx, *lhs_v, *rhs_v);
+        let powers_of_two: Vec<_> = config.gate_chip.pow_of_two()[..output_bits.len()]
+            .iter()
+            .map(|power| QuantumCell::Constant(*power))
+            .collect();
+        let composed = config.gate_chip.inner_product(ctx, output_bits, powers_of_two);
 
-        config.gate_chip.is_equal(&mut ctx, *output_v, and_out);
+        ctx.constrain_equal(&composed, &output_v);
     }
 
-    pub(crate) fn add_xor_constrain(
+    /// Constrain the verification of the ECDSA signature `r || s` of
+    /// `hashed_message` under the public key `(public_key_x, public_key_y)`
+    /// over the curve `C`, every input being a big-endian byte array
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn add_ecdsa_constrain<C>(
         &self,
-        lhs: Witness,
-        rhs: Witness,
+        func: BlackBoxFunc,
+        public_key_x: &[FunctionInput],
+        public_key_y: &[FunctionInput],
+        signature: &[FunctionInput],
+        hashed_message: &[FunctionInput],
         output: Witness,
         config: &PlonkConfig,
+        ctx: &mut Context<Fr>,
         witness_assignments: &mut AssignedMap<Fr>,
-    ) {
-        let mut ctx = Context::<Fr>::new(false, 0);
+    ) -> Result<(), Error>
+    where
+        C: CurveAffineExt,
+        C::Base: PrimeField,
+        C::ScalarExt: PrimeField,
+    {
+        for (name, inputs, len) in [
+            ("`x` component for public key", public_key_x, 32),
+            ("`y` component for public key", public_key_y, 32),
+            ("signature", signature, 64),
+        ] {
+            if inputs.len() != len {
+                return Err(Error::MalformedBlackBoxFunc(
+                    func,
+                    format!("Expected {len} bytes of {name} but got {}", inputs.len()),
+                ));
+            }
+        }
+        if hashed_message.len() > 32 {
+            return Err(Error::MalformedBlackBoxFunc(
+                func,
+                format!(
+                    "Expected at most 32 bytes of hashed message but got {}",
+                    hashed_message.len()
+                ),
+            ));
+        }
 
-        // assign lhs, rhs, output or get existing assignnments
-        let lhs_v = &witness_assignments.get_or_assign(
-            &mut ctx,
-            &lhs,
-            noir_field_to_halo2_field(
-                *self.witness_values.get(&lhs).unwrap_or(&FieldElement::zero()),
-            ),
-        );
-        let rhs_v = &witness_assignments.get_or_assign(
-            &mut ctx,
-            &rhs,
-            noir_field_to_halo2_field(
-                *self.witness_values.get(&rhs).unwrap_or(&FieldElement::zero()),
-            ),
+        // loading the chips here instead of in config cus
+        // puting them in a struct requires lifetime parameters
+        let fp_chip =
+            FpChip::<Fr, C::Base>::new(&config.range_chip, ECDSA_LIMB_BITS, ECDSA_NUM_LIMBS);
+        let fq_chip =
+            FpChip::<Fr, C::ScalarExt>::new(&config.range_chip, ECDSA_LIMB_BITS, ECDSA_NUM_LIMBS);
+        let ecc_chip = EccChip::new(&fp_chip);
+
+        // the public key is asserted to be on the curve, as it is not checked by the verification
+        let x = self.load_bytes(&fp_chip, public_key_x, config, ctx, witness_assignments);
+        let y = self.load_bytes(&fp_chip, public_key_y, config, ctx, witness_assignments);
+        let public_key = EcPoint::new(x, y);
+        ecc_chip.assert_is_on_curve::<C>(ctx, &public_key);
+
+        let r = self.load_bytes(&fq_chip, &signature[..32], config, ctx, witness_assignments);
+        let s = self.load_bytes(&fq_chip, &signature[32..], config, ctx, witness_assignments);
+        let m = self.load_bytes(&fq_chip, hashed_message, config, ctx, witness_assignments);
+
+        let result = ecdsa_verify_no_pubkey_check::<Fr, C::Base, C::ScalarExt, C>(
+            &ecc_chip,
+            ctx,
+            public_key,
+            r,
+            s,
+            m,
+            ECDSA_WINDOW_BITS,
+            ECDSA_WINDOW_BITS,
         );
-        let output_v = &witness_assignments.get_or_assign(
-            &mut ctx,
+
+        let output = witness_assignments.get_or_assign(
+            ctx,
             &output,
             noir_field_to_halo2_field(
                 *self.witness_values.get(&output).unwrap_or(&FieldElement::zero()),
             ),
         );
+        ctx.constrain_equal(&output, &result);
+        Ok(())
+    }
+
+    /// Load big-endian bytes as the non-native field element they encode.
+    /// Every byte is range checked, and the bytes of each limb are composed
+    /// into the limbs the field chip reduces the element from.
+    fn load_bytes<Fp: PrimeField>(
+        &self,
+        chip: &FpChip<Fr, Fp>,
+        inputs: &[FunctionInput],
+        config: &PlonkConfig,
+        ctx: &mut Context<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> ProperCrtUint<Fr> {
+        // little-endian bytes, so that limbs start with the least significant one
+        let mut values = Vec::with_capacity(inputs.len());
+        let mut bytes = Vec::with_capacity(inputs.len());
+        for input in inputs.iter().rev() {
+            let value = *self.witness_values.get(&input.witness).unwrap_or(&FieldElement::zero());
+            let byte = witness_assignments.get_or_assign(
+                ctx,
+                &input.witness,
+                noir_field_to_halo2_field(value),
+            );
+            config.range_chip.range_check(ctx, byte, 8);
+            values.push(value.to_u128() as u8);
+            bytes.push(byte);
+        }
 
-        // lhs + rhs - 2 * (lhs & rhs)
-        let two_val = QuantumCell::Constant(Fr::from(2));
-        let and_res = config.gate_chip.and(&mut ctx, *lhs_v, *rhs_v);
-        let and_res_with_two = config.gate_chip.mul(&mut ctx, and_res, two_val);
-        let add_res = config.gate_chip.add(&mut ctx, *lhs_v, *rhs_v);
-        let final_res = config.gate_chip.sub(&mut ctx, add_res, and_res_with_two);
+        // constant powers of `2^bits`
+        let powers = |bits: usize, len: usize| {
+            (0..len)
+                .map(move |i| QuantumCell::Constant(Fr::from(2).pow_vartime([(bits * i) as u64])))
+        };
+        let limbs: Vec<AssignedValue<Fr>> = (0..ECDSA_NUM_LIMBS)
+            .map(|i| {
+                let chunk: Vec<_> =
+                    bytes.iter().skip(i * BYTES_PER_LIMB).take(BYTES_PER_LIMB).copied().collect();
+                if chunk.is_empty() {
+                    ctx.load_zero()
+                } else {
+                    let len = chunk.len();
+                    config.gate_chip.inner_product(ctx, chunk, powers(8, len))
+                }
+            })
+            .collect();
+        let native = config.gate_chip.inner_product(
+            ctx,
+            limbs.clone(),
+            powers(ECDSA_LIMB_BITS, ECDSA_NUM_LIMBS),
+        );
 
-        config.gate_chip.is_equal(&mut ctx, final_res, *output_v);
+        values.reverse();
+        let value = BigInt::from_bytes_be(Sign::Plus, &values);
+        let integer = CRTInteger::new(OverflowInteger::new(limbs, ECDSA_LIMB_BITS), native, value);
+        chip.carry_mod(ctx, integer)
     }
 
-    pub(crate) fn add_ecdsa_secp256k1_constrain(
+    /// Constrain the Keccak256 digest of the bytes of `inputs` to the 32 bytes of
+    /// `outputs`, hashing only the first `var_message_size` bytes when given
+    pub(crate) fn add_keccak256_constrain(
         &self,
-        hashed_message: Vec<Witness>,
-        signature: Vec<Witness>,
-        public_key_x: Vec<Witness>,
-        public_key_y: Vec<Witness>,
-        result: Witness,
+        inputs: &[FunctionInput],
+        var_message_size: Option<&FunctionInput>,
+        outputs: &[Witness],
         config: &PlonkConfig,
-    ) {
-        let r = self.noir_field_to_secp255k1_fq_field(signature[..32].to_vec());
-        let s = self.noir_field_to_secp255k1_fq_field(signature[32..].to_vec());
-        let msghash = self.noir_field_to_secp255k1_fq_field(hashed_message);
-        let public_key_x = self.noir_field_to_secp255k1_fp_field(public_key_x);
-        let public_key_y = self.noir_field_to_secp255k1_fp_field(public_key_y);
+        ctx: &mut Context<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), Error> {
+        let message =
+            self.load_message(BlackBoxFunc::Keccak256, inputs, outputs, ctx, witness_assignments)?;
+        let length = match var_message_size {
+            Some(size) => witness_assignments.get_or_assign(
+                ctx,
+                &size.witness,
+                noir_field_to_halo2_field(
+                    *self.witness_values.get(&size.witness).unwrap_or(&FieldElement::zero()),
+                ),
+            ),
+            None => ctx.load_constant(Fr::from(inputs.len() as u64)),
+        };
 
-        let pk = Secp256k1Affine::from_xy(public_key_x, public_key_y).unwrap();
+        let digest = Keccak256Chip::new(&config.range_chip).digest(ctx, &message, length);
+        self.constrain_digest(outputs, &digest, ctx, witness_assignments);
+        Ok(())
+    }
 
-        // loading the chip here instead of in config cus
-        // puting them in a struct requires lifetime parameters
-        // not sure if theres a way around this
-        // this could be okay
-        let mut ctx = Context::<Fr>::new(false, 0);
-        let ecdsa_range_chip = RangeChip::<Fr>::default(17);
-        let ecdsa_fp_chip = FpChip::new(&ecdsa_range_chip, 88, 3);
-        let ecdsa_fq_chip = FqChip::new(&ecdsa_range_chip, 88, 3);
-
-        let [m, r, s] = [msghash, r, s].map(|x| ecdsa_fq_chip.load_private(&mut ctx, x));
-
-        let ecc_chip = EccChip::<Fr, FpChip<Fr>>::new(&ecdsa_fp_chip);
-        let pk = ecc_chip.load_private_unchecked(&mut ctx, (pk.x, pk.y));
-        // test ECDSA
-        let res = ecdsa_verify_no_pubkey_check::<Fr, Fp, Fq, Secp256k1Affine>(
-            &ecc_chip, &mut ctx, pk, r, s, m, 4, 4,
-        );
-        assert_eq!(res.value(), &Fr::one());
+    /// Constrain the SHA256 digest of the bytes of `inputs` to the 32 bytes of `outputs`
+    pub(crate) fn add_sha256_constrain(
+        &self,
+        inputs: &[FunctionInput],
+        outputs: &[Witness],
+        config: &PlonkConfig,
+        ctx: &mut Context<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), Error> {
+        let message =
+            self.load_message(BlackBoxFunc::SHA256, inputs, outputs, ctx, witness_assignments)?;
 
-        let result_value = noir_field_to_halo2_field(
-            *self.witness_values.get(&result).unwrap_or(&FieldElement::zero()),
-        );
+        let digest = Sha256Chip::new(&config.gate_chip).digest(ctx, &message);
+        self.constrain_digest(outputs, &digest, ctx, witness_assignments);
+        Ok(())
+    }
+
+    /// Cells of the message bytes of a hash, which the hash chips range check
+    fn load_message(
+        &self,
+        func: BlackBoxFunc,
+        inputs: &[FunctionInput],
+        outputs: &[Witness],
+        ctx: &mut Context<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<Vec<AssignedValue<Fr>>, Error> {
+        if let Some(input) = inputs.iter().find(|input| input.num_bits > 8) {
+            return Err(Error::MalformedBlackBoxFunc(
+                func,
+                format!("Expected bytes of message but got {} bits", input.num_bits),
+            ));
+        }
+        if outputs.len() != 32 {
+            return Err(Error::MalformedBlackBoxFunc(
+                func,
+                format!("Expected 32 bytes of digest but got {}", outputs.len()),
+            ));
+        }
 
-        let output = ctx.load_witness(result_value);
-        config.gate_chip.is_equal(&mut ctx, output, res);
+        Ok(inputs
+            .iter()
+            .map(|input| {
+                witness_assignments.get_or_assign(
+                    ctx,
+                    &input.witness,
+                    noir_field_to_halo2_field(
+                        *self.witness_values.get(&input.witness).unwrap_or(&FieldElement::zero()),
+                    ),
+                )
+            })
+            .collect())
     }
-}
 
-impl_noir_field_to_secp255k1_field_conversion!(NoirHalo2Translator, Fr, Fp, Fq);
+    fn constrain_digest(
+        &self,
+        outputs: &[Witness],
+        digest: &[AssignedValue<Fr>],
+        ctx: &mut Context<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) {
+        for (output, byte) in outputs.iter().zip(digest) {
+            let output = witness_assignments.get_or_assign(
+                ctx,
+                output,
+                noir_field_to_halo2_field(
+                    *self.witness_values.get(output).unwrap_or(&FieldElement::zero()),
+                ),
+            );
+            ctx.constrain_equal(&output, byte);
+        }
+    }
+
+    /// Cells of the public witnesses in the order of their indices, which the
+    /// circuit builder copies to its instance column
+    pub(crate) fn expose_public(
+        &self,
+        ctx: &mut Context<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        self.circuit
+            .public_inputs()
+            .indices()
+            .iter()
+            .map(|index| {
+                let witness = Witness(*index);
+                witness_assignments.get_or_assign(
+                    ctx,
+                    &witness,
+                    noir_field_to_halo2_field(
+                        *self.witness_values.get(&witness).unwrap_or(&FieldElement::zero()),
+                    ),
+                )
+            })
+            .collect()
+    }
+}
 
 noir_field_to_halo2_field!(Fr);
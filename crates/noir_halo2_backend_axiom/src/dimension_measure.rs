@@ -1,152 +1,93 @@
-// copied and adapted from https://github.com/privacy-scaling-explorations/halo2wrong/blob/master/halo2wrong/src/utils.rs
-// cus fn k() is private.
-
-use halo2_base::halo2_proofs::{
-    circuit::Value,
-    plonk::{
-        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
-        Fixed, FloorPlanner, Instance, Selector,
-    },
+use crate::{
+    circuit_translator::NoirHalo2Translator,
+    halo2_plonk_api::{lookup_bits, PlonkConfig, MINIMUM_ROWS},
 };
-use halo2_ecc::fields::PrimeField;
-use std::{cell::RefCell, ops::RangeInclusive};
+use halo2_base::{gates::builder::GateThreadBuilder, halo2_proofs::halo2curves::bn256::Fr};
+use noir_halo2_backend_common::errors::Error;
+use std::collections::HashSet;
 
-#[derive(Default)]
-pub struct DimensionMeasurement {
-    instance: RefCell<u64>,
-    advice: RefCell<u64>,
-    fixed: RefCell<u64>,
-}
+/// Smallest `k` measured
+const MIN_K: u32 = 8;
+/// Largest `k` the Aztec CRS has points for, past which
+/// circuits grow more columns instead of more rows
+const MAX_K: u32 = 26;
 
+pub struct DimensionMeasurement;
+
+/// Cells of a circuit built on a thread builder
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Dimension {
-    blinding_factor: u64,
-    instance: u64,
-    advice: u64,
-    fixed: u64,
+    k: u32,
+    advice_per_phase: Vec<usize>,
+    lookup_advice_per_phase: Vec<usize>,
+    fixed: usize,
 }
 
 impl Dimension {
-    pub(crate) fn k(&self) -> u32 {
-        u64::BITS
-            - ([self.instance, self.advice, self.fixed]
-                .into_iter()
-                .max_by(Ord::cmp)
-                .expect("Unexpected empty column iterator")
-                + self.blinding_factor)
-                .next_power_of_two()
-                .leading_zeros()
-            - 1
+    fn new(builder: &GateThreadBuilder<Fr>, k: u32) -> Self {
+        let advice_per_phase = builder
+            .threads
+            .iter()
+            .map(|threads| threads.iter().map(|ctx| ctx.advice.len()).sum())
+            .collect();
+        let lookup_advice_per_phase = builder
+            .threads
+            .iter()
+            .map(|threads| threads.iter().map(|ctx| ctx.cells_to_lookup.len()).sum())
+            .collect();
+        // constants are assigned once to the fixed columns, whatever their number of copies
+        let fixed = builder
+            .threads
+            .iter()
+            .flatten()
+            .flat_map(|ctx| ctx.constant_equality_constraints.iter().map(|(c, _)| *c))
+            .collect::<HashSet<Fr>>()
+            .len();
+
+        Dimension { k, advice_per_phase, lookup_advice_per_phase, fixed }
     }
 
-    fn _advice_range(&self) -> RangeInclusive<usize> {
-        0..=self.advice as usize
+    pub(crate) fn k(&self) -> u32 {
+        self.k
     }
-}
 
-impl DimensionMeasurement {
-    fn update<C: Into<Any>>(&self, column: C, offset: usize) {
-        let mut target = match column.into() {
-            Any::Instance => self.instance.borrow_mut(),
-            Any::Advice(_advice) => self.advice.borrow_mut(),
-            Any::Fixed => self.fixed.borrow_mut(),
-        };
-        if offset as u64 > *target {
-            *target = offset as u64;
-        }
+    /// Rows of the tallest column, if every kind of cell had a single column
+    fn rows(&self) -> usize {
+        self.advice_per_phase
+            .iter()
+            .chain(&self.lookup_advice_per_phase)
+            .chain([&self.fixed])
+            .copied()
+            .max()
+            .unwrap_or_default()
     }
 
-    pub fn measure<F: PrimeField, C: Circuit<F>>(circuit: &C) -> Result<Dimension, Error> {
-        let mut cs = ConstraintSystem::default();
-        let config = C::configure(&mut cs);
-        let mut measurement = Self::default();
-        C::FloorPlanner::synthesize(&mut measurement, circuit, config, cs.constants().to_vec())?;
-        Ok(Dimension {
-            blinding_factor: cs.blinding_factors() as u64,
-            instance: measurement.instance.take(),
-            advice: measurement.advice.take(),
-            fixed: measurement.fixed.take(),
-        })
+    fn fits(&self) -> bool {
+        self.rows() + MINIMUM_ROWS <= 1 << self.k
     }
 }
 
-impl<F: PrimeField> Assignment<F> for DimensionMeasurement {
-    fn enter_region<NR, N>(&mut self, _: N)
-    where
-        NR: Into<String>,
-        N: FnOnce() -> NR,
-    {
-    }
-
-    fn exit_region(&mut self) {}
-
-    fn get_challenge(&self, _challenge: Challenge) -> Value<F> {
-        Value::unknown()
-    }
-
-    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, offset: usize) -> Result<(), Error>
-    where
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-    {
-        self.update(Fixed, offset);
-        Ok(())
-    }
-
-    fn query_instance(&self, _: Column<Instance>, offset: usize) -> Result<Value<F>, Error> {
-        self.update(Instance, offset);
-        Ok(Value::unknown())
-    }
-
-    fn annotate_column<A, AR>(&mut self, _annotation: A, _column: Column<Any>)
-    where
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-    {
-        // Do nothing.
-    }
-
-    fn assign_advice<'r, 'v>(
-        &'r mut self,
-        _column: Column<Advice>,
-        row: usize,
-        _to: Value<Assigned<F>>,
-    ) -> Value<&'v Assigned<F>> {
-        self.update(Any::advice(), row);
-        Value::unknown()
-    }
-
-    fn assign_fixed(&mut self, _column: Column<Fixed>, row: usize, _to: Assigned<F>) {
-        self.update(Any::advice(), row);
-    }
-
-    fn copy(
-        &mut self,
-        left_column: Column<Any>,
-        left_row: usize,
-        right_column: Column<Any>,
-        right_row: usize,
-    ) {
-        self.update(*left_column.column_type(), left_row);
-        self.update(*right_column.column_type(), right_row);
-    }
-
-    fn fill_from_row(
-        &mut self,
-        _: Column<Fixed>,
-        offset: usize,
-        _: Value<Assigned<F>>,
-    ) -> Result<(), Error> {
-        self.update(Fixed, offset);
-        Ok(())
-    }
-
-    fn push_namespace<NR, N>(&mut self, _: N)
-    where
-        NR: Into<String>,
-        N: FnOnce() -> NR,
-    {
+impl DimensionMeasurement {
+    /// Measure the circuit of a translated program at the smallest `k` whose
+    /// rows fit its cells in a column of each kind. Range checks decompose
+    /// into lookups of `k - 1` bits, so the circuit is built again for each `k`.
+    pub fn measure(
+        translator: &NoirHalo2Translator<Fr>,
+        parallel_witness_gen: bool,
+    ) -> Result<Dimension, Error> {
+        let mut k = MIN_K;
+        loop {
+            let mut builder = GateThreadBuilder::keygen();
+            translator.build(
+                &mut builder,
+                &PlonkConfig::new(lookup_bits(k)),
+                parallel_witness_gen,
+            )?;
+            let dimension = Dimension::new(&builder, k);
+            if dimension.fits() || k == MAX_K {
+                return Ok(dimension);
+            }
+            k += 1;
+        }
     }
-
-    fn pop_namespace(&mut self, _: Option<String>) {}
 }
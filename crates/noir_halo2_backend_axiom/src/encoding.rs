@@ -0,0 +1,87 @@
+use halo2_base::halo2_proofs::{
+    halo2curves::{
+        bn256::{Fq, Fr, G1Affine},
+        group::ff::PrimeField,
+        CurveAffine,
+    },
+    plonk::VerifyingKey,
+};
+use snark_verifier::util::arithmetic::fe_to_limbs;
+
+/// Number of limbs of a point coordinate, as decomposed by the PSE backend so
+/// that both backends share their encodings
+const NUMBER_OF_LIMBS: usize = 4;
+/// Bit length of a limb of a point coordinate
+const BIT_LEN_LIMB: usize = 68;
+
+/// Element of a proof transcript
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ProofElement {
+    Point(G1Affine),
+    Scalar(Fr),
+}
+
+/// Limbs of the coordinates of a point, x first
+fn point_to_limbs(point: &G1Affine) -> Vec<Fr> {
+    [point.x, point.y].map(fe_to_limbs::<Fq, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>).concat()
+}
+
+/// Field of 32 big-endian bytes, if canonical
+fn field_from_be_bytes<F: PrimeField>(bytes: &[u8]) -> Option<F> {
+    let mut repr = F::Repr::default();
+    for (byte, value) in repr.as_mut().iter_mut().zip(bytes.iter().rev()) {
+        *byte = *value;
+    }
+    Option::from(F::from_repr(repr))
+}
+
+/// Elements of a proof of the EVM transcript, points as the 64 big-endian
+/// bytes of their coordinates and scalars as 32 big-endian bytes. A pair of
+/// scalars is a point on the curve with negligible probability, so points are
+/// told apart from scalars without the verifying key.
+pub(crate) fn proof_elements(proof: &[u8]) -> Option<Vec<ProofElement>> {
+    let mut elements = Vec::new();
+    let mut offset = 0;
+    while offset < proof.len() {
+        let point = proof.get(offset..offset + 64).and_then(|bytes| {
+            let x = field_from_be_bytes::<Fq>(&bytes[..32])?;
+            let y = field_from_be_bytes::<Fq>(&bytes[32..])?;
+            Option::<G1Affine>::from(G1Affine::from_xy(x, y))
+        });
+        if let Some(point) = point {
+            elements.push(ProofElement::Point(point));
+            offset += 64;
+        } else {
+            let scalar = field_from_be_bytes::<Fr>(proof.get(offset..offset + 32)?)?;
+            elements.push(ProofElement::Scalar(scalar));
+            offset += 32;
+        }
+    }
+    Some(elements)
+}
+
+/// Fields of proof elements, points decomposed into the limbs of their
+/// coordinates and scalars as they are
+pub(crate) fn elements_to_fields(elements: &[ProofElement]) -> Vec<Fr> {
+    elements
+        .iter()
+        .flat_map(|element| match element {
+            ProofElement::Point(point) => point_to_limbs(point),
+            ProofElement::Scalar(scalar) => vec![*scalar],
+        })
+        .collect()
+}
+
+/// Fields of a verifying key: `k`, the numbers of fixed and permutation
+/// commitments, then the limbs of these commitments in transcript order
+pub(crate) fn vk_to_fields(vk: &VerifyingKey<G1Affine>) -> Vec<Fr> {
+    let fixed_commitments = vk.fixed_commitments();
+    let permutation_commitments = vk.permutation().commitments();
+    let mut fields = vec![
+        Fr::from(vk.get_domain().k() as u64),
+        Fr::from(fixed_commitments.len() as u64),
+        Fr::from(permutation_commitments.len() as u64),
+    ];
+    fields.extend(fixed_commitments.iter().chain(permutation_commitments).flat_map(point_to_limbs));
+    fields
+}
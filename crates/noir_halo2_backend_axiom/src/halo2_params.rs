@@ -1,8 +1,7 @@
-use crate::dimension_measure::DimensionMeasurement;
 use halo2_base::halo2_proofs::{
     arithmetic::g_to_lagrange,
     halo2curves::{
-        bn256::{Bn256, Fq, Fq2, Fr, G1Affine, G2Affine},
+        bn256::{Bn256, Fq, Fq2, G1Affine, G2Affine},
         group::prime::PrimeCurveAffine,
         pairing::Engine,
         serde::SerdeObject,
@@ -12,15 +11,17 @@ use halo2_base::halo2_proofs::{
     SerdeFormat,
 };
 use noir_halo2_backend_common::{aztec_crs::get_aztec_crs, errors::Error};
-use std::io::Write;
+use std::io::{self, Write};
+
+/// Length of a point of G1 in the raw bytes encoding
+const G1_RAW_BYTES: usize = 64;
+/// Length of a point of G2 in the raw bytes encoding
+const G2_RAW_BYTES: usize = 128;
 
 pub(crate) async fn constuct_halo2_params_from_aztec_crs(
-    translator: impl halo2_base::halo2_proofs::plonk::Circuit<Fr>,
+    k: u32,
 ) -> Result<ParamsKZG<Bn256>, Error> {
-    let dimension = DimensionMeasurement::measure(&translator).unwrap();
-    let k = dimension.k();
     let n = 1 << k;
-    assert!(n == 1 << k);
 
     let (g1_data, g2_data) = get_aztec_crs(n).await?;
 
@@ -36,6 +37,42 @@ pub(crate) async fn constuct_halo2_params_from_aztec_crs(
     Ok(params_kzg(k, g, g_lagrange, g2, s_g2))
 }
 
+/// Reads a `ParamsKZG<Bn256>` from a common reference string in the
+/// `SerdeFormat::RawBytes` encoding.
+///
+/// The reader of axiom's halo2 panics on malformed input, so the `k` header is
+/// checked against the length of the points it announces, and the points
+/// against their curves, before they are read unchecked.
+pub(crate) fn read_halo2_params(common_reference_string: &[u8]) -> Result<ParamsKZG<Bn256>, Error> {
+    let malformed = |kind: io::ErrorKind| Error::ReadCRS(kind.into());
+
+    let k =
+        common_reference_string.get(..4).ok_or_else(|| malformed(io::ErrorKind::UnexpectedEof))?;
+    let k = u32::from_le_bytes(k.try_into().unwrap());
+
+    // g and g_lagrange hold 2^k points each, followed by g2 and s_g2
+    let g1_len = 1usize
+        .checked_shl(k)
+        .and_then(|n| n.checked_mul(2 * G1_RAW_BYTES))
+        .ok_or_else(|| malformed(io::ErrorKind::InvalidData))?;
+    let points = g1_len
+        .checked_add(2 * G2_RAW_BYTES)
+        .and_then(|len| common_reference_string.get(4..4 + len))
+        .ok_or_else(|| malformed(io::ErrorKind::UnexpectedEof))?;
+
+    let (g1_points, g2_points) = points.split_at(g1_len);
+    if g1_points.chunks(G1_RAW_BYTES).any(|point| G1Affine::from_raw_bytes(point).is_none())
+        || g2_points.chunks(G2_RAW_BYTES).any(|point| G2Affine::from_raw_bytes(point).is_none())
+    {
+        return Err(malformed(io::ErrorKind::InvalidData));
+    }
+
+    Ok(ParamsKZG::<Bn256>::read_custom(
+        &mut &common_reference_string[..4 + points.len()],
+        SerdeFormat::RawBytesUnchecked,
+    ))
+}
+
 /// Constructs a `ParamsKZG<Bn256>` from its parameters
 fn params_kzg(
     k: u32,
@@ -1,73 +1,249 @@
 use crate::circuit_translator::NoirHalo2Translator;
 use halo2_base::{
-    gates::{GateChip, RangeChip},
+    gates::{
+        builder::{
+            FlexGateConfigParams, GateThreadBuilder, MultiPhaseThreadBreakPoints,
+            RangeWithInstanceCircuitBuilder,
+        },
+        GateChip, RangeChip,
+    },
     halo2_proofs::{
         halo2curves::{
             bn256::{Bn256, Fr, G1Affine, G1},
             group::cofactor::CofactorCurve,
         },
         plonk::{
-            create_proof, keygen_pk, keygen_vk, verify_proof, ConstraintSystem, Error, ProvingKey,
+            create_proof, keygen_pk, keygen_vk, verify_proof, Error as Halo2Error, ProvingKey,
             VerifyingKey,
         },
-        poly::kzg::{
-            commitment::{KZGCommitmentScheme, ParamsKZG},
-            multiopen::{ProverGWC, VerifierGWC},
-            strategy::SingleStrategy,
-        },
-        transcript::{
-            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        poly::{
+            commitment::Params,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverGWC, VerifierGWC},
+                strategy::SingleStrategy,
+            },
         },
+        transcript::{TranscriptReadBuffer, TranscriptWriterBuffer},
+        SerdeFormat,
     },
+    AssignedValue,
 };
+use noir_halo2_backend_common::errors::Error;
 use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
+use std::{env, io};
+
+/// Rows kept free at the bottom of the columns for the blinding factors
+pub(crate) const MINIMUM_ROWS: usize = 20;
+
+/// Bit length of the lookup table of the range chip of a circuit of `2^k`
+/// rows, the largest table that leaves room for the blinding factors
+pub(crate) fn lookup_bits(k: u32) -> usize {
+    k as usize - 1
+}
+
+/// Parameters of a circuit built on a `GateThreadBuilder`: the columns
+/// `RangeWithInstanceCircuitBuilder` is configured with, derived at keygen from the
+/// statistics of the builder, and the break points of its advice
+/// columns found at keygen, which proving has to follow along with
+/// the threads the opcodes were laid out in
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CircuitParams {
+    pub(crate) config_params: FlexGateConfigParams,
+    pub(crate) lookup_bits: usize,
+    pub(crate) break_points: MultiPhaseThreadBreakPoints,
+    #[serde(default)]
+    pub(crate) parallel_witness_gen: bool,
+}
+
+impl CircuitParams {
+    /// `RangeWithInstanceCircuitBuilder` reads its configuration from the environment,
+    /// so it must be set before configuring the circuit or reading its keys
+    pub(crate) fn set_env(&self) {
+        env::set_var(
+            "FLEX_GATE_CONFIG_PARAMS",
+            serde_json::to_string(&self.config_params).expect("config params should serialize"),
+        );
+        env::set_var("LOOKUP_BITS", self.lookup_bits.to_string());
+    }
+}
+
+/// Build the circuit of a translated program on a thread builder, and
+/// configure the columns its cells need to fit in `2^k` rows. The cells
+/// of the public inputs are returned along with the builder.
+#[allow(clippy::type_complexity)]
+pub(crate) fn halo2_build(
+    translator: &NoirHalo2Translator<Fr>,
+    mut builder: GateThreadBuilder<Fr>,
+    k: u32,
+    parallel_witness_gen: bool,
+) -> Result<(GateThreadBuilder<Fr>, Vec<AssignedValue<Fr>>, FlexGateConfigParams), Error> {
+    let public_inputs =
+        translator.build(&mut builder, &PlonkConfig::new(lookup_bits(k)), parallel_witness_gen)?;
+    let config_params = builder.config(k as usize, Some(MINIMUM_ROWS));
+    env::set_var("LOOKUP_BITS", lookup_bits(k).to_string());
+    Ok((builder, public_inputs, config_params))
+}
 
 pub fn halo2_keygen(
-    circuit: &NoirHalo2Translator<Fr>,
+    translator: &NoirHalo2Translator<Fr>,
     params: &ParamsKZG<Bn256>,
-) -> (ProvingKey<<G1 as CofactorCurve>::Affine>, VerifyingKey<<G1 as CofactorCurve>::Affine>) {
-    let vk = keygen_vk(params, circuit).expect("keygen_vk should not fail");
+    parallel_witness_gen: bool,
+) -> Result<
+    (
+        ProvingKey<<G1 as CofactorCurve>::Affine>,
+        VerifyingKey<<G1 as CofactorCurve>::Affine>,
+        CircuitParams,
+    ),
+    Error,
+> {
+    let (builder, public_inputs, config_params) =
+        halo2_build(translator, GateThreadBuilder::keygen(), params.k(), parallel_witness_gen)?;
+    let circuit = RangeWithInstanceCircuitBuilder::keygen(builder, public_inputs);
+
+    let vk = keygen_vk(params, &circuit).map_err(Error::synthesis)?;
     let vk_return = vk.clone();
-    let pk = keygen_pk(params, vk, circuit).expect("keygen_pk should not fail");
-    (pk, vk_return)
+    let pk = keygen_pk(params, vk, &circuit).map_err(Error::synthesis)?;
+
+    let break_points = circuit.break_points();
+    Ok((
+        pk,
+        vk_return,
+        CircuitParams {
+            config_params,
+            lookup_bits: lookup_bits(params.k()),
+            break_points,
+            parallel_witness_gen,
+        },
+    ))
 }
 
 pub fn halo2_prove(
-    circuit: NoirHalo2Translator<Fr>,
+    translator: NoirHalo2Translator<Fr>,
     params: &ParamsKZG<Bn256>,
     pk: &ProvingKey<<G1 as CofactorCurve>::Affine>,
-) -> Vec<u8> {
+    circuit_params: &CircuitParams,
+    public_inputs: &[Fr],
+) -> Result<Vec<u8>, Error> {
+    // the prover only generates witnesses, laid out along the keygen break points
+    let mut builder = GateThreadBuilder::prover();
+    let assigned_inputs = translator.build(
+        &mut builder,
+        &PlonkConfig::new(circuit_params.lookup_bits),
+        circuit_params.parallel_witness_gen,
+    )?;
+    circuit_params.set_env();
+    let circuit = RangeWithInstanceCircuitBuilder::prover(
+        builder,
+        assigned_inputs,
+        circuit_params.break_points.clone(),
+    );
+
     let rng = OsRng;
-    let mut transcript: Blake2bWrite<Vec<u8>, _, Challenge255<_>> =
-        Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    let mut transcript = TranscriptWriterBuffer::<_, G1Affine, _>::init(Vec::new());
     create_proof::<
         KZGCommitmentScheme<Bn256>,
         ProverGWC<'_, Bn256>,
-        Challenge255<G1Affine>,
         _,
-        Blake2bWrite<Vec<u8>, G1Affine, Challenge255<_>>,
         _,
-    >(params, pk, &[circuit], &[&[]], rng, &mut transcript)
-    .expect("proof generation should not fail");
-    transcript.finalize()
+        EvmTranscript<_, _, _, _>,
+        _,
+    >(params, pk, &[circuit], &[&[public_inputs]], rng, &mut transcript)
+    .map_err(Error::synthesis)?;
+    Ok(transcript.finalize())
 }
 
 pub fn halo2_verify(
     params: &ParamsKZG<Bn256>,
     vk: &VerifyingKey<<G1 as CofactorCurve>::Affine>,
     proof: &[u8],
-) -> Result<(), Error> {
+    public_inputs: &[Fr],
+) -> Result<(), Halo2Error> {
     let strategy = SingleStrategy::new(params);
-    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    let mut transcript = TranscriptReadBuffer::<_, G1Affine, _>::init(proof);
     verify_proof::<
         KZGCommitmentScheme<Bn256>,
         VerifierGWC<'_, Bn256>,
-        Challenge255<G1Affine>,
-        Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
+        _,
+        EvmTranscript<_, _, _, _>,
         SingleStrategy<'_, Bn256>,
-    >(params, vk, strategy, &[&[]], &mut transcript)
+    >(params, vk, strategy, &[&[public_inputs]], &mut transcript)
+}
+
+/// Serialize the Proving Key prefixed with the
+/// parameters its circuit was built with
+pub fn halo2_pk_to_bytes(
+    pk: &ProvingKey<<G1 as CofactorCurve>::Affine>,
+    circuit_params: &CircuitParams,
+) -> Vec<u8> {
+    prefix_circuit_params(circuit_params, pk.to_bytes(SerdeFormat::RawBytes))
+}
+
+/// Read a Proving Key serialized by `halo2_pk_to_bytes`,
+/// configuring its circuit with the stored parameters
+pub fn halo2_pk_from_bytes(
+    bytes: &[u8],
+) -> io::Result<(ProvingKey<<G1 as CofactorCurve>::Affine>, CircuitParams)> {
+    let (circuit_params, bytes) = split_circuit_params(bytes)?;
+    circuit_params.set_env();
+    let pk = ProvingKey::from_bytes::<RangeWithInstanceCircuitBuilder<Fr>>(
+        bytes,
+        SerdeFormat::RawBytes,
+    )?;
+    Ok((pk, circuit_params))
+}
+
+/// Serialize the Verifying Key prefixed with the
+/// parameters its circuit was built with
+pub fn halo2_vk_to_bytes(
+    vk: &VerifyingKey<<G1 as CofactorCurve>::Affine>,
+    circuit_params: &CircuitParams,
+) -> Vec<u8> {
+    prefix_circuit_params(circuit_params, vk.to_bytes(SerdeFormat::RawBytes))
+}
+
+/// Read a Verifying Key serialized by `halo2_vk_to_bytes`,
+/// configuring its circuit with the stored parameters
+pub fn halo2_vk_from_bytes(
+    bytes: &[u8],
+) -> io::Result<(VerifyingKey<<G1 as CofactorCurve>::Affine>, CircuitParams)> {
+    let (circuit_params, bytes) = split_circuit_params(bytes)?;
+    circuit_params.set_env();
+    let vk = VerifyingKey::from_bytes::<RangeWithInstanceCircuitBuilder<Fr>>(
+        bytes,
+        SerdeFormat::RawBytes,
+    )?;
+    Ok((vk, circuit_params))
+}
+
+fn prefix_circuit_params(circuit_params: &CircuitParams, key: Vec<u8>) -> Vec<u8> {
+    let circuit_params =
+        serde_json::to_vec(circuit_params).expect("circuit params should serialize");
+    let mut bytes = (circuit_params.len() as u32).to_le_bytes().to_vec();
+    bytes.extend(circuit_params);
+    bytes.extend(key);
+    bytes
+}
+
+fn split_circuit_params(bytes: &[u8]) -> io::Result<(CircuitParams, &[u8])> {
+    let truncated = io::Error::new(io::ErrorKind::UnexpectedEof, "truncated key");
+    if bytes.len() < 4 {
+        return Err(truncated);
+    }
+    let (len, bytes) = bytes.split_at(4);
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+    if bytes.len() < len {
+        return Err(truncated);
+    }
+    let (circuit_params, bytes) = bytes.split_at(len);
+    Ok((serde_json::from_slice(circuit_params)?, bytes))
 }
 
+/// Chips the opcodes are constrained with, all of them laid out by the
+/// thread builder on the same columns. The public inputs are copied from
+/// these columns to the instance column of `RangeWithInstanceCircuitBuilder`.
 #[derive(Clone)]
 pub struct PlonkConfig {
     pub(crate) range_chip: RangeChip<Fr>,
@@ -75,8 +251,8 @@ pub struct PlonkConfig {
 }
 
 impl PlonkConfig {
-    pub fn configure(_meta: &mut ConstraintSystem<Fr>) -> Self {
-        let range_chip = RangeChip::default(17);
+    pub fn new(lookup_bits: usize) -> Self {
+        let range_chip = RangeChip::default(lookup_bits);
         let gate_chip = GateChip::default();
 
         PlonkConfig { range_chip, gate_chip }
@@ -1,20 +1,33 @@
 mod acvm_interop;
 mod assigned_map;
+mod chips;
 mod circuit_translator;
 mod constrains;
 mod dimension_measure;
+mod encoding;
 mod halo2_params;
 mod halo2_plonk_api;
 mod tests;
 #[cfg(target_family = "wasm")]
 mod wasm;
 
+/// Environment variable opting in to generate the witnesses of the black box
+/// functions of a program in parallel threads, when set to `1` or `true`
+const PARALLEL_WITNESS_GEN_VAR: &str = "NOIR_HALO2_PARALLEL_WITNESS_GEN";
+
 #[derive(Debug)]
-pub struct AxiomHalo2;
+pub struct AxiomHalo2 {
+    /// Whether the keys generated by this backend lay out the black box
+    /// functions in threads of their own, whose witnesses are generated
+    /// in parallel when proving with them
+    pub(crate) parallel_witness_gen: bool,
+}
 
 impl AxiomHalo2 {
     pub(crate) fn new() -> AxiomHalo2 {
-        AxiomHalo2 {}
+        let parallel_witness_gen =
+            matches!(std::env::var(PARALLEL_WITNESS_GEN_VAR).as_deref(), Ok("1" | "true"));
+        AxiomHalo2 { parallel_witness_gen }
     }
 }
 
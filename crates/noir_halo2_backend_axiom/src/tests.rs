@@ -1,147 +1,164 @@
 #[cfg(test)]
 mod test {
     // put in axiom folder to avoid publishing mods
-    use crate::{circuit_translator::NoirHalo2Translator, dimension_measure::DimensionMeasurement};
-    // use acvm::{acir::native_types::Witness, FieldElement};
-    // use halo2_base::halo2_proofs::{
-    //     dev::{FailureLocation, MockProver, VerifyFailure},
-    //     halo2curves::bn256::Fr,
-    //     plonk::Any,
-    // };
-    use halo2_base::halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use crate::{
+        circuit_translator::NoirHalo2Translator, dimension_measure::DimensionMeasurement,
+        halo2_params::read_halo2_params, halo2_plonk_api::halo2_build,
+    };
+    use acvm::{
+        acir::{
+            circuit::{
+                opcodes::{BlackBoxFuncCall, FunctionInput},
+                Circuit as NoirCircuit, Opcode,
+            },
+            native_types::{Witness, WitnessMap},
+        },
+        FieldElement,
+    };
+    use halo2_base::{
+        gates::builder::{GateThreadBuilder, RangeWithInstanceCircuitBuilder},
+        halo2_proofs::{
+            dev::MockProver,
+            halo2curves::bn256::{Bn256, Fr},
+            poly::kzg::commitment::ParamsKZG,
+            SerdeFormat,
+        },
+    };
     use noir_halo2_backend_common::test_helpers::build_artifacts;
-    use std::marker::PhantomData;
-
-    // #[test]
-    // fn test_public_io_circuit_success() {
-    //     // get circuit
-    //     let (circuit, witness_values) = build_artifacts("10_public_io");
-
-    //     // instantiate halo2 circuit
-    //     let translator = NoirHalo2Translator::<Fr> {
-    //         circuit,
-    //         witness_values,
-    //         _marker: PhantomData::<Fr>,
-    //     };
-    //     let dimension = DimensionMeasurement::measure(&translator).unwrap();
-
-    //     // instance value (known to be 7)
-    //     let instance = vec![Fr::from_raw([7u64, 0, 0, 0])];
-
-    //     // run mock prover expecting success
-    //     let prover = MockProver::run(dimension.k(), &translator, vec![instance]).unwrap();
-    //     assert_eq!(prover.verify(), Ok(()));
-    // }
-
-    // #[test]
-    // fn test_public_io_circuit_fail_instance() {
-    //     // get circuit
-    //     let (circuit, witness_values) = build_artifacts("10_public_io");
-
-    //     // instantiate halo2 circuit
-    //     let translator = NoirHalo2Translator::<Fr> {
-    //         circuit,
-    //         witness_values,
-    //         _marker: PhantomData::<Fr>,
-    //     };
-    //     let dimension = DimensionMeasurement::measure(&translator).unwrap();
-
-    //     // instance value (known to be 7, incorrectly set to 8)
-    //     let instance = vec![Fr::from_raw([8u64, 0, 0, 0])];
-
-    //     // define permutation error expected when instance value is not set or incorrect
-    //     let permutation_error = Err(vec![
-    //         VerifyFailure::Permutation {
-    //             column: (Any::advice(), 0).into(),
-    //             location: FailureLocation::InRegion {
-    //                 region: (7, "region 0").into(),
-    //                 offset: 0,
-    //             },
-    //         },
-    //         VerifyFailure::Permutation {
-    //             column: (Any::Instance, 0usize).into(),
-    //             location: FailureLocation::OutsideRegion { row: 0 },
-    //         },
-    //     ]);
-
-    //     // run mock prover with incorrect instance expecting permutation failure
-    //     let prover = MockProver::run(dimension.k(), &translator, vec![instance]).unwrap();
-    //     assert_eq!(prover.verify(), permutation_error);
-
-    //     // run mock prover with no instance expecting permutation failure
-    //     let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
-    //     assert_eq!(prover.verify(), permutation_error);
-    // }
-
-    // #[test]
-    // fn test_public_io_circuit_fail_witness() {
-    //     // get circuit
-    //     let (circuit, mut witness_values) = build_artifacts("10_public_io");
-
-    //     // mutate witness to be incorrect
-    //     witness_values.insert(Witness(1), FieldElement::from(4u128));
-
-    //     // instantiate halo2 circuit
-    //     let translator = NoirHalo2Translator::<Fr> {
-    //         circuit,
-    //         witness_values,
-    //         _marker: PhantomData::<Fr>,
-    //     };
-    //     let dimension = DimensionMeasurement::measure(&translator).unwrap();
-
-    //     // instance value (known to be 7)
-    //     let instance = vec![Fr::from_raw([7u64, 0, 0, 0])];
-
-    //     // run mock prover expecting success
-    //     let prover = MockProver::run(dimension.k(), &translator, vec![instance]).unwrap();
-    //     assert_eq!(
-    //         prover.verify(),
-    //         Err(vec![VerifyFailure::ConstraintNotSatisfied {
-    //             constraint: ((0, "main_gate").into(), 0, "").into(),
-    //             location: FailureLocation::InRegion {
-    //                 region: (5, "region 0").into(),
-    //                 offset: 5,
-    //             },
-    //             cell_values: vec![
-    //                 (((Any::advice(), 0).into(), 0).into(), String::from("0x4")),
-    //                 (((Any::advice(), 1).into(), 0).into(), String::from("0x4")),
-    //                 (((Any::advice(), 2).into(), 0).into(), String::from("0x7")),
-    //                 (((Any::advice(), 3).into(), 0).into(), String::from("0")),
-    //                 (((Any::advice(), 4).into(), 0).into(), String::from("0")),
-    //                 (((Any::advice(), 4).into(), 1).into(), String::from("0")),
-    //                 (((Any::Fixed, 0).into(), 0).into(), String::from("-1")),
-    //                 (((Any::Fixed, 1).into(), 0).into(), String::from("-1")),
-    //                 (((Any::Fixed, 2).into(), 0).into(), String::from("1")),
-    //                 (((Any::Fixed, 3).into(), 0).into(), String::from("0")),
-    //                 (((Any::Fixed, 4).into(), 0).into(), String::from("0")),
-    //                 (((Any::Fixed, 5).into(), 0).into(), String::from("0")),
-    //                 (((Any::Fixed, 6).into(), 0).into(), String::from("0")),
-    //                 (((Any::Fixed, 7).into(), 0).into(), String::from("0")),
-    //                 (((Any::Fixed, 8).into(), 0).into(), String::from("0")),
-    //             ]
-    //         }])
-    //     );
-    // }
-
-    // #[test]
-    // fn test_add_circuit_success() {
-    //     // get circuit
-    //     let (circuit, witness_values) = build_artifacts("10_public_io");
-    //     // instantiate halo2 circuit
-    //     let translator = NoirHalo2Translator::<Fr> {
-    //         circuit,
-    //         witness_values,
-    //         _marker: PhantomData::<Fr>,
-    //     };
-    //     let dimension = DimensionMeasurement::measure(&translator).unwrap();
-
-    //     // instance value (known to be equal to 7)
-    //     let instance = vec![Fr::from_raw([7u64, 0, 0, 0])];
-
-    //     // run mock prover expecting success
-    //     let prover = MockProver::run(dimension.k(), &translator, vec![instance]).unwrap();
-    //     assert_eq!(prover.verify(), Ok(()));
-    // }
+    use rand::rngs::OsRng;
+    use std::{collections::BTreeMap, marker::PhantomData};
+
+    /// Mock circuit of a translated program sized by its measure,
+    /// along with its public inputs and `k`
+    fn mock_circuit(
+        translator: &NoirHalo2Translator<Fr>,
+        parallel_witness_gen: bool,
+    ) -> (RangeWithInstanceCircuitBuilder<Fr>, Vec<Fr>, u32) {
+        let k = DimensionMeasurement::measure(translator, parallel_witness_gen).unwrap().k();
+        let (builder, public_inputs, _) =
+            halo2_build(translator, GateThreadBuilder::mock(), k, parallel_witness_gen).unwrap();
+        let circuit = RangeWithInstanceCircuitBuilder::mock(builder, public_inputs);
+        let instance = circuit.instance();
+        (circuit, instance, k)
+    }
+
+    #[test]
+    fn test_public_io_circuit_success() {
+        // get circuit
+        let (circuit, witness_values) = build_artifacts("10_public_io", "axiom_halo2_backend");
+
+        // instantiate halo2 circuit
+        let translator =
+            NoirHalo2Translator::<Fr> { circuit, witness_values, _marker: PhantomData::<Fr> };
+        let (circuit, instance, k) = mock_circuit(&translator, false);
+
+        // instance value (known to be 7)
+        assert_eq!(instance, vec![Fr::from_raw([7u64, 0, 0, 0])]);
+
+        // run mock prover expecting success
+        let prover = MockProver::run(k, &circuit, vec![instance]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_public_io_circuit_fail_instance() {
+        // get circuit
+        let (circuit, witness_values) = build_artifacts("10_public_io", "axiom_halo2_backend");
+
+        // instantiate halo2 circuit
+        let translator =
+            NoirHalo2Translator::<Fr> { circuit, witness_values, _marker: PhantomData::<Fr> };
+        let (circuit, _, k) = mock_circuit(&translator, false);
+
+        // instance value (known to be 7, incorrectly set to 8)
+        let instance = vec![Fr::from_raw([8u64, 0, 0, 0])];
+
+        // run mock prover with incorrect instance expecting permutation failure
+        let prover = MockProver::run(k, &circuit, vec![instance]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // run mock prover with no instance expecting permutation failure
+        let prover = MockProver::run(k, &circuit, vec![vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_public_io_circuit_fail_witness() {
+        // get circuit
+        let (circuit, mut witness_values) = build_artifacts("10_public_io", "axiom_halo2_backend");
+
+        // mutate witness to be incorrect
+        witness_values.insert(Witness(1), FieldElement::from(4u128));
+
+        // instantiate halo2 circuit
+        let translator =
+            NoirHalo2Translator::<Fr> { circuit, witness_values, _marker: PhantomData::<Fr> };
+        let (circuit, _, k) = mock_circuit(&translator, false);
+
+        // instance value (known to be 7)
+        let instance = vec![Fr::from_raw([7u64, 0, 0, 0])];
+
+        // run mock prover expecting failure
+        // expects [-1(4) + -1(4) + 1(7)] == 0, should be [-1(3) + -1(4) + 1(7)]
+        let prover = MockProver::run(k, &circuit, vec![instance]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_mul_circuit_fail_witness() {
+        // get circuit
+        let (circuit, mut witness_values) = build_artifacts("1_mul", "axiom_halo2_backend");
+
+        // mutate the expected product to be incorrect
+        witness_values.insert(Witness(3), FieldElement::from(429981697u128));
+
+        // instantiate halo2 circuit
+        let translator =
+            NoirHalo2Translator::<Fr> { circuit, witness_values, _marker: PhantomData::<Fr> };
+        let (circuit, instance, k) = mock_circuit(&translator, false);
+
+        // run mock prover expecting failure
+        let prover = MockProver::run(k, &circuit, vec![instance]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// A single 8-bit AND or XOR of w1 and w2 into w3
+    fn bitwise_circuit(and: bool, output: u128) -> NoirHalo2Translator<Fr> {
+        let input = |index: u32| FunctionInput { witness: Witness(index), num_bits: 8 };
+        let (lhs, rhs, output_witness) = (input(1), input(2), Witness(3));
+        let call = if and {
+            BlackBoxFuncCall::AND { lhs, rhs, output: output_witness }
+        } else {
+            BlackBoxFuncCall::XOR { lhs, rhs, output: output_witness }
+        };
+        let circuit = NoirCircuit {
+            current_witness_index: 3,
+            opcodes: vec![Opcode::BlackBoxFuncCall(call)],
+            ..Default::default()
+        };
+        let witness_values = WitnessMap::from(BTreeMap::from_iter(
+            [0xb5, 0x3c, output]
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| (Witness(i as u32 + 1), FieldElement::from(value))),
+        ));
+        NoirHalo2Translator::<Fr> { circuit, witness_values, _marker: PhantomData::<Fr> }
+    }
+
+    #[test]
+    fn test_bitwise_circuit() {
+        for (and, output, wrong_output) in [(true, 0x34, 0x35), (false, 0x89, 0x34)] {
+            // run mock prover expecting success
+            let (circuit, instance, k) = mock_circuit(&bitwise_circuit(and, output), false);
+            let prover = MockProver::run(k, &circuit, vec![instance]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            // run mock prover with a wrong output expecting failure
+            let (circuit, instance, k) = mock_circuit(&bitwise_circuit(and, wrong_output), false);
+            let prover = MockProver::run(k, &circuit, vec![instance]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
 
     #[test]
     fn test_circuits_native() {
@@ -155,6 +172,10 @@ mod test {
             "7_function",
             "8_bit_and",
             "9_poseidon",
+            "12_bit_xor",
+            "13_sha256",
+            "14_keccak256",
+            "15_ecdsa_secp256k1",
         ];
         for program in test_dirs_names {
             // get circuit
@@ -163,11 +184,52 @@ mod test {
             // instantiate halo2 circuit
             let translator =
                 NoirHalo2Translator::<Fr> { circuit, witness_values, _marker: PhantomData::<Fr> };
-            let dimension = DimensionMeasurement::measure(&translator).unwrap();
+            let (circuit, instance, k) = mock_circuit(&translator, false);
+
+            // run mock prover expecting success
+            let prover = MockProver::run(k, &circuit, vec![instance]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_circuits_native_parallel() {
+        let test_dirs_names =
+            vec!["10_public_io", "13_sha256", "14_keccak256", "15_ecdsa_secp256k1"];
+        for program in test_dirs_names {
+            // get circuit
+            let (circuit, witness_values) = build_artifacts(program, "axiom_halo2_backend");
+
+            // instantiate halo2 circuit with the black box functions in threads of their own
+            let translator =
+                NoirHalo2Translator::<Fr> { circuit, witness_values, _marker: PhantomData::<Fr> };
+            let (circuit, instance, k) = mock_circuit(&translator, true);
 
             // run mock prover expecting success
-            let prover = MockProver::run(dimension.k(), &translator, vec![]).unwrap();
+            let prover = MockProver::run(k, &circuit, vec![instance]).unwrap();
             assert_eq!(prover.verify(), Ok(()));
         }
     }
+
+    #[test]
+    fn test_read_halo2_params_rejects_malformed_crs() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let mut crs = Vec::new();
+        params.write_custom(&mut crs, SerdeFormat::RawBytes);
+        assert!(read_halo2_params(&crs).is_ok());
+
+        // truncated strings are errors rather than panics
+        assert!(read_halo2_params(&crs[..2]).is_err());
+        assert!(read_halo2_params(&crs[..crs.len() - 1]).is_err());
+
+        // so is a k announcing more points than the string holds
+        let mut oversized = crs.clone();
+        oversized[..4].copy_from_slice(&30u32.to_le_bytes());
+        assert!(read_halo2_params(&oversized).is_err());
+
+        // and a point off the curve
+        let mut off_curve = crs.clone();
+        off_curve[4 + 32] ^= 1;
+        assert!(read_halo2_params(&off_curve).is_err());
+    }
 }
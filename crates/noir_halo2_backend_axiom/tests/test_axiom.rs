@@ -2,10 +2,7 @@ use noir_halo2_backend_common::test_helpers::{
     configure_test_dirs, install_nargo, run_nargo_tests,
 };
 
-// TODO: Axiom is currently not working.
-// tho tests passes the crs size does not
-// change with each test.
-// todo: add circuit builder API (and maybe multithreading)
+// todo: multithreading
 #[test]
 fn test_axiom_backend() {
     let test_program_dirs = configure_test_dirs();
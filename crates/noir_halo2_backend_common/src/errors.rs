@@ -1,4 +1,4 @@
-use acvm::acir::BlackBoxFunc;
+use acvm::acir::{circuit::Opcode, BlackBoxFunc};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -38,6 +38,30 @@ pub enum Error {
     #[error("Unsupported Black Box Function: {0}")]
     _UnsupportedBlackBoxFunc(BlackBoxFunc),
 
+    #[error("Unsupported opcode: {}", .0.name())]
+    UnsupportedOpcode(Opcode),
+
+    #[error("{0} is not supported by this backend")]
+    UnsupportedFeature(&'static str),
+
+    #[error("Failed to read the common reference string ({0})")]
+    ReadCRS(std::io::Error),
+
+    #[error("The common reference string fits circuits of 2^{0} rows but the circuit needs 2^{1}")]
+    CRSTooSmall(u32, u32),
+
+    #[error("Failed to read the proving key ({0})")]
+    ReadProvingKey(std::io::Error),
+
+    #[error("Failed to read the verification key ({0})")]
+    ReadVerificationKey(std::io::Error),
+
+    #[error("Malformed proof, expected a halo2 proof laid out as by the EVM transcript")]
+    MalformedProof,
+
+    #[error("Halo2 synthesis failed ({0})")]
+    Synthesis(#[source] Box<dyn std::error::Error + Send + Sync>),
+
     #[error(transparent)]
     FromFeature(#[from] FeatureError),
 
@@ -45,6 +69,13 @@ pub enum Error {
     CRS(#[from] CRSError),
 }
 
+impl Error {
+    /// Wrap the `plonk::Error` of any halo2 fork
+    pub fn synthesis(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Error::Synthesis(Box::new(error))
+    }
+}
+
 #[derive(Debug, Error)]
 #[error(transparent)]
 pub struct BackendError(#[from] Error);
@@ -1,4 +1,8 @@
-use acvm::acir::native_types::Witness;
+use crate::errors::Error;
+use acvm::acir::{
+    circuit::{Circuit, Opcode},
+    native_types::Witness,
+};
 
 pub trait Secp256k1FieldConversion {
     type Base;
@@ -71,3 +75,15 @@ macro_rules! impl_noir_field_to_secp255k1_field_conversion {
         }
     };
 }
+
+/// Fail on the first opcode of `circuit` that the backend doesn't support,
+/// before its translation panics on it
+pub fn ensure_opcodes_supported(
+    circuit: &Circuit,
+    supports_opcode: impl Fn(&Opcode) -> bool,
+) -> Result<(), Error> {
+    match circuit.opcodes.iter().find(|opcode| !supports_opcode(opcode)) {
+        Some(opcode) => Err(Error::UnsupportedOpcode(opcode.clone())),
+        None => Ok(()),
+    }
+}
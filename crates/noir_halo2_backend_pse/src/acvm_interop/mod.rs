@@ -1,6 +1,6 @@
 mod common_reference_string;
 mod proof_system;
-mod pwg;
+pub(crate) mod pwg;
 mod smart_contract;
 
 use crate::PseHalo2;
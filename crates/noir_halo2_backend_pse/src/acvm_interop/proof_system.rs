@@ -1,24 +1,35 @@
+use super::pwg::halo2_field_to_noir_field;
 use crate::{
+    chips::aggregation::{decide, protocol_to_fields},
     circuit_translator::NoirHalo2Translator,
     dimension_measure::DimensionMeasurement,
-    halo2_plonk_api::{halo2_keygen, halo2_prove, halo2_verify, OpcodeFlags},
+    encoding::{elements_to_fields, proof_elements, vk_to_fields},
+    halo2_plonk_api::{
+        halo2_keygen, halo2_prove, halo2_prove_recursive, halo2_verify, halo2_verify_recursive,
+        halo2_vk_from_bytes, halo2_vk_to_bytes, OpcodeFlags,
+    },
     PseHalo2,
 };
 use acvm::{
     acir::{
-        circuit::{Circuit as NoirCircuit, Opcode},
+        circuit::{opcodes::BlackBoxFuncCall, Circuit as NoirCircuit, Opcode},
         native_types::WitnessMap,
         BlackBoxFunc,
     },
     FieldElement, Language, ProofSystemCompiler,
 };
-use noir_halo2_backend_common::{errors::BackendError, noir_field_to_halo2_field};
+use noir_halo2_backend_common::{
+    errors::{BackendError, Error},
+    noir_field_to_halo2_field,
+    utils::ensure_opcodes_supported,
+};
 use pse_halo2wrong::halo2::{
     halo2curves::bn256::{Bn256, Fr, G1Affine},
-    plonk::{ProvingKey, VerifyingKey},
-    poly::kzg::commitment::ParamsKZG,
+    plonk::ProvingKey,
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
     SerdeFormat,
 };
+use pse_snark_verifier::system::halo2::{compile, Config};
 use std::marker::PhantomData;
 
 impl ProofSystemCompiler for PseHalo2 {
@@ -32,7 +43,7 @@ impl ProofSystemCompiler for PseHalo2 {
             _marker: PhantomData::<Fr>,
         };
 
-        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+        let dimension = DimensionMeasurement::measure(&translator).map_err(Error::synthesis)?;
         let k = dimension.k();
 
         Ok(1 << k)
@@ -45,6 +56,8 @@ impl ProofSystemCompiler for PseHalo2 {
         mut common_reference_string: &[u8],
         circuit: &NoirCircuit,
     ) -> Result<(Vec<u8>, Vec<u8>), BackendError> {
+        ensure_opcodes_supported(circuit, |opcode| self.supports_opcode(opcode))?;
+
         let translator = NoirHalo2Translator::<Fr> {
             circuit: circuit.clone(),
             witness_values: WitnessMap::new(),
@@ -53,10 +66,11 @@ impl ProofSystemCompiler for PseHalo2 {
 
         let params =
             ParamsKZG::<Bn256>::read_custom(&mut common_reference_string, SerdeFormat::RawBytes)
-                .unwrap();
-        let (pk, vk) = halo2_keygen(&translator, &params);
+                .map_err(Error::ReadCRS)?;
+        let opcode_flags = OpcodeFlags::new(&circuit.opcodes);
+        let (pk, vk) = halo2_keygen(&translator, &params).map_err(Error::synthesis)?;
 
-        Ok((pk.to_bytes(SerdeFormat::RawBytes), vk.to_bytes(SerdeFormat::RawBytes)))
+        Ok((pk.to_bytes(SerdeFormat::RawBytes), halo2_vk_to_bytes(&vk, &opcode_flags)))
     }
 
     /// Generate proof with Proving Key
@@ -66,11 +80,13 @@ impl ProofSystemCompiler for PseHalo2 {
         circuit: &NoirCircuit,
         witness_values: WitnessMap,
         proving_key: &[u8],
-        _is_recursive: bool,
+        is_recursive: bool,
     ) -> Result<Vec<u8>, BackendError> {
+        ensure_opcodes_supported(circuit, |opcode| self.supports_opcode(opcode))?;
+
         let params =
             ParamsKZG::<Bn256>::read_custom(&mut common_reference_string, SerdeFormat::RawBytes)
-                .unwrap();
+                .map_err(Error::ReadCRS)?;
 
         let opcode_flags = OpcodeFlags::new(&circuit.opcodes);
 
@@ -79,25 +95,32 @@ impl ProofSystemCompiler for PseHalo2 {
             SerdeFormat::RawBytes,
             opcode_flags,
         )
-        .unwrap();
+        .map_err(Error::ReadProvingKey)?;
+
+        let mut translator = NoirHalo2Translator::<Fr> {
+            circuit: circuit.clone(),
+            witness_values,
+            _marker: PhantomData::<Fr>,
+        };
+        // aggregation objects are solved by the backend
+        translator.solve_aggregation_objects().map_err(Error::synthesis)?;
 
         let instance: Vec<Fr> = circuit
             .public_inputs()
             .indices()
             .iter()
-            .map(|index| match witness_values.get_index(*index) {
+            .map(|index| match translator.witness_values.get_index(*index) {
                 Some(val) => noir_field_to_halo2_field(*val),
                 None => noir_field_to_halo2_field(FieldElement::zero()),
             })
             .collect();
 
-        let translator = NoirHalo2Translator::<Fr> {
-            circuit: circuit.clone(),
-            witness_values,
-            _marker: PhantomData::<Fr>,
-        };
-
-        let proof = halo2_prove(translator, &params, &pk, &instance[..]);
+        let proof = if is_recursive {
+            halo2_prove_recursive(translator, &params, &pk, &instance[..])
+        } else {
+            halo2_prove(translator, &params, &pk, &instance[..])
+        }
+        .map_err(Error::synthesis)?;
 
         Ok(proof)
     }
@@ -110,32 +133,57 @@ impl ProofSystemCompiler for PseHalo2 {
         public_inputs: WitnessMap,
         circuit: &NoirCircuit,
         verification_key: &[u8],
-        _is_recursive: bool,
+        is_recursive: bool,
     ) -> Result<bool, BackendError> {
         let params =
             ParamsKZG::<Bn256>::read_custom(&mut common_reference_string, SerdeFormat::RawBytes)
-                .unwrap();
+                .map_err(Error::ReadCRS)?;
+
+        // the pairing checks of the aggregated proofs are deferred to the
+        // verifier of the aggregation objects, which must thus be public
+        // for the proofs they aggregate not to be left unchecked
+        let public_indices = circuit.public_inputs().indices();
+        for opcode in &circuit.opcodes {
+            if let Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RecursiveAggregation {
+                output_aggregation_object,
+                ..
+            }) = opcode
+            {
+                let aggregation_object: Option<Vec<Fr>> = output_aggregation_object
+                    .iter()
+                    .map(|witness| {
+                        public_indices
+                            .contains(&witness.0)
+                            .then(|| public_inputs.get(witness))
+                            .flatten()
+                            .map(|el| noir_field_to_halo2_field(*el))
+                    })
+                    .collect();
+                match aggregation_object {
+                    Some(aggregation_object) if decide(&params, &aggregation_object) => {}
+                    _ => return Ok(false),
+                }
+            }
+        }
 
-        let opcode_flags = OpcodeFlags::new(&circuit.opcodes);
-
-        let vk = VerifyingKey::<G1Affine>::from_bytes::<NoirHalo2Translator<Fr>>(
-            verification_key,
-            SerdeFormat::RawBytes,
-            opcode_flags,
-        )
-        .unwrap();
+        let (vk, _) = halo2_vk_from_bytes(verification_key).map_err(Error::ReadVerificationKey)?;
 
         let instance: Vec<Fr> =
             public_inputs.into_iter().map(|(_, el)| noir_field_to_halo2_field(el)).collect();
 
-        Ok(halo2_verify(&params, &vk, proof, &instance[..]).is_ok())
+        let verified = if is_recursive {
+            halo2_verify_recursive(&params, &vk, proof, &instance[..])
+        } else {
+            halo2_verify(&params, &vk, proof, &instance[..])
+        };
+        Ok(verified.is_ok())
     }
 
     /// Type of constraint system
     // The pse-halo2 backend supports Plonkish constraint
-    // of width 5 but we only used 3
+    // of width 5, one witness per main gate column
     fn np_language(&self) -> Language {
-        Language::PLONKCSat { width: 3 }
+        Language::PLONKCSat { width: 5 }
     }
 
     /// Opcodes supported by pse-halo2 backend
@@ -144,37 +192,61 @@ impl ProofSystemCompiler for PseHalo2 {
             Opcode::Arithmetic(_) => true,
             Opcode::Directive(_) | Opcode::Brillig(_) => true,
             Opcode::BlackBoxFuncCall(func) => match func.get_black_box_func() {
-                BlackBoxFunc::RANGE | BlackBoxFunc::AND => true,
-                BlackBoxFunc::XOR
+                BlackBoxFunc::RANGE
+                | BlackBoxFunc::AND
+                | BlackBoxFunc::XOR
                 | BlackBoxFunc::SHA256
-                | BlackBoxFunc::Blake2s
-                | BlackBoxFunc::Pedersen
-                | BlackBoxFunc::HashToField128Security
+                | BlackBoxFunc::Keccak256
                 | BlackBoxFunc::EcdsaSecp256k1
                 | BlackBoxFunc::EcdsaSecp256r1
-                | BlackBoxFunc::Keccak256
+                | BlackBoxFunc::Pedersen
                 | BlackBoxFunc::FixedBaseScalarMul
-                | BlackBoxFunc::RecursiveAggregation
-                | BlackBoxFunc::SchnorrVerify => false,
+                | BlackBoxFunc::Blake2s
+                | BlackBoxFunc::HashToField128Security
+                | BlackBoxFunc::SchnorrVerify
+                | BlackBoxFunc::RecursiveAggregation => true,
             },
-            Opcode::Block(_) | Opcode::ROM(_) | Opcode::RAM(_) => false,
+            Opcode::Block(_) | Opcode::ROM(_) | Opcode::RAM(_) => true,
         }
     }
 
+    /// Encode a proof as the fields of its transcript: the public inputs,
+    /// then its points as the limbs of their coordinates and its scalars
     fn proof_as_fields(
         &self,
-        _proof: &[u8],
-        _public_inputs: WitnessMap,
+        proof: &[u8],
+        public_inputs: WitnessMap,
     ) -> Result<Vec<FieldElement>, Self::Error> {
-        panic!("vk_as_fields not supported in this backend");
+        let elements = proof_elements(proof).ok_or(BackendError::from(Error::MalformedProof))?;
+
+        let mut fields: Vec<FieldElement> = public_inputs.into_iter().map(|(_, el)| el).collect();
+        fields.extend(elements_to_fields(&elements).into_iter().map(halo2_field_to_noir_field));
+        Ok(fields)
     }
 
+    /// Encode a Verification Key as its size and commitments followed by its
+    /// compiled protocol, along with the hash it initializes transcripts with
     fn vk_as_fields(
         &self,
-        _common_reference_string: &[u8],
-        _verification_key: &[u8],
+        mut common_reference_string: &[u8],
+        verification_key: &[u8],
     ) -> Result<(Vec<FieldElement>, FieldElement), Self::Error> {
-        panic!("vk_as_fields not supported in this backend");
+        let mut params =
+            ParamsKZG::<Bn256>::read_custom(&mut common_reference_string, SerdeFormat::RawBytes)
+                .map_err(Error::ReadCRS)?;
+
+        let (vk, _) = halo2_vk_from_bytes(verification_key).map_err(Error::ReadVerificationKey)?;
+        params.downsize(vk.get_domain().k());
+
+        // the number of public inputs is set by the aggregation verifying the proof
+        let protocol = compile(&params, &vk, Config::kzg().with_num_instance(vec![0]));
+
+        let mut fields = vk_to_fields(&vk);
+        fields.extend(protocol_to_fields(&protocol));
+        Ok((
+            fields.into_iter().map(halo2_field_to_noir_field).collect(),
+            halo2_field_to_noir_field(vk.transcript_repr()),
+        ))
     }
 }
 
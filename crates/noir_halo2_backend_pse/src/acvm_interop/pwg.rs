@@ -1,30 +1,51 @@
 use acvm::{acir::FieldElement, BlackBoxFunctionSolver, BlackBoxResolutionError};
+use noir_halo2_backend_common::noir_field_to_halo2_field;
+use pse_halo2wrong::{curves::group::ff::PrimeField, halo2::halo2curves::bn256::Fr};
 
-use crate::PseHalo2;
+use crate::{
+    chips::grumpkin::{pedersen_commitment, schnorr_verify, GrumpkinPoint},
+    PseHalo2,
+};
 
 impl BlackBoxFunctionSolver for PseHalo2 {
     fn schnorr_verify(
         &self,
-        _public_key_x: &FieldElement,
-        _public_key_y: &FieldElement,
-        _signature: &[u8],
-        _message: &[u8],
+        public_key_x: &FieldElement,
+        public_key_y: &FieldElement,
+        signature: &[u8],
+        message: &[u8],
     ) -> Result<bool, BlackBoxResolutionError> {
-        Err(BlackBoxResolutionError::Unsupported(acvm::acir::BlackBoxFunc::SchnorrVerify))
+        let public_key = GrumpkinPoint {
+            x: noir_field_to_halo2_field(*public_key_x),
+            y: noir_field_to_halo2_field(*public_key_y),
+        };
+        Ok(schnorr_verify(&public_key, signature, message))
     }
 
     fn pedersen(
         &self,
-        _inputs: &[FieldElement],
-        _domain_separator: u32,
+        inputs: &[FieldElement],
+        domain_separator: u32,
     ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
-        Err(BlackBoxResolutionError::Unsupported(acvm::acir::BlackBoxFunc::Pedersen))
+        let inputs: Vec<Fr> =
+            inputs.iter().map(|input| noir_field_to_halo2_field(*input)).collect();
+        let commitment = pedersen_commitment(&inputs, domain_separator);
+        Ok((halo2_field_to_noir_field(commitment.x), halo2_field_to_noir_field(commitment.y)))
     }
 
     fn fixed_base_scalar_mul(
         &self,
-        _input: &FieldElement,
+        input: &FieldElement,
     ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
-        Err(BlackBoxResolutionError::Unsupported(acvm::acir::BlackBoxFunc::FixedBaseScalarMul))
+        let product = GrumpkinPoint::generator().mul(&noir_field_to_halo2_field(*input));
+        Ok((halo2_field_to_noir_field(product.x), halo2_field_to_noir_field(product.y)))
     }
 }
+
+pub(crate) fn halo2_field_to_noir_field(halo2_ele: Fr) -> FieldElement {
+    let mut bytes = halo2_ele.to_repr();
+    bytes.as_mut().reverse();
+    FieldElement::from_be_bytes_reduce(bytes.as_ref())
+}
+
+noir_field_to_halo2_field!(Fr);
@@ -1,6 +1,6 @@
-use crate::{circuit_translator::NoirHalo2Translator, halo2_plonk_api::OpcodeFlags, PseHalo2};
+use crate::{halo2_plonk_api::halo2_vk_from_bytes, PseHalo2};
 use acvm::{acir::circuit::Circuit, SmartContract};
-use noir_halo2_backend_common::errors::BackendError;
+use noir_halo2_backend_common::errors::{BackendError, Error};
 use pse_halo2wrong::{
     curves::bn256::{Bn256, Fq, Fr, G1Affine},
     halo2::{
@@ -24,7 +24,7 @@ fn gen_evm_verifier(
     params: &ParamsKZG<Bn256>,
     vk: &VerifyingKey<G1Affine>,
     num_instance: Vec<usize>,
-) -> String {
+) -> Result<String, Error> {
     let protocol = compile(params, vk, Config::kzg().with_num_instance(num_instance.clone()));
     let vk = (params.get_g()[0], params.g2(), params.s_g2()).into();
 
@@ -33,10 +33,11 @@ fn gen_evm_verifier(
     let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
 
     let instances = transcript.load_instances(num_instance);
-    let proof = PlonkVerifier::read_proof(&vk, &protocol, &instances, &mut transcript).unwrap();
-    PlonkVerifier::verify(&vk, &protocol, &instances, &proof).unwrap();
+    let proof = PlonkVerifier::read_proof(&vk, &protocol, &instances, &mut transcript)
+        .map_err(|_| Error::MalformedProof)?;
+    PlonkVerifier::verify(&vk, &protocol, &instances, &proof).map_err(|_| Error::MalformedProof)?;
 
-    loader.yul_code()
+    Ok(loader.yul_code())
 }
 
 impl SmartContract for PseHalo2 {
@@ -52,23 +53,15 @@ impl SmartContract for PseHalo2 {
         // get trusted setup params
         let params =
             ParamsKZG::<Bn256>::read_custom(&mut common_reference_string, SerdeFormat::RawBytes)
-                .unwrap();
+                .map_err(Error::ReadCRS)?;
 
-        // get opcode params informing circuit translation
-        let opcode_flags = OpcodeFlags::default();
-
-        // generate verifying key
-        let vk = VerifyingKey::<G1Affine>::from_bytes::<NoirHalo2Translator<Fr>>(
-            verification_key,
-            SerdeFormat::RawBytes,
-            opcode_flags,
-        )
-        .unwrap();
+        // read verifying key along with the opcode flags configuring its circuit
+        let (vk, _) = halo2_vk_from_bytes(verification_key).map_err(Error::ReadVerificationKey)?;
 
         // get number of public inputs used in circuit
         let num_instance = circuit.public_inputs().0.len();
 
         // generate Yul verifier and return
-        Ok(gen_evm_verifier(&params, &vk, vec![num_instance]))
+        Ok(gen_evm_verifier(&params, &vk, vec![num_instance])?)
     }
 }
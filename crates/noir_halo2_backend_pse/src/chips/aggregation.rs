@@ -0,0 +1,268 @@
+use super::ecdsa::{BIT_LEN_LIMB, NUMBER_OF_LIMBS};
+use crate::encoding::{
+    bytes_to_fields, fields_to_bytes, fields_to_elements, poseidon_proof_bytes,
+    vk_commitments_from_fields,
+};
+use pse_ecc::{integer::rns::Rns, BaseFieldEccChip, EccConfig};
+use pse_halo2wrong::{
+    curves::{
+        bn256::{Bn256, Fq, Fr, G1Affine},
+        group::prime::PrimeCurveAffine,
+        CurveAffine,
+    },
+    halo2::{
+        circuit::Value,
+        plonk::Error,
+        poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+    },
+    RegionCtx,
+};
+use pse_maingate::{AssignedValue, MainGate, MainGateConfig, MainGateInstructions, RangeConfig};
+use pse_snark_verifier::{
+    loader::{self, native::NativeLoader},
+    pcs::{
+        kzg::{
+            Gwc19, KzgAccumulator, KzgAs, KzgDecidingKey, KzgSuccinctVerifyingKey, LimbsEncoding,
+            LimbsEncodingInstructions,
+        },
+        AccumulationDecider, AccumulationScheme, AccumulatorEncoding,
+    },
+    system::halo2::transcript,
+    util::arithmetic::{fe_from_limbs, fe_to_limbs},
+    verifier::{self, plonk::PlonkProtocol, SnarkVerifier},
+};
+use std::rc::Rc;
+
+/// Number of limbs of an aggregation object, the coordinates of the two
+/// points of a KZG accumulator
+pub(crate) const AGGREGATION_OBJECT_LEN: usize = 4 * NUMBER_OF_LIMBS;
+
+// poseidon parameters of the transcript of the proofs verified in circuit
+const T: usize = 5;
+const RATE: usize = 4;
+const R_F: usize = 8;
+const R_P: usize = 60;
+
+type As = KzgAs<Bn256, Gwc19>;
+type PlonkSuccinctVerifier =
+    verifier::plonk::PlonkSuccinctVerifier<As, LimbsEncoding<NUMBER_OF_LIMBS, BIT_LEN_LIMB>>;
+type EccChip = BaseFieldEccChip<G1Affine, NUMBER_OF_LIMBS, BIT_LEN_LIMB>;
+type Halo2Loader<'a> = loader::halo2::Halo2Loader<'a, G1Affine, EccChip>;
+
+/// Transcript of the proofs that can be verified by the aggregation chip
+pub(crate) type PoseidonTranscript<L, S> =
+    transcript::halo2::PoseidonTranscript<G1Affine, L, S, T, RATE, R_F, R_P>;
+
+/// Assigned inputs and aggregation objects of a recursive aggregation
+pub(crate) struct AssignedAggregation {
+    pub(crate) public_inputs: Vec<AssignedValue<Fr>>,
+    pub(crate) key_hash: AssignedValue<Fr>,
+    pub(crate) input_aggregation_object: Vec<AssignedValue<Fr>>,
+    pub(crate) output_aggregation_object: Vec<AssignedValue<Fr>>,
+}
+
+/// Configuration of the aggregation chip, which shares the main gate and the
+/// range tables of the circuit with halo2wrong's BN254 base field ECC chip
+#[derive(Clone, Debug)]
+pub struct AggregationConfig {
+    main_gate_config: MainGateConfig,
+    range_config: RangeConfig,
+}
+
+impl AggregationConfig {
+    pub fn new(main_gate_config: MainGateConfig, range_config: RangeConfig) -> Self {
+        AggregationConfig { main_gate_config, range_config }
+    }
+
+    fn ecc_config(&self) -> EccConfig {
+        EccConfig::new(self.range_config.clone(), self.main_gate_config.clone())
+    }
+}
+
+/// Succinct verification of KZG proofs on the halo2 loader of snark-verifier.
+///
+/// The pairing check of a proof is deferred: the proof is reduced to a KZG
+/// accumulator, folded with the accumulator of the input aggregation object,
+/// and the result is output as the limbs of its two points. The final
+/// verifier decides the last accumulator with a single pairing.
+#[derive(Clone, Debug)]
+pub struct AggregationChip {
+    config: AggregationConfig,
+    svk: KzgSuccinctVerifyingKey<G1Affine>,
+}
+
+impl AggregationChip {
+    pub fn new(config: AggregationConfig) -> Self {
+        // the first point of the structured reference string is the generator
+        AggregationChip { config, svk: KzgSuccinctVerifyingKey::new(G1Affine::generator()) }
+    }
+
+    /// Overflow bit lengths the range chip needs to constrain the limbs of the
+    /// BN254 base field
+    pub fn overflow_bit_lens() -> Vec<usize> {
+        Rns::<Fq, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::construct().overflow_lengths()
+    }
+
+    /// Verify `proof` of `protocol` for `public_inputs`, folding the input
+    /// aggregation object if any. The loader takes the region over, which is
+    /// handed back along with the assigned inputs and output.
+    pub(crate) fn aggregate<'a>(
+        &self,
+        mut ctx: RegionCtx<'a, Fr>,
+        protocol: &PlonkProtocol<G1Affine>,
+        public_inputs: &[Value<Fr>],
+        proof: Value<&[u8]>,
+        input_aggregation_object: Option<&[Value<Fr>]>,
+    ) -> Result<(RegionCtx<'a, Fr>, AssignedAggregation), Error> {
+        // the verifying key is baked into the circuit, and so is its hash
+        let main_gate = MainGate::<Fr>::new(self.config.main_gate_config.clone());
+        let key_hash = main_gate.assign_constant(
+            &mut ctx,
+            protocol.transcript_initial_state.ok_or(Error::Synthesis)?,
+        )?;
+
+        let loader = Halo2Loader::new(EccChip::new(self.config.ecc_config()), ctx);
+        let assigned = {
+            let public_inputs: Vec<_> =
+                public_inputs.iter().map(|value| loader.assign_scalar(*value)).collect();
+            let input_limbs: Vec<_> = input_aggregation_object
+                .unwrap_or_default()
+                .iter()
+                .map(|value| loader.assign_scalar(*value))
+                .collect();
+
+            let protocol = protocol.loaded(&loader);
+            let instances = [public_inputs.clone()];
+            let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(&loader, proof);
+            let proof = PlonkSuccinctVerifier::read_proof(
+                &self.svk,
+                &protocol,
+                &instances,
+                &mut transcript,
+            )
+            .map_err(|_| Error::Synthesis)?;
+            let mut accumulators =
+                PlonkSuccinctVerifier::verify(&self.svk, &protocol, &instances, &proof)
+                    .map_err(|_| Error::Synthesis)?;
+            if input_aggregation_object.is_some() {
+                let input_limbs: Vec<_> = input_limbs.iter().collect();
+                accumulators.push(
+                    LimbsEncoding::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>::from_repr(&input_limbs)
+                        .map_err(|_| Error::Synthesis)?,
+                );
+            }
+
+            // folding accumulators without blinding needs no proof
+            let mut transcript =
+                PoseidonTranscript::<Rc<Halo2Loader>, _>::new(&loader, Value::known(&[][..]));
+            let proof = As::read_proof(&Default::default(), &accumulators, &mut transcript)
+                .map_err(|_| Error::Synthesis)?;
+            let accumulator = As::verify(&Default::default(), &accumulators, &proof)
+                .map_err(|_| Error::Synthesis)?;
+
+            let mut output_limbs = Vec::with_capacity(AGGREGATION_OBJECT_LEN);
+            for point in [accumulator.lhs, accumulator.rhs] {
+                output_limbs.extend(
+                    loader
+                        .ecc_chip()
+                        .assign_ec_point_to_limbs(&mut loader.ctx_mut(), point.assigned())?,
+                );
+            }
+
+            AssignedAggregation {
+                public_inputs: public_inputs.iter().map(|input| input.assigned().clone()).collect(),
+                key_hash,
+                input_aggregation_object: input_limbs
+                    .iter()
+                    .map(|limb| limb.assigned().clone())
+                    .collect(),
+                output_aggregation_object: output_limbs,
+            }
+        };
+
+        let ctx = Rc::try_unwrap(loader).map_err(|_| Error::Synthesis)?.into_ctx();
+        Ok((ctx, assigned))
+    }
+}
+
+/// Aggregation object of `proof` verified natively, folding the input
+/// aggregation object if any. It mirrors the aggregation chip so that the
+/// witness of its output can be solved ahead of proving.
+pub(crate) fn aggregate_native(
+    protocol: &PlonkProtocol<G1Affine>,
+    public_inputs: &[Fr],
+    proof: &[u8],
+    input_aggregation_object: Option<&[Fr]>,
+) -> Option<Vec<Fr>> {
+    let svk = KzgSuccinctVerifyingKey::new(G1Affine::generator());
+    let instances = [public_inputs.to_vec()];
+    let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(proof);
+    let proof =
+        PlonkSuccinctVerifier::read_proof(&svk, protocol, &instances, &mut transcript).ok()?;
+    let mut accumulators =
+        PlonkSuccinctVerifier::verify(&svk, protocol, &instances, &proof).ok()?;
+    if let Some(limbs) = input_aggregation_object {
+        accumulators.push(accumulator_from_limbs(limbs)?);
+    }
+
+    let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(&[][..]);
+    let proof = As::read_proof(&Default::default(), &accumulators, &mut transcript).ok()?;
+    let accumulator = As::verify(&Default::default(), &accumulators, &proof).ok()?;
+
+    Some(
+        [accumulator.lhs.x, accumulator.lhs.y, accumulator.rhs.x, accumulator.rhs.y]
+            .map(fe_to_limbs::<Fq, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>)
+            .concat(),
+    )
+}
+
+/// Run the deferred pairing check of an aggregation object
+pub(crate) fn decide(params: &ParamsKZG<Bn256>, aggregation_object: &[Fr]) -> bool {
+    let dk: KzgDecidingKey<Bn256> = (params.get_g()[0], params.g2(), params.s_g2()).into();
+    accumulator_from_limbs(aggregation_object)
+        .map_or(false, |accumulator| As::decide(&dk, accumulator).is_ok())
+}
+
+/// KZG accumulator of the limbs of an aggregation object
+fn accumulator_from_limbs(limbs: &[Fr]) -> Option<KzgAccumulator<G1Affine, NativeLoader>> {
+    if limbs.len() != AGGREGATION_OBJECT_LEN {
+        return None;
+    }
+    let coordinates: Vec<Fq> = limbs
+        .chunks(NUMBER_OF_LIMBS)
+        .map(|limbs| {
+            Some(fe_from_limbs::<Fr, Fq, NUMBER_OF_LIMBS, BIT_LEN_LIMB>(limbs.try_into().ok()?))
+        })
+        .collect::<Option<_>>()?;
+    let lhs = Option::from(G1Affine::from_xy(coordinates[0], coordinates[1]))?;
+    let rhs = Option::from(G1Affine::from_xy(coordinates[2], coordinates[3]))?;
+    Some(KzgAccumulator::new(lhs, rhs))
+}
+
+/// Proof read by the aggregation of the fields of `proof_as_fields`, past the
+/// public inputs they start with
+pub(crate) fn proof_from_fields(fields: &[Fr], num_instance: usize) -> Option<Vec<u8>> {
+    Some(poseidon_proof_bytes(&fields_to_elements(fields.get(num_instance..)?)))
+}
+
+/// Encode a compiled protocol as the bytes of its fields but the preprocessed
+/// commitments, which lead the fields of a verifying key
+pub(crate) fn protocol_to_fields(protocol: &PlonkProtocol<G1Affine>) -> Vec<Fr> {
+    let mut protocol = protocol.clone();
+    protocol.preprocessed.clear();
+    bytes_to_fields(&serde_json::to_vec(&protocol).expect("protocol should serialize"))
+}
+
+/// Decode the protocol of a proof with `num_instance` public inputs from the
+/// fields of its verifying key
+pub(crate) fn protocol_from_fields(
+    fields: &[Fr],
+    num_instance: usize,
+) -> Option<PlonkProtocol<G1Affine>> {
+    let (preprocessed, fields) = vk_commitments_from_fields(fields)?;
+    let mut protocol: PlonkProtocol<G1Affine> =
+        serde_json::from_slice(&fields_to_bytes(fields)?).ok()?;
+    protocol.preprocessed = preprocessed;
+    protocol.num_instance = vec![num_instance];
+    Some(protocol)
+}
@@ -0,0 +1,283 @@
+use pse_halo2wrong::{
+    curves::group::ff::PrimeField,
+    halo2::{
+        circuit::{AssignedCell, Layouter, Value},
+        halo2curves::bn256::Fr,
+        plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+        poly::Rotation,
+    },
+    RegionCtx,
+};
+
+/// Bit length of the limbs AND and XOR are looked up by, the tables of
+/// every pair of limbs holding `2^(2 * BITWISE_LIMB_BITS)` rows
+pub(crate) const BITWISE_LIMB_BITS: usize = 4;
+
+/// Bit length of the limbs of the tables blake2s looks its bytes up in
+pub(crate) const BYTE_LIMB_BITS: usize = 8;
+
+/// Bitwise operations backed by the lookup tables
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BitwiseOp {
+    And,
+    Xor,
+}
+
+impl BitwiseOp {
+    fn apply(&self, lhs: u64, rhs: u64) -> u64 {
+        match self {
+            BitwiseOp::And => lhs & rhs,
+            BitwiseOp::Xor => lhs ^ rhs,
+        }
+    }
+}
+
+/// Assigned cells holding the full values of a bitwise operation
+pub(crate) type AssignedBitwise =
+    (AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>);
+
+/// Configuration of the bitwise chip.
+///
+/// Each operand is decomposed into little-endian limbs of `limb_bits` bits
+/// with a running sum, and each triple of limbs is looked up in a table
+/// holding every `(lhs, rhs, lhs & rhs, lhs ^ rhs)` combination.
+#[derive(Clone, Debug)]
+pub struct BitwiseConfig {
+    pub(crate) limb_bits: usize,
+
+    lhs: Column<Advice>,
+    rhs: Column<Advice>,
+    out: Column<Advice>,
+    lhs_limb: Column<Advice>,
+    rhs_limb: Column<Advice>,
+    out_limb: Column<Advice>,
+
+    q_running: Selector,
+    q_last: Selector,
+    q_and: Selector,
+    q_xor: Selector,
+
+    pub(crate) table_lhs: TableColumn,
+    pub(crate) table_rhs: TableColumn,
+    pub(crate) table_and: TableColumn,
+    pub(crate) table_xor: TableColumn,
+}
+
+#[derive(Clone, Debug)]
+pub struct BitwiseChip {
+    config: BitwiseConfig,
+}
+
+impl BitwiseChip {
+    pub fn new(config: BitwiseConfig) -> Self {
+        BitwiseChip { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<Fr>, limb_bits: usize) -> BitwiseConfig {
+        let lhs = meta.advice_column();
+        let rhs = meta.advice_column();
+        let out = meta.advice_column();
+        let lhs_limb = meta.advice_column();
+        let rhs_limb = meta.advice_column();
+        let out_limb = meta.advice_column();
+
+        meta.enable_equality(lhs);
+        meta.enable_equality(rhs);
+        meta.enable_equality(out);
+
+        let q_running = meta.selector();
+        let q_last = meta.selector();
+        let q_and = meta.complex_selector();
+        let q_xor = meta.complex_selector();
+
+        let table_lhs = meta.lookup_table_column();
+        let table_rhs = meta.lookup_table_column();
+        let table_and = meta.lookup_table_column();
+        let table_xor = meta.lookup_table_column();
+
+        // acc_i = acc_{i+1} * 2^limb_bits + limb_i, and the last
+        // accumulator is the most significant limb itself
+        meta.create_gate("bitwise limb decomposition", |meta| {
+            let q_running = meta.query_selector(q_running);
+            let q_last = meta.query_selector(q_last);
+            let radix = Expression::Constant(Fr::from(1u64 << limb_bits));
+
+            let mut constraints = Vec::new();
+            for (acc, limb) in [(lhs, lhs_limb), (rhs, rhs_limb), (out, out_limb)] {
+                let acc_cur = meta.query_advice(acc, Rotation::cur());
+                let acc_next = meta.query_advice(acc, Rotation::next());
+                let limb = meta.query_advice(limb, Rotation::cur());
+
+                constraints.push(
+                    q_running.clone() * (acc_cur.clone() - acc_next * radix.clone() - limb.clone()),
+                );
+                constraints.push(q_last.clone() * (acc_cur - limb));
+            }
+            constraints
+        });
+
+        for (name, selector, table_out) in
+            [("bitwise and", q_and, table_and), ("bitwise xor", q_xor, table_xor)]
+        {
+            meta.lookup(name, |meta| {
+                let q = meta.query_selector(selector);
+                let lhs_limb = meta.query_advice(lhs_limb, Rotation::cur());
+                let rhs_limb = meta.query_advice(rhs_limb, Rotation::cur());
+                let out_limb = meta.query_advice(out_limb, Rotation::cur());
+
+                vec![
+                    (q.clone() * lhs_limb, table_lhs),
+                    (q.clone() * rhs_limb, table_rhs),
+                    (q * out_limb, table_out),
+                ]
+            });
+        }
+
+        BitwiseConfig {
+            limb_bits,
+            lhs,
+            rhs,
+            out,
+            lhs_limb,
+            rhs_limb,
+            out_limb,
+            q_running,
+            q_last,
+            q_and,
+            q_xor,
+            table_lhs,
+            table_rhs,
+            table_and,
+            table_xor,
+        }
+    }
+
+    /// Assign `lhs op rhs` for operands of `num_bits` bits, returning the cells
+    /// holding the full lhs, rhs and output values so they can be copy
+    /// constrained to the acir witnesses
+    pub(crate) fn assign(
+        &self,
+        ctx: &mut RegionCtx<'_, Fr>,
+        op: BitwiseOp,
+        lhs: Fr,
+        rhs: Fr,
+        num_bits: u32,
+    ) -> Result<AssignedBitwise, Error> {
+        let limb_bits = self.config.limb_bits;
+        let num_limbs = ((num_bits as usize + limb_bits - 1) / limb_bits).max(1);
+
+        let lhs_limbs = decompose(lhs, num_limbs, limb_bits);
+        let rhs_limbs = decompose(rhs, num_limbs, limb_bits);
+        let out_limbs: Vec<u64> =
+            lhs_limbs.iter().zip(rhs_limbs.iter()).map(|(l, r)| op.apply(*l, *r)).collect();
+
+        let lhs_accs = running_sums(&lhs_limbs, limb_bits);
+        let rhs_accs = running_sums(&rhs_limbs, limb_bits);
+        let out_accs = running_sums(&out_limbs, limb_bits);
+
+        let mut assigned = None;
+        for i in 0..num_limbs {
+            if i == num_limbs - 1 {
+                ctx.enable(self.config.q_last)?;
+            } else {
+                ctx.enable(self.config.q_running)?;
+            }
+            match op {
+                BitwiseOp::And => ctx.enable(self.config.q_and)?,
+                BitwiseOp::Xor => ctx.enable(self.config.q_xor)?,
+            }
+
+            let lhs_acc =
+                ctx.assign_advice(|| "lhs acc", self.config.lhs, Value::known(lhs_accs[i]))?;
+            let rhs_acc =
+                ctx.assign_advice(|| "rhs acc", self.config.rhs, Value::known(rhs_accs[i]))?;
+            let out_acc =
+                ctx.assign_advice(|| "out acc", self.config.out, Value::known(out_accs[i]))?;
+
+            ctx.assign_advice(
+                || "lhs limb",
+                self.config.lhs_limb,
+                Value::known(Fr::from(lhs_limbs[i])),
+            )?;
+            ctx.assign_advice(
+                || "rhs limb",
+                self.config.rhs_limb,
+                Value::known(Fr::from(rhs_limbs[i])),
+            )?;
+            ctx.assign_advice(
+                || "out limb",
+                self.config.out_limb,
+                Value::known(Fr::from(out_limbs[i])),
+            )?;
+
+            if i == 0 {
+                assigned = Some((lhs_acc, rhs_acc, out_acc));
+            }
+            ctx.next();
+        }
+
+        Ok(assigned.expect("at least one limb is assigned"))
+    }
+
+    /// Load the `(lhs, rhs, lhs & rhs, lhs ^ rhs)` table for every pair of limbs
+    pub(crate) fn load_table(&self, layouter: &mut impl Layouter<Fr>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "bitwise table",
+            |mut table| {
+                let limb_range = 1u64 << self.config.limb_bits;
+                let mut offset = 0;
+                for lhs in 0..limb_range {
+                    for rhs in 0..limb_range {
+                        table.assign_cell(
+                            || "lhs",
+                            self.config.table_lhs,
+                            offset,
+                            || Value::known(Fr::from(lhs)),
+                        )?;
+                        table.assign_cell(
+                            || "rhs",
+                            self.config.table_rhs,
+                            offset,
+                            || Value::known(Fr::from(rhs)),
+                        )?;
+                        table.assign_cell(
+                            || "and",
+                            self.config.table_and,
+                            offset,
+                            || Value::known(Fr::from(BitwiseOp::And.apply(lhs, rhs))),
+                        )?;
+                        table.assign_cell(
+                            || "xor",
+                            self.config.table_xor,
+                            offset,
+                            || Value::known(Fr::from(BitwiseOp::Xor.apply(lhs, rhs))),
+                        )?;
+                        offset += 1;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Little-endian limbs of `limb_bits` bits
+fn decompose(value: Fr, num_limbs: usize, limb_bits: usize) -> Vec<u64> {
+    let repr = value.to_repr();
+    let bit = |i: usize| repr.as_ref().get(i / 8).map_or(0, |byte| (*byte as u64 >> (i % 8)) & 1);
+    (0..num_limbs)
+        .map(|j| (0..limb_bits).fold(0, |limb, i| limb | bit(j * limb_bits + i) << i))
+        .collect()
+}
+
+/// Running sums of the limbs, from the full value down to the most significant limb
+fn running_sums(limbs: &[u64], limb_bits: usize) -> Vec<Fr> {
+    let radix = Fr::from(1u64 << limb_bits);
+    let mut accs = vec![Fr::zero(); limbs.len()];
+    let mut acc = Fr::zero();
+    for (i, limb) in limbs.iter().enumerate().rev() {
+        acc = acc * radix + Fr::from(*limb);
+        accs[i] = acc;
+    }
+    accs
+}
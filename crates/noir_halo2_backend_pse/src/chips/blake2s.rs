@@ -0,0 +1,570 @@
+use super::bitwise::{BitwiseConfig, BYTE_LIMB_BITS};
+use pse_halo2wrong::halo2::{
+    circuit::{AssignedCell, Region, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+/// Blake2s initialization vector
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Message word permutations of the rounds
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Right rotations applied to the xor of two words, 0 being a plain xor
+const ROTATIONS: [u32; 5] = [0, 16, 12, 8, 7];
+
+/// Number of bytes absorbed per compression
+const BLOCK_BYTES: usize = 64;
+
+/// An assigned 32-bit word along with its native value
+#[derive(Clone, Debug)]
+struct Word {
+    cell: AssignedCell<Fr, Fr>,
+    value: u32,
+}
+
+/// Cells of a xor row
+struct XorRow {
+    lhs: Word,
+    rhs: Word,
+    out: Word,
+    lhs_bytes: Vec<AssignedCell<Fr, Fr>>,
+    out_bytes: Vec<AssignedCell<Fr, Fr>>,
+}
+
+/// Assigned message bytes and digest bytes of a Blake2s call
+pub(crate) type AssignedBlake2s = (Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>);
+
+/// Configuration of the Blake2s chip.
+///
+/// The chip is built on the lookup tables of the bitwise chip. A xor row holds
+/// two words, their xor and the little-endian bytes of all three, and each
+/// triple of bytes is looked up in the xor table. The output word is composed
+/// from the bytes of the xor rotated by 0, 16, 12, 8 or 7 bits, the byte cut
+/// by a rotation being split through a lookup in the and table. Modular
+/// additions of two or three words are checked on the word values with a carry.
+#[derive(Clone, Debug)]
+pub struct Blake2sConfig {
+    lhs: Column<Advice>,
+    rhs: Column<Advice>,
+    extra: Column<Advice>,
+    out: Column<Advice>,
+    lhs_bytes: [Column<Advice>; 4],
+    rhs_bytes: [Column<Advice>; 4],
+    out_bytes: [Column<Advice>; 4],
+    // low bits of the byte cut by a rotation, or the carry of an addition
+    low: Column<Advice>,
+
+    q_xor: Selector,
+    q_rotate: [Selector; 5],
+    q_add: Selector,
+}
+
+#[derive(Clone, Debug)]
+pub struct Blake2sChip {
+    config: Blake2sConfig,
+}
+
+/// Little-endian composition of bytes
+fn compose(bytes: &[Expression<Fr>]) -> Expression<Fr> {
+    bytes.iter().rev().fold(Expression::Constant(Fr::zero()), |acc, byte| {
+        acc * Expression::Constant(Fr::from(256)) + byte.clone()
+    })
+}
+
+impl Blake2sChip {
+    pub fn new(config: Blake2sConfig) -> Self {
+        Blake2sChip { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fr>,
+        bitwise_config: &BitwiseConfig,
+    ) -> Blake2sConfig {
+        // the xors and rotation splits are looked up byte by byte
+        assert_eq!(bitwise_config.limb_bits, BYTE_LIMB_BITS);
+
+        let lhs = meta.advice_column();
+        let rhs = meta.advice_column();
+        let extra = meta.advice_column();
+        let out = meta.advice_column();
+        let lhs_bytes = [(); 4].map(|_| meta.advice_column());
+        let rhs_bytes = [(); 4].map(|_| meta.advice_column());
+        let out_bytes = [(); 4].map(|_| meta.advice_column());
+        let low = meta.advice_column();
+        let constants: Column<Fixed> = meta.fixed_column();
+
+        for column in [lhs, rhs, extra, out].iter().chain(lhs_bytes.iter()).chain(out_bytes.iter())
+        {
+            meta.enable_equality(*column);
+        }
+        meta.enable_constant(constants);
+
+        let q_xor = meta.complex_selector();
+        let q_rotate = [(); 5].map(|_| meta.complex_selector());
+        let q_add = meta.selector();
+
+        meta.create_gate("blake2s xor bytes", |meta| {
+            let q = meta.query_selector(q_xor);
+            let lhs = meta.query_advice(lhs, Rotation::cur());
+            let rhs = meta.query_advice(rhs, Rotation::cur());
+            let lhs_bytes = lhs_bytes.map(|byte| meta.query_advice(byte, Rotation::cur()));
+            let rhs_bytes = rhs_bytes.map(|byte| meta.query_advice(byte, Rotation::cur()));
+
+            vec![q.clone() * (lhs - compose(&lhs_bytes)), q * (rhs - compose(&rhs_bytes))]
+        });
+
+        // rotating right by n = 8k + s splits byte k at bit s, scaled by 2^s the
+        // rotated word is a combination of the bytes and of the low bits of byte k
+        for (q_rotate, n) in q_rotate.iter().zip(ROTATIONS) {
+            meta.create_gate("blake2s rotate", |meta| {
+                let q = meta.query_selector(*q_rotate);
+                let out = meta.query_advice(out, Rotation::cur());
+                let low = meta.query_advice(low, Rotation::cur());
+                let bytes = out_bytes.map(|byte| meta.query_advice(byte, Rotation::cur()));
+
+                let (k, s) = ((n / 8) as usize, n % 8);
+                let pow = |e: u32| Expression::Constant(Fr::from(1u64 << e));
+                let mut rotated = bytes[k].clone() - low.clone() + low * pow(32);
+                for (j, byte) in bytes.iter().enumerate() {
+                    if j > k {
+                        rotated = rotated + byte.clone() * pow(8 * (j - k) as u32);
+                    } else if j < k {
+                        rotated = rotated + byte.clone() * pow(32 + 8 * j as u32 - 8 * k as u32);
+                    }
+                }
+
+                vec![q * (out * pow(s) - rotated)]
+            });
+        }
+
+        meta.create_gate("blake2s add", |meta| {
+            let q = meta.query_selector(q_add);
+            let lhs = meta.query_advice(lhs, Rotation::cur());
+            let rhs = meta.query_advice(rhs, Rotation::cur());
+            let extra = meta.query_advice(extra, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            let carry = meta.query_advice(low, Rotation::cur());
+
+            let one = Expression::Constant(Fr::one());
+            let two = Expression::Constant(Fr::from(2));
+            vec![
+                q.clone()
+                    * (lhs + rhs + extra
+                        - out
+                        - carry.clone() * Expression::Constant(Fr::from(1u64 << 32))),
+                q * carry.clone() * (carry.clone() - one) * (carry - two),
+            ]
+        });
+
+        for i in 0..4 {
+            meta.lookup("blake2s xor", |meta| {
+                let q = meta.query_selector(q_xor);
+                let lhs_byte = meta.query_advice(lhs_bytes[i], Rotation::cur());
+                let rhs_byte = meta.query_advice(rhs_bytes[i], Rotation::cur());
+                let out_byte = meta.query_advice(out_bytes[i], Rotation::cur());
+
+                vec![
+                    (q.clone() * lhs_byte, bitwise_config.table_lhs),
+                    (q.clone() * rhs_byte, bitwise_config.table_rhs),
+                    (q * out_byte, bitwise_config.table_xor),
+                ]
+            });
+        }
+
+        // the low bits of the byte cut by the rotation are its and with a mask
+        meta.lookup("blake2s rotation split", |meta| {
+            let low = meta.query_advice(low, Rotation::cur());
+            let bytes = out_bytes.map(|byte| meta.query_advice(byte, Rotation::cur()));
+
+            let mut split = Expression::Constant(Fr::zero());
+            let mut mask = Expression::Constant(Fr::zero());
+            let mut any = Expression::Constant(Fr::zero());
+            for (q_rotate, n) in q_rotate.iter().zip(ROTATIONS) {
+                let q = meta.query_selector(*q_rotate);
+                split = split + q.clone() * bytes[(n / 8) as usize].clone();
+                mask = mask + q.clone() * Expression::Constant(Fr::from((1u64 << (n % 8)) - 1));
+                any = any + q;
+            }
+
+            vec![
+                (split, bitwise_config.table_lhs),
+                (mask, bitwise_config.table_rhs),
+                (any * low, bitwise_config.table_and),
+            ]
+        });
+
+        Blake2sConfig {
+            lhs,
+            rhs,
+            extra,
+            out,
+            lhs_bytes,
+            rhs_bytes,
+            out_bytes,
+            low,
+            q_xor,
+            q_rotate,
+            q_add,
+        }
+    }
+
+    /// Assign the Blake2s digest of `message`, returning the cells holding the
+    /// message bytes and the 32 digest bytes
+    pub(crate) fn digest(
+        &self,
+        region: &mut Region<'_, Fr>,
+        message: &[u8],
+    ) -> Result<AssignedBlake2s, Error> {
+        let mut offset = 0;
+
+        // parameter block of an unkeyed hash with a 32 byte digest
+        let mut state = Vec::with_capacity(8);
+        for (i, iv) in IV.iter().enumerate() {
+            let value = if i == 0 { iv ^ 0x01010020 } else { *iv };
+            state.push(self.constant(region, &mut offset, value)?);
+        }
+
+        let num_blocks = ((message.len() + BLOCK_BYTES - 1) / BLOCK_BYTES).max(1);
+        let mut message_cells = Vec::with_capacity(message.len());
+        for block_index in 0..num_blocks {
+            let mut block = Vec::with_capacity(16);
+            for i in 0..16 {
+                let start = block_index * BLOCK_BYTES + 4 * i;
+                let bytes: Vec<u8> =
+                    (start..start + 4).map(|j| message.get(j).copied().unwrap_or(0)).collect();
+                let value = u32::from_le_bytes(bytes.clone().try_into().unwrap());
+
+                // a xor with zero range checks the message bytes
+                let row = self.assign_xor(region, &mut offset, value, 0, 0)?;
+                region.constrain_constant(row.rhs.cell.cell(), Fr::zero())?;
+                for (j, cell) in row.lhs_bytes.into_iter().enumerate() {
+                    if start + j < message.len() {
+                        message_cells.push(cell);
+                    } else {
+                        region.constrain_constant(cell.cell(), Fr::zero())?;
+                    }
+                }
+                block.push(row.lhs);
+            }
+
+            let last = block_index == num_blocks - 1;
+            let counter =
+                if last { message.len() as u64 } else { ((block_index + 1) * BLOCK_BYTES) as u64 };
+            state = self.compress(region, &mut offset, &state, &block, counter, last)?;
+        }
+
+        // the digest is the little-endian serialization of the state, which is
+        // the output bytes of the last xor rows
+        let mut digest_cells = Vec::with_capacity(32);
+        for word in state {
+            let row = self.assign_xor(region, &mut offset, word.value, 0, 0)?;
+            region.constrain_equal(row.lhs.cell.cell(), word.cell.cell())?;
+            region.constrain_constant(row.rhs.cell.cell(), Fr::zero())?;
+            digest_cells.extend(row.out_bytes);
+        }
+
+        Ok((message_cells, digest_cells))
+    }
+
+    /// Compression of a block into the state, `counter` being the number of
+    /// message bytes hashed up to the end of the block
+    fn compress(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        state: &[Word],
+        block: &[Word],
+        counter: u64,
+        last: bool,
+    ) -> Result<Vec<Word>, Error> {
+        let mut v = state.to_vec();
+        for (i, iv) in IV.iter().enumerate() {
+            let value = match i {
+                4 => iv ^ counter as u32,
+                5 => iv ^ (counter >> 32) as u32,
+                6 if last => !iv,
+                _ => *iv,
+            };
+            v.push(self.constant(region, offset, value)?);
+        }
+
+        for sigma in SIGMA {
+            for (i, [a, b, c, d]) in [
+                [0, 4, 8, 12],
+                [1, 5, 9, 13],
+                [2, 6, 10, 14],
+                [3, 7, 11, 15],
+                [0, 5, 10, 15],
+                [1, 6, 11, 12],
+                [2, 7, 8, 13],
+                [3, 4, 9, 14],
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                let x = &block[sigma[2 * i]];
+                let y = &block[sigma[2 * i + 1]];
+
+                v[a] = self.add(region, offset, &v[a], &v[b], Some(x))?;
+                v[d] = self.xor_rotate(region, offset, &v[d], &v[a], 16)?;
+                v[c] = self.add(region, offset, &v[c], &v[d], None)?;
+                v[b] = self.xor_rotate(region, offset, &v[b], &v[c], 12)?;
+                v[a] = self.add(region, offset, &v[a], &v[b], Some(y))?;
+                v[d] = self.xor_rotate(region, offset, &v[d], &v[a], 8)?;
+                v[c] = self.add(region, offset, &v[c], &v[d], None)?;
+                v[b] = self.xor_rotate(region, offset, &v[b], &v[c], 7)?;
+            }
+        }
+
+        let mut next = Vec::with_capacity(8);
+        for (i, h) in state.iter().enumerate() {
+            let partial = self.xor_rotate(region, offset, h, &v[i], 0)?;
+            next.push(self.xor_rotate(region, offset, &partial, &v[i + 8], 0)?);
+        }
+
+        Ok(next)
+    }
+
+    /// Modular addition of two or three words
+    fn add(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        a: &Word,
+        b: &Word,
+        c: Option<&Word>,
+    ) -> Result<Word, Error> {
+        self.config.q_add.enable(region, *offset)?;
+
+        let c_value = c.map(|c| c.value).unwrap_or(0);
+        let sum = a.value as u64 + b.value as u64 + c_value as u64;
+        let lhs = self.assign_word(region, *offset, self.config.lhs, a.value)?;
+        let rhs = self.assign_word(region, *offset, self.config.rhs, b.value)?;
+        let extra = self.assign_word(region, *offset, self.config.extra, c_value)?;
+        region.constrain_equal(lhs.cell.cell(), a.cell.cell())?;
+        region.constrain_equal(rhs.cell.cell(), b.cell.cell())?;
+        match c {
+            Some(c) => region.constrain_equal(extra.cell.cell(), c.cell.cell())?,
+            None => region.constrain_constant(extra.cell.cell(), Fr::zero())?,
+        }
+        region.assign_advice(
+            || "carry",
+            self.config.low,
+            *offset,
+            || Value::known(Fr::from(sum >> 32)),
+        )?;
+        let out = self.assign_word(region, *offset, self.config.out, sum as u32)?;
+        *offset += 1;
+
+        Ok(out)
+    }
+
+    /// Xor two words and rotate the result right by `rotation` bits
+    fn xor_rotate(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        a: &Word,
+        b: &Word,
+        rotation: u32,
+    ) -> Result<Word, Error> {
+        let row = self.assign_xor(region, offset, a.value, b.value, rotation)?;
+        region.constrain_equal(row.lhs.cell.cell(), a.cell.cell())?;
+        region.constrain_equal(row.rhs.cell.cell(), b.cell.cell())?;
+        Ok(row.out)
+    }
+
+    /// Assign a xor row of `lhs` and `rhs` rotated right by `rotation` bits
+    fn assign_xor(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        lhs: u32,
+        rhs: u32,
+        rotation: u32,
+    ) -> Result<XorRow, Error> {
+        let index = ROTATIONS.iter().position(|n| *n == rotation).ok_or(Error::Synthesis)?;
+        self.config.q_xor.enable(region, *offset)?;
+        self.config.q_rotate[index].enable(region, *offset)?;
+
+        let xor = lhs ^ rhs;
+        let mut cells = Vec::with_capacity(3);
+        for (value, columns) in [
+            (lhs, self.config.lhs_bytes),
+            (rhs, self.config.rhs_bytes),
+            (xor, self.config.out_bytes),
+        ] {
+            let bytes = value
+                .to_le_bytes()
+                .iter()
+                .zip(columns.iter())
+                .map(|(byte, column)| {
+                    region.assign_advice(
+                        || "byte",
+                        *column,
+                        *offset,
+                        || Value::known(Fr::from(*byte as u64)),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            cells.push(bytes);
+        }
+        let split_byte = xor.to_le_bytes()[(rotation / 8) as usize];
+        region.assign_advice(
+            || "low bits",
+            self.config.low,
+            *offset,
+            || Value::known(Fr::from((split_byte & ((1u8 << (rotation % 8)) - 1)) as u64)),
+        )?;
+
+        let lhs = self.assign_word(region, *offset, self.config.lhs, lhs)?;
+        let rhs = self.assign_word(region, *offset, self.config.rhs, rhs)?;
+        let out = self.assign_word(region, *offset, self.config.out, xor.rotate_right(rotation))?;
+        *offset += 1;
+
+        let out_bytes = cells.pop().unwrap();
+        cells.pop();
+        let lhs_bytes = cells.pop().unwrap();
+        Ok(XorRow { lhs, rhs, out, lhs_bytes, out_bytes })
+    }
+
+    /// Assign a word constrained to a constant on its own row
+    fn constant(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        value: u32,
+    ) -> Result<Word, Error> {
+        let word = self.assign_word(region, *offset, self.config.out, value)?;
+        region.constrain_constant(word.cell.cell(), Fr::from(value as u64))?;
+        *offset += 1;
+        Ok(word)
+    }
+
+    fn assign_word(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: usize,
+        column: Column<Advice>,
+        value: u32,
+    ) -> Result<Word, Error> {
+        let cell = region.assign_advice(
+            || "word",
+            column,
+            offset,
+            || Value::known(Fr::from(value as u64)),
+        )?;
+        Ok(Word { cell, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Blake2sChip, Blake2sConfig};
+    use crate::chips::bitwise::{BitwiseChip, BitwiseConfig, BYTE_LIMB_BITS};
+    use pse_halo2wrong::halo2::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+
+    #[derive(Clone, Default)]
+    struct Blake2sTestCircuit {
+        message: Vec<u8>,
+        digest: Vec<u8>,
+    }
+
+    impl Circuit<Fr> for Blake2sTestCircuit {
+        type Config = (BitwiseConfig, Blake2sConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let bitwise_config = BitwiseChip::configure(meta, BYTE_LIMB_BITS);
+            let blake2s_config = Blake2sChip::configure(meta, &bitwise_config);
+            (bitwise_config, blake2s_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let bitwise_chip = BitwiseChip::new(config.0);
+            let blake2s_chip = Blake2sChip::new(config.1);
+            layouter.assign_region(
+                || "blake2s",
+                |mut region| {
+                    let (_, digest_cells) = blake2s_chip.digest(&mut region, &self.message)?;
+                    // constrain the digest to the expected value
+                    for (cell, byte) in digest_cells.iter().zip(self.digest.iter()) {
+                        region.constrain_constant(cell.cell(), Fr::from(*byte as u64))?;
+                    }
+                    Ok(())
+                },
+            )?;
+            bitwise_chip.load_table(&mut layouter)
+        }
+    }
+
+    fn run(message: &[u8], digest: Vec<u8>) -> Result<(), Vec<String>> {
+        let circuit = Blake2sTestCircuit { message: message.to_vec(), digest };
+        let prover = MockProver::run(17, &circuit, vec![]).unwrap();
+        prover.verify().map_err(|failures| failures.iter().map(|f| f.to_string()).collect())
+    }
+
+    fn run_against_solver(message: &[u8]) -> Result<(), Vec<String>> {
+        run(message, acvm_blackbox_solver::blake2s(message).unwrap().to_vec())
+    }
+
+    #[test]
+    fn test_blake2s_empty_message() {
+        assert_eq!(run_against_solver(b""), Ok(()));
+    }
+
+    #[test]
+    fn test_blake2s_abc() {
+        assert_eq!(
+            run(
+                b"abc",
+                hex::decode("508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982")
+                    .unwrap()
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_blake2s_two_blocks() {
+        let message: Vec<u8> = (0..100).collect();
+        assert_eq!(run_against_solver(&message), Ok(()));
+    }
+
+    #[test]
+    fn test_blake2s_wrong_digest() {
+        let mut digest = acvm_blackbox_solver::blake2s(b"abc").unwrap().to_vec();
+        digest[31] ^= 1;
+        assert!(run(b"abc", digest).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+use pse_ecc::{
+    integer::{
+        rns::Integer, AssignedInteger, IntegerChip, IntegerInstructions, Range, UnassignedInteger,
+    },
+    EccConfig, GeneralEccChip,
+};
+use pse_halo2wrong::{
+    curves::{
+        group::{ff::PrimeField, Curve, Group},
+        CurveAffine,
+    },
+    halo2::{circuit::Value, halo2curves::bn256::Fr, plonk::Error},
+    RegionCtx,
+};
+use pse_maingate::{
+    AssignedCondition, AssignedValue, MainGate, MainGateConfig, MainGateInstructions, RangeChip,
+    RangeConfig, RangeInstructions, Term,
+};
+use rand::rngs::OsRng;
+
+/// Number of limbs of the non-native field elements
+pub(crate) const NUMBER_OF_LIMBS: usize = 4;
+/// Bit length of the limbs of the non-native field elements
+pub(crate) const BIT_LEN_LIMB: usize = 68;
+/// Window size of the scalar multiplications
+const WINDOW_SIZE: usize = 2;
+
+/// Assigned bytes of the public key, signature and hashed message of an ECDSA
+/// verification along with its result
+pub(crate) struct AssignedEcdsa {
+    pub(crate) public_key_x: Vec<AssignedValue<Fr>>,
+    pub(crate) public_key_y: Vec<AssignedValue<Fr>>,
+    pub(crate) signature: Vec<AssignedValue<Fr>>,
+    pub(crate) hashed_message: Vec<AssignedValue<Fr>>,
+    pub(crate) result: AssignedCondition<Fr>,
+}
+
+/// Configuration of the ECDSA chip, which shares the main gate and the range
+/// tables of the circuit with halo2wrong's non-native ECC chip
+#[derive(Clone, Debug)]
+pub struct EcdsaConfig {
+    main_gate_config: MainGateConfig,
+    range_config: RangeConfig,
+}
+
+impl EcdsaConfig {
+    pub fn new(main_gate_config: MainGateConfig, range_config: RangeConfig) -> Self {
+        EcdsaConfig { main_gate_config, range_config }
+    }
+
+    fn ecc_config(&self) -> EccConfig {
+        EccConfig::new(self.range_config.clone(), self.main_gate_config.clone())
+    }
+}
+
+/// ECDSA verification over the curve `C` from big-endian byte arrays
+#[derive(Clone, Debug)]
+pub struct EcdsaChip<C: CurveAffine> {
+    config: EcdsaConfig,
+    aux_generator: C,
+}
+
+impl<C: CurveAffine> EcdsaChip<C> {
+    pub fn new(config: EcdsaConfig) -> Self {
+        // the auxiliary generator offsets the accumulators of the scalar
+        // multiplications away from exceptional cases of incomplete additions
+        EcdsaChip { config, aux_generator: C::CurveExt::random(OsRng).to_affine() }
+    }
+
+    /// Overflow bit lengths the range chip needs to constrain the limbs of the
+    /// base and scalar fields of `C`
+    pub fn overflow_bit_lens() -> Vec<usize> {
+        let (rns_base, rns_scalar) = GeneralEccChip::<C, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::rns();
+        let mut overflow_bit_lens = rns_base.overflow_lengths();
+        overflow_bit_lens.extend(rns_scalar.overflow_lengths());
+        overflow_bit_lens
+    }
+
+    /// Verify the signature `r || s` of `hashed_message` under the public key
+    /// `(public_key_x, public_key_y)`, returning the assigned bytes and the
+    /// boolean result of the verification
+    pub(crate) fn verify(
+        &self,
+        ctx: &mut RegionCtx<'_, Fr>,
+        public_key_x: &[u8],
+        public_key_y: &[u8],
+        signature: &[u8],
+        hashed_message: &[u8],
+    ) -> Result<AssignedEcdsa, Error> {
+        if public_key_x.len() != 32
+            || public_key_y.len() != 32
+            || signature.len() != 64
+            || hashed_message.len() > 32
+        {
+            return Err(Error::Synthesis);
+        }
+
+        let main_gate = MainGate::<Fr>::new(self.config.main_gate_config.clone());
+        let range_chip = RangeChip::<Fr>::new(self.config.range_config.clone());
+        let mut ecc_chip =
+            GeneralEccChip::<C, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::new(self.config.ecc_config());
+        ecc_chip.assign_aux_generator(ctx, Value::known(self.aux_generator))?;
+        ecc_chip.assign_aux(ctx, WINDOW_SIZE, 1)?;
+        let scalar_chip = ecc_chip.scalar_field_chip();
+
+        // public key
+        let (public_key_x_cells, public_key_x_limbs) =
+            assign_bytes(ctx, &main_gate, &range_chip, public_key_x)?;
+        let (public_key_y_cells, public_key_y_limbs) =
+            assign_bytes(ctx, &main_gate, &range_chip, public_key_y)?;
+        let x = field_from_bytes::<C::Base>(public_key_x)?;
+        let y = field_from_bytes::<C::Base>(public_key_y)?;
+        let public_key: C = Option::from(C::from_xy(x, y)).ok_or(Error::Synthesis)?;
+        let public_key = ecc_chip.assign_point(ctx, Value::known(public_key))?;
+        assert_limbs(ctx, &main_gate, public_key.x(), &public_key_x_limbs)?;
+        assert_limbs(ctx, &main_gate, public_key.y(), &public_key_y_limbs)?;
+
+        // signature and hashed message, the hash is left padded to the scalar size
+        let (r_cells, r_limbs) = assign_bytes(ctx, &main_gate, &range_chip, &signature[..32])?;
+        let (s_cells, s_limbs) = assign_bytes(ctx, &main_gate, &range_chip, &signature[32..])?;
+        let (hashed_message_cells, hashed_message_limbs) =
+            assign_bytes(ctx, &main_gate, &range_chip, hashed_message)?;
+        let r = assign_integer(ctx, &main_gate, scalar_chip, &signature[..32], &r_limbs)?;
+        let s = assign_integer(ctx, &main_gate, scalar_chip, &signature[32..], &s_limbs)?;
+        let m =
+            assign_integer(ctx, &main_gate, scalar_chip, hashed_message, &hashed_message_limbs)?;
+
+        // 0 < r, s < n
+        scalar_chip.assert_not_zero(ctx, &r)?;
+        scalar_chip.assert_not_zero(ctx, &s)?;
+        scalar_chip.assert_in_field(ctx, &r)?;
+
+        // u1 = m * s^-1 and u2 = r * s^-1
+        let (s_inv, _) = scalar_chip.invert(ctx, &s)?;
+        let u1 = scalar_chip.mul(ctx, &m, &s_inv)?;
+        let u2 = scalar_chip.mul(ctx, &r, &s_inv)?;
+
+        // q = u1 * G + u2 * public key
+        let generator = ecc_chip.assign_constant(ctx, C::generator())?;
+        let g1 = ecc_chip.mul(ctx, &generator, &u1, WINDOW_SIZE)?;
+        let g2 = ecc_chip.mul(ctx, &public_key, &u2, WINDOW_SIZE)?;
+        let q = ecc_chip.add(ctx, &g1, &g2)?;
+
+        // the signature is valid if q.x = r mod n, both sides are in field so that
+        // their limbs are unique
+        let q_x = ecc_chip.base_field_chip().reduce(ctx, q.x())?;
+        let q_x = scalar_chip.reduce_external(ctx, &q_x)?;
+        scalar_chip.assert_in_field(ctx, &q_x)?;
+        let mut result = main_gate.is_equal(ctx, &q_x.limb(0), &r.limb(0))?;
+        for i in 1..NUMBER_OF_LIMBS {
+            let limb_equal = main_gate.is_equal(ctx, &q_x.limb(i), &r.limb(i))?;
+            result = main_gate.and(ctx, &result, &limb_equal)?;
+        }
+
+        let mut signature_cells = r_cells;
+        signature_cells.extend(s_cells);
+        Ok(AssignedEcdsa {
+            public_key_x: public_key_x_cells,
+            public_key_y: public_key_y_cells,
+            signature: signature_cells,
+            hashed_message: hashed_message_cells,
+            result,
+        })
+    }
+}
+
+/// Little-endian bits `[start, start + len)` of a big-endian byte array
+fn bits_of(bytes: &[u8], start: usize, len: usize) -> u128 {
+    (start..start + len).rev().fold(0u128, |acc, bit| {
+        let byte = if bit / 8 < bytes.len() { bytes[bytes.len() - 1 - bit / 8] } else { 0 };
+        (acc << 1) | ((byte >> (bit % 8)) & 1) as u128
+    })
+}
+
+/// Limbs of the integer encoded by a big-endian byte array
+fn limb_values(bytes: &[u8]) -> [Fr; NUMBER_OF_LIMBS] {
+    std::array::from_fn(|i| Fr::from_u128(bits_of(bytes, i * BIT_LEN_LIMB, BIT_LEN_LIMB)))
+}
+
+/// Field element of a big-endian byte array
+fn field_from_bytes<F: PrimeField>(bytes: &[u8]) -> Result<F, Error> {
+    let mut repr = F::Repr::default();
+    for (byte, value) in repr.as_mut().iter_mut().zip(bytes.iter().rev()) {
+        *byte = *value;
+    }
+    Option::from(F::from_repr(repr)).ok_or(Error::Synthesis)
+}
+
+/// Assign big-endian bytes and compose them into the little-endian limbs of the
+/// integer they encode. Limbs are 68 bits long so bytes straddling two limbs are
+/// split into range checked nibbles.
+fn assign_bytes(
+    ctx: &mut RegionCtx<'_, Fr>,
+    main_gate: &MainGate<Fr>,
+    range_chip: &RangeChip<Fr>,
+    bytes: &[u8],
+) -> Result<(Vec<AssignedValue<Fr>>, Vec<AssignedValue<Fr>>), Error> {
+    let byte_cells = bytes
+        .iter()
+        .map(|byte| main_gate.assign_value(ctx, Value::known(Fr::from(*byte as u64))))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    // (cell, limb, shift) of every chunk of bits
+    let mut chunks = Vec::with_capacity(byte_cells.len() + 2);
+    for (k, (byte, cell)) in bytes.iter().rev().zip(byte_cells.iter().rev()).enumerate() {
+        let (limb, shift) = (8 * k / BIT_LEN_LIMB, 8 * k % BIT_LEN_LIMB);
+        let split = BIT_LEN_LIMB - shift;
+        if split >= 8 {
+            chunks.push((cell.clone(), limb, shift));
+        } else {
+            let low = range_chip.assign(
+                ctx,
+                Value::known(Fr::from((byte & ((1 << split) - 1)) as u64)),
+                8,
+                split,
+            )?;
+            let high = range_chip.assign(
+                ctx,
+                Value::known(Fr::from((byte >> split) as u64)),
+                8,
+                8 - split,
+            )?;
+            let composed = main_gate.compose(
+                ctx,
+                &[Term::Assigned(&low, Fr::one()), Term::Assigned(&high, Fr::from(1u64 << split))],
+                Fr::zero(),
+            )?;
+            main_gate.assert_equal(ctx, &composed, cell)?;
+            chunks.push((low, limb, shift));
+            chunks.push((high, limb + 1, 0));
+        }
+    }
+
+    let limbs = (0..NUMBER_OF_LIMBS)
+        .map(|i| {
+            let terms: Vec<Term<Fr>> = chunks
+                .iter()
+                .filter(|(_, limb, _)| *limb == i)
+                .map(|(cell, _, shift)| Term::Assigned(cell, Fr::from_u128(1 << shift)))
+                .collect();
+            main_gate.compose(ctx, &terms, Fr::zero())
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok((byte_cells, limbs))
+}
+
+/// Assign the integer encoded by big-endian bytes, constrained to the limbs
+/// composed from the assigned bytes
+fn assign_integer<W: PrimeField>(
+    ctx: &mut RegionCtx<'_, Fr>,
+    main_gate: &MainGate<Fr>,
+    integer_chip: &IntegerChip<W, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    bytes: &[u8],
+    limbs: &[AssignedValue<Fr>],
+) -> Result<AssignedInteger<W, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>, Error> {
+    let integer = Integer::from_limbs(&limb_values(bytes), integer_chip.rns());
+    let assigned = integer_chip.assign_integer(
+        ctx,
+        UnassignedInteger::from(Value::known(integer)),
+        Range::Remainder,
+    )?;
+    assert_limbs(ctx, main_gate, &assigned, limbs)?;
+    Ok(assigned)
+}
+
+/// Constrain the limbs of an assigned integer to the limbs composed from its bytes
+fn assert_limbs<W: PrimeField>(
+    ctx: &mut RegionCtx<'_, Fr>,
+    main_gate: &MainGate<Fr>,
+    integer: &AssignedInteger<W, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    limbs: &[AssignedValue<Fr>],
+) -> Result<(), Error> {
+    for (i, limb) in limbs.iter().enumerate() {
+        main_gate.assert_equal(ctx, &integer.limb(i), limb)?;
+    }
+    Ok(())
+}
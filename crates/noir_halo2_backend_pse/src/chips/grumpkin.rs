@@ -0,0 +1,816 @@
+use acvm_blackbox_solver::blake2s;
+use pse_halo2wrong::{
+    curves::group::ff::{Field, PrimeField},
+    halo2::{
+        circuit::{AssignedCell, Region, Value},
+        halo2curves::bn256::Fr,
+        plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+        poly::Rotation,
+    },
+};
+use sha3::{Digest, Keccak256};
+
+/// Number of 2-bit windows of a fixed base scalar multiplication, covering the
+/// 254 bits of the scalar
+const NUM_WINDOWS: usize = 127;
+/// Number of Pedersen generators reserved for the default domain separator
+const NUM_DEFAULT_GENERATORS: usize = 2048;
+/// Number of Pedersen generators reserved for every other domain separator
+const NUM_GENERATORS_PER_HASH_INDEX: usize = 8;
+
+/// Affine point of Grumpkin, the curve `y^2 = x^3 - 17` over the BN254 scalar
+/// field. As `-17` is not a square there is no point with `x = 0`, so the point
+/// at infinity is encoded as `(0, 0)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct GrumpkinPoint {
+    pub(crate) x: Fr,
+    pub(crate) y: Fr,
+}
+
+impl GrumpkinPoint {
+    pub(crate) fn identity() -> Self {
+        GrumpkinPoint { x: Fr::zero(), y: Fr::zero() }
+    }
+
+    /// Generator `(1, sqrt(-16))` of Noir's embedded curve
+    pub(crate) fn generator() -> Self {
+        GrumpkinPoint {
+            x: Fr::one(),
+            y: Fr::from_raw([
+                0x833fc48d823f272c,
+                0x2d270d45f1181294,
+                0xcf135e7506a45d63,
+                0x0000000000000002,
+            ]),
+        }
+    }
+
+    pub(crate) fn is_identity(&self) -> bool {
+        self.x == Fr::zero() && self.y == Fr::zero()
+    }
+
+    pub(crate) fn add(&self, other: &Self) -> Self {
+        if self.is_identity() {
+            return *other;
+        }
+        if other.is_identity() {
+            return *self;
+        }
+        if self.x == other.x {
+            return if self.y == other.y { self.double() } else { Self::identity() };
+        }
+        let lambda = (other.y - self.y) * (other.x - self.x).invert().unwrap();
+        self.with_slope(other, lambda)
+    }
+
+    pub(crate) fn double(&self) -> Self {
+        // the group has prime order, so no point other than infinity has y = 0
+        if self.is_identity() {
+            return *self;
+        }
+        let lambda = Fr::from(3) * self.x.square() * (Fr::from(2) * self.y).invert().unwrap();
+        self.with_slope(self, lambda)
+    }
+
+    pub(crate) fn mul(&self, scalar: &Fr) -> Self {
+        let mut bytes = scalar.to_repr();
+        bytes.as_mut().reverse();
+        self.mul_bytes(bytes.as_ref())
+    }
+
+    /// Multiple of the point by the integer of big-endian `bytes`, which may
+    /// exceed the modulus of either field
+    pub(crate) fn mul_bytes(&self, bytes: &[u8]) -> Self {
+        bytes.iter().flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1)).fold(
+            Self::identity(),
+            |acc, bit| {
+                let acc = acc.double();
+                if bit {
+                    acc.add(self)
+                } else {
+                    acc
+                }
+            },
+        )
+    }
+
+    /// Sum of `self` and `other` on the line of slope `lambda`
+    fn with_slope(&self, other: &Self, lambda: Fr) -> Self {
+        let x = lambda.square() - self.x - other.x;
+        let y = lambda * (self.x - x) - self.y;
+        GrumpkinPoint { x, y }
+    }
+}
+
+/// Hash a seed to a point of Grumpkin the way barretenberg derives its
+/// generators: the keccak256 digest of the 32 byte big-endian seed is read as a
+/// little-endian integer, reduced into the x coordinate, and its top bit gives
+/// the parity of the y coordinate
+fn hash_to_curve(seed: u64) -> Option<GrumpkinPoint> {
+    let mut input = [0u8; 32];
+    input[24..].copy_from_slice(&seed.to_be_bytes());
+    let digest: [u8; 32] = Keccak256::digest(input).into();
+
+    let limb = |i: usize| u64::from_le_bytes(digest[8 * i..8 * (i + 1)].try_into().unwrap());
+    let x = Fr::from_raw([limb(0), limb(1), 0, 0])
+        + Fr::from_raw([limb(2), limb(3), 0, 0]) * Fr::from_raw([0, 0, 1, 0]);
+    let y: Option<Fr> = (x.square() * x - Fr::from(17)).sqrt().into();
+    let y = y?;
+    let odd = digest[31] >> 7 == 1;
+    let y = if (y.to_repr().as_ref()[0] & 1 == 1) == odd { y } else { -y };
+
+    Some(GrumpkinPoint { x, y })
+}
+
+/// Generators of the Pedersen hash under `domain_separator`.
+///
+/// Barretenberg hashes consecutive seeds to the curve and keeps every third point
+/// as a generator, the two others being auxiliary points of its own scalar
+/// multiplications. The default domain separator owns the first generators and
+/// every other one a small range after them.
+pub(crate) fn pedersen_generators(domain_separator: u32, count: usize) -> Vec<GrumpkinPoint> {
+    let start = match domain_separator {
+        0 => 0,
+        index => NUM_DEFAULT_GENERATORS + (index as usize - 1) * NUM_GENERATORS_PER_HASH_INDEX,
+    };
+    (1..).filter_map(hash_to_curve).step_by(3).skip(start).take(count).collect()
+}
+
+/// Pedersen commitment to `inputs` under `domain_separator`
+pub(crate) fn pedersen_commitment(inputs: &[Fr], domain_separator: u32) -> GrumpkinPoint {
+    inputs
+        .iter()
+        .zip(pedersen_generators(domain_separator, inputs.len()))
+        .fold(GrumpkinPoint::identity(), |acc, (input, generator)| acc.add(&generator.mul(input)))
+}
+
+/// Nonce `s * G + e * public_key` of a Schnorr signature `s || e`, both halves
+/// being read as big-endian integers
+pub(crate) fn schnorr_nonce(public_key: &GrumpkinPoint, signature: &[u8]) -> GrumpkinPoint {
+    GrumpkinPoint::generator()
+        .mul_bytes(&signature[..32])
+        .add(&public_key.mul_bytes(&signature[32..]))
+}
+
+/// Message whose Blake2s digest is the challenge of a Schnorr signature: the
+/// big-endian x coordinate of the Pedersen commitment to the x coordinate of
+/// the nonce and to the public key, followed by the signed message
+pub(crate) fn schnorr_challenge_message(
+    public_key: &GrumpkinPoint,
+    nonce: &GrumpkinPoint,
+    message: &[u8],
+) -> Vec<u8> {
+    let commitment = pedersen_commitment(&[nonce.x, public_key.x, public_key.y], 0);
+    let mut challenge_message = commitment.x.to_repr().as_ref().to_vec();
+    challenge_message.reverse();
+    challenge_message.extend_from_slice(message);
+    challenge_message
+}
+
+/// Verify a Schnorr signature `s || e` of `message` the way barretenberg does,
+/// the signature being valid if its nonce is not infinity and `e` is the
+/// Blake2s digest of its challenge message
+pub(crate) fn schnorr_verify(public_key: &GrumpkinPoint, signature: &[u8], message: &[u8]) -> bool {
+    if signature.len() != 64 {
+        return false;
+    }
+    let nonce = schnorr_nonce(public_key, signature);
+    !nonce.is_identity()
+        && blake2s(&schnorr_challenge_message(public_key, &nonce, message))
+            .map_or(false, |digest| digest[..] == signature[32..])
+}
+
+/// 2-bit windows of a field element, most significant first
+fn windows(value: &Fr) -> Vec<usize> {
+    let repr = value.to_repr();
+    (0..NUM_WINDOWS).rev().map(|w| ((repr.as_ref()[w / 4] >> (2 * (w % 4))) & 3) as usize).collect()
+}
+
+fn invert_or_zero(value: Fr) -> Fr {
+    value.invert().unwrap_or(Fr::zero())
+}
+
+/// Assigned coordinates of a Grumpkin point along with its value
+#[derive(Clone, Debug)]
+pub(crate) struct AssignedPoint {
+    pub(crate) x: AssignedCell<Fr, Fr>,
+    pub(crate) y: AssignedCell<Fr, Fr>,
+    value: GrumpkinPoint,
+}
+
+/// Configuration of the Grumpkin chip.
+///
+/// Grumpkin's base field is the native field of the circuit, so points are
+/// handled without any non-native arithmetic. Two points `(x, y)` and
+/// `(x_q, y_q)` are added on a row with their sum on the next row, following the
+/// complete addition of the Orchard ECC chip which also doubles and handles the
+/// point at infinity.
+///
+/// A fixed base scalar multiplication takes a row per 2-bit window of the scalar,
+/// most significant first. The point added on the row is selected by the window
+/// bits from a fixed table of multiples of the base, and the windows are both
+/// accumulated into the scalar and compared against those of the largest field
+/// element so that the decomposition is canonical.
+///
+/// A variable base multiplication doubles its accumulator on a row per bit of
+/// the scalar, then adds on the next row either infinity or the base copied
+/// next to the bit.
+#[derive(Clone, Debug)]
+pub struct GrumpkinConfig {
+    x: Column<Advice>,
+    y: Column<Advice>,
+    x_q: Column<Advice>,
+    y_q: Column<Advice>,
+    lambda: Column<Advice>,
+    alpha: Column<Advice>,
+    beta: Column<Advice>,
+    gamma: Column<Advice>,
+    delta: Column<Advice>,
+    bits: [Column<Advice>; 2],
+    scalar: Column<Advice>,
+    // whether the windows so far are those of the largest field element
+    tight: Column<Advice>,
+    table_x: [Column<Fixed>; 4],
+    table_y: [Column<Fixed>; 4],
+    max_window: [Column<Fixed>; 4],
+    // base point of a variable base multiplication
+    x_b: Column<Advice>,
+    y_b: Column<Advice>,
+
+    q_add: Selector,
+    q_window: Selector,
+    q_select: Selector,
+    q_on_curve: Selector,
+}
+
+#[derive(Clone, Debug)]
+pub struct GrumpkinChip {
+    config: GrumpkinConfig,
+}
+
+impl GrumpkinChip {
+    pub fn new(config: GrumpkinConfig) -> Self {
+        GrumpkinChip { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> GrumpkinConfig {
+        let [x, y, x_q, y_q, lambda, alpha, beta, gamma, delta] =
+            [(); 9].map(|_| meta.advice_column());
+        let bits = [(); 2].map(|_| meta.advice_column());
+        let scalar = meta.advice_column();
+        let tight = meta.advice_column();
+        let table_x = [(); 4].map(|_| meta.fixed_column());
+        let table_y = [(); 4].map(|_| meta.fixed_column());
+        let max_window = [(); 4].map(|_| meta.fixed_column());
+        let [x_b, y_b] = [(); 2].map(|_| meta.advice_column());
+        let constants = meta.fixed_column();
+
+        for column in [x, y, x_q, y_q, scalar, tight, bits[0], x_b, y_b] {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constants);
+
+        let q_add = meta.selector();
+        let q_window = meta.selector();
+        let q_select = meta.selector();
+        let q_on_curve = meta.selector();
+
+        let one = Expression::Constant(Fr::one());
+
+        meta.create_gate("grumpkin complete addition", |meta| {
+            let q = meta.query_selector(q_add);
+            let x_p = meta.query_advice(x, Rotation::cur());
+            let y_p = meta.query_advice(y, Rotation::cur());
+            let x_q = meta.query_advice(x_q, Rotation::cur());
+            let y_q = meta.query_advice(y_q, Rotation::cur());
+            let x_r = meta.query_advice(x, Rotation::next());
+            let y_r = meta.query_advice(y, Rotation::next());
+            let lambda = meta.query_advice(lambda, Rotation::cur());
+            let alpha = meta.query_advice(alpha, Rotation::cur());
+            let beta = meta.query_advice(beta, Rotation::cur());
+            let gamma = meta.query_advice(gamma, Rotation::cur());
+            let delta = meta.query_advice(delta, Rotation::cur());
+
+            let dx = x_q.clone() - x_p.clone();
+            let dy = y_q.clone() - y_p.clone();
+            let sy = y_q.clone() + y_p.clone();
+            let x_sum = lambda.clone() * lambda.clone() - x_p.clone() - x_q.clone() - x_r.clone();
+            let y_sum = lambda.clone() * (x_p.clone() - x_r.clone()) - y_p.clone() - y_r.clone();
+            let not_dx = one.clone() - dx.clone() * alpha;
+            let not_x_p = one.clone() - x_p.clone() * beta;
+            let not_x_q = one.clone() - x_q.clone() * gamma;
+            let not_sum = not_dx.clone() - sy.clone() * delta;
+            let both = x_p.clone() * x_q.clone();
+
+            vec![
+                // slope of distinct points
+                q.clone() * dx.clone() * (dx.clone() * lambda.clone() - dy),
+                // slope of the tangent when the x coordinates are equal
+                q.clone()
+                    * not_dx
+                    * (Expression::Constant(Fr::from(2)) * y_p.clone() * lambda
+                        - Expression::Constant(Fr::from(3)) * x_p.clone() * x_p.clone()),
+                // sum of points that are neither infinity nor opposite
+                q.clone() * both.clone() * dx.clone() * x_sum.clone(),
+                q.clone() * both.clone() * dx * y_sum.clone(),
+                q.clone() * both.clone() * sy.clone() * x_sum,
+                q.clone() * both * sy * y_sum,
+                // infinity plus q
+                q.clone() * not_x_p.clone() * (x_r.clone() - x_q.clone()),
+                q.clone() * not_x_p * (y_r.clone() - y_q),
+                // p plus infinity
+                q.clone() * not_x_q.clone() * (x_r.clone() - x_p),
+                q.clone() * not_x_q * (y_r.clone() - y_p),
+                // p plus -p
+                q.clone() * not_sum.clone() * x_r,
+                q * not_sum * y_r,
+            ]
+        });
+
+        meta.create_gate("grumpkin fixed base window", |meta| {
+            let q = meta.query_selector(q_window);
+            let [b0, b1] = bits.map(|bit| meta.query_advice(bit, Rotation::cur()));
+            let x_q = meta.query_advice(x_q, Rotation::cur());
+            let y_q = meta.query_advice(y_q, Rotation::cur());
+            let scalar_cur = meta.query_advice(scalar, Rotation::cur());
+            let scalar_next = meta.query_advice(scalar, Rotation::next());
+            let tight_cur = meta.query_advice(tight, Rotation::cur());
+            let tight_next = meta.query_advice(tight, Rotation::next());
+
+            // lagrange basis of the window over its bits
+            let basis = [
+                (one.clone() - b0.clone()) * (one.clone() - b1.clone()),
+                b0.clone() * (one.clone() - b1.clone()),
+                (one.clone() - b0.clone()) * b1.clone(),
+                b0.clone() * b1.clone(),
+            ];
+            let mut selected_x = Expression::Constant(Fr::zero());
+            let mut selected_y = Expression::Constant(Fr::zero());
+            let mut equal = Expression::Constant(Fr::zero());
+            let mut greater = Expression::Constant(Fr::zero());
+            let mut below = Expression::Constant(Fr::zero());
+            for (v, basis) in basis.into_iter().enumerate() {
+                let max = meta.query_fixed(max_window[v], Rotation::cur());
+                selected_x =
+                    selected_x + basis.clone() * meta.query_fixed(table_x[v], Rotation::cur());
+                selected_y =
+                    selected_y + basis.clone() * meta.query_fixed(table_y[v], Rotation::cur());
+                equal = equal + basis.clone() * max.clone();
+                // the window exceeds the maximal window when the latter is below it
+                greater = greater + basis * below.clone();
+                below = below + max;
+            }
+            let window = b0.clone() + Expression::Constant(Fr::from(2)) * b1.clone();
+
+            vec![
+                q.clone() * b0.clone() * (one.clone() - b0),
+                q.clone() * b1.clone() * (one.clone() - b1),
+                q.clone() * (x_q - selected_x),
+                q.clone() * (y_q - selected_y),
+                q.clone() * (scalar_next - Expression::Constant(Fr::from(4)) * scalar_cur - window),
+                q.clone() * (tight_next - tight_cur.clone() * equal),
+                q * tight_cur * greater,
+            ]
+        });
+
+        meta.create_gate("grumpkin variable base bit", |meta| {
+            let q = meta.query_selector(q_select);
+            let bit = meta.query_advice(bits[0], Rotation::cur());
+            let x_q = meta.query_advice(x_q, Rotation::cur());
+            let y_q = meta.query_advice(y_q, Rotation::cur());
+            let x_b = meta.query_advice(x_b, Rotation::cur());
+            let y_b = meta.query_advice(y_b, Rotation::cur());
+
+            // the added point is the base if the bit is set, infinity otherwise
+            vec![
+                q.clone() * bit.clone() * (one.clone() - bit.clone()),
+                q.clone() * (x_q - bit.clone() * x_b),
+                q * (y_q - bit * y_b),
+            ]
+        });
+
+        meta.create_gate("grumpkin point on curve", |meta| {
+            let q = meta.query_selector(q_on_curve);
+            let x = meta.query_advice(x, Rotation::cur());
+            let y = meta.query_advice(y, Rotation::cur());
+
+            vec![
+                q * (y.clone() * y - x.clone() * x.clone() * x
+                    + Expression::Constant(Fr::from(17))),
+            ]
+        });
+
+        GrumpkinConfig {
+            x,
+            y,
+            x_q,
+            y_q,
+            lambda,
+            alpha,
+            beta,
+            gamma,
+            delta,
+            bits,
+            scalar,
+            tight,
+            table_x,
+            table_y,
+            max_window,
+            x_b,
+            y_b,
+            q_add,
+            q_window,
+            q_select,
+            q_on_curve,
+        }
+    }
+
+    /// Assign a point constrained to lie on the curve, which infinity does not
+    pub(crate) fn load_point(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        point: GrumpkinPoint,
+    ) -> Result<AssignedPoint, Error> {
+        self.config.q_on_curve.enable(region, *offset)?;
+        let assigned = self.assign_point(region, *offset, point)?;
+        *offset += 1;
+        Ok(assigned)
+    }
+
+    /// Assign a constant point
+    pub(crate) fn load_constant(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        point: GrumpkinPoint,
+    ) -> Result<AssignedPoint, Error> {
+        let assigned = self.assign_point(region, *offset, point)?;
+        region.constrain_constant(assigned.x.cell(), point.x)?;
+        region.constrain_constant(assigned.y.cell(), point.y)?;
+        *offset += 1;
+        Ok(assigned)
+    }
+
+    /// Add two assigned points, doubling them if they are equal
+    pub(crate) fn add(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        p: &AssignedPoint,
+        q: &AssignedPoint,
+    ) -> Result<AssignedPoint, Error> {
+        let (x, y, x_q, y_q) = self.assign_addition(region, *offset, p.value, q.value)?;
+        region.constrain_equal(x.cell(), p.x.cell())?;
+        region.constrain_equal(y.cell(), p.y.cell())?;
+        region.constrain_equal(x_q.cell(), q.x.cell())?;
+        region.constrain_equal(y_q.cell(), q.y.cell())?;
+        *offset += 1;
+
+        let sum = self.assign_point(region, *offset, p.value.add(&q.value))?;
+        *offset += 1;
+        Ok(sum)
+    }
+
+    /// Multiply the fixed point `base` by `scalar`, returning the cell holding
+    /// the scalar and the assigned product
+    pub(crate) fn mul_fixed(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        scalar: Fr,
+        base: GrumpkinPoint,
+    ) -> Result<(AssignedCell<Fr, Fr>, AssignedPoint), Error> {
+        // multiples 4^w * base of every window, most significant first
+        let mut window_bases = Vec::with_capacity(NUM_WINDOWS);
+        let mut window_base = base;
+        for _ in 0..NUM_WINDOWS {
+            window_bases.push(window_base);
+            window_base = window_base.double().double();
+        }
+        window_bases.reverse();
+
+        // the scalar starts from zero, tight against the largest field element
+        let mut acc = GrumpkinPoint::identity();
+        let mut acc_scalar = Fr::zero();
+        let mut tight = true;
+        let (mut scalar_cell, tight_cell) =
+            self.assign_scalar(region, *offset, acc_scalar, tight)?;
+        region.constrain_constant(scalar_cell.cell(), Fr::zero())?;
+        region.constrain_constant(tight_cell.cell(), Fr::one())?;
+
+        let max_windows = windows(&-Fr::one());
+        for (w, ((window, max_window), window_base)) in
+            windows(&scalar).into_iter().zip(max_windows).zip(window_bases).enumerate()
+        {
+            self.config.q_window.enable(region, *offset)?;
+            let table = [
+                GrumpkinPoint::identity(),
+                window_base,
+                window_base.double(),
+                window_base.double().add(&window_base),
+            ];
+            for (v, point) in table.iter().enumerate() {
+                region.assign_fixed(
+                    || "table x",
+                    self.config.table_x[v],
+                    *offset,
+                    || Value::known(point.x),
+                )?;
+                region.assign_fixed(
+                    || "table y",
+                    self.config.table_y[v],
+                    *offset,
+                    || Value::known(point.y),
+                )?;
+                region.assign_fixed(
+                    || "max window",
+                    self.config.max_window[v],
+                    *offset,
+                    || Value::known(Fr::from((v == max_window) as u64)),
+                )?;
+            }
+            for (i, bit) in self.config.bits.iter().enumerate() {
+                region.assign_advice(
+                    || "window bit",
+                    *bit,
+                    *offset,
+                    || Value::known(Fr::from(((window >> i) & 1) as u64)),
+                )?;
+            }
+            let (x, y, _, _) = self.assign_addition(region, *offset, acc, table[window])?;
+            if w == 0 {
+                // the accumulator starts from infinity
+                region.constrain_constant(x.cell(), Fr::zero())?;
+                region.constrain_constant(y.cell(), Fr::zero())?;
+            }
+            *offset += 1;
+
+            acc = acc.add(&table[window]);
+            acc_scalar = acc_scalar * Fr::from(4) + Fr::from(window as u64);
+            tight = tight && window == max_window;
+            (scalar_cell, _) = self.assign_scalar(region, *offset, acc_scalar, tight)?;
+        }
+
+        let product = self.assign_point(region, *offset, acc)?;
+        *offset += 1;
+
+        Ok((scalar_cell, product))
+    }
+
+    /// Sum of the multiplications of assigned points by the integers of
+    /// big-endian bytes of the same length, returning the cells of the bits of
+    /// every integer, most significant first, and the assigned sum
+    pub(crate) fn mul_bits(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        terms: &[(&[u8], &AssignedPoint)],
+    ) -> Result<(Vec<Vec<AssignedCell<Fr, Fr>>>, AssignedPoint), Error> {
+        let num_bytes = terms.first().map_or(0, |(bytes, _)| bytes.len());
+        if terms.iter().any(|(bytes, _)| bytes.len() != num_bytes) {
+            return Err(Error::Synthesis);
+        }
+
+        let mut bit_cells = vec![Vec::with_capacity(8 * num_bytes); terms.len()];
+        let mut acc = GrumpkinPoint::identity();
+        for i in 0..8 * num_bytes {
+            // double the accumulator, which starts from infinity
+            let (x, y, x_q, y_q) = self.assign_addition(region, *offset, acc, acc)?;
+            if i == 0 {
+                region.constrain_constant(x.cell(), Fr::zero())?;
+                region.constrain_constant(y.cell(), Fr::zero())?;
+            }
+            region.constrain_equal(x.cell(), x_q.cell())?;
+            region.constrain_equal(y.cell(), y_q.cell())?;
+            *offset += 1;
+            acc = acc.double();
+
+            for ((bytes, base), bit_cells) in terms.iter().zip(bit_cells.iter_mut()) {
+                let bit = (bytes[i / 8] >> (7 - i % 8)) & 1 == 1;
+                let addend = if bit { base.value } else { GrumpkinPoint::identity() };
+
+                self.config.q_select.enable(region, *offset)?;
+                bit_cells.push(region.assign_advice(
+                    || "bit",
+                    self.config.bits[0],
+                    *offset,
+                    || Value::known(Fr::from(bit as u64)),
+                )?);
+                base.x.copy_advice(|| "x_b", region, self.config.x_b, *offset)?;
+                base.y.copy_advice(|| "y_b", region, self.config.y_b, *offset)?;
+                self.assign_addition(region, *offset, acc, addend)?;
+                *offset += 1;
+                acc = acc.add(&addend);
+            }
+        }
+
+        let product = self.assign_point(region, *offset, acc)?;
+        *offset += 1;
+
+        Ok((bit_cells, product))
+    }
+
+    /// Pedersen commitment to `scalars` under `domain_separator`, as the sum of
+    /// the fixed base multiplications of every scalar by its generator,
+    /// returning the cells holding the scalars and the assigned commitment
+    pub(crate) fn pedersen(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        scalars: &[Fr],
+        domain_separator: u32,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, AssignedPoint), Error> {
+        let generators = pedersen_generators(domain_separator, scalars.len());
+
+        let mut scalar_cells = Vec::with_capacity(scalars.len());
+        let mut commitment: Option<AssignedPoint> = None;
+        for (scalar, generator) in scalars.iter().zip(generators) {
+            let (scalar_cell, term) = self.mul_fixed(region, offset, *scalar, generator)?;
+            scalar_cells.push(scalar_cell);
+            commitment = Some(match commitment {
+                Some(acc) => self.add(region, offset, &acc, &term)?,
+                None => term,
+            });
+        }
+
+        Ok((scalar_cells, commitment.ok_or(Error::Synthesis)?))
+    }
+
+    /// Assign the points of an addition and its witnesses at `offset`, the sum
+    /// being expected on the next row
+    fn assign_addition(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: usize,
+        p: GrumpkinPoint,
+        q: GrumpkinPoint,
+    ) -> Result<
+        (AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>),
+        Error,
+    > {
+        self.config.q_add.enable(region, offset)?;
+
+        let lambda = if p.x != q.x {
+            (q.y - p.y) * (q.x - p.x).invert().unwrap()
+        } else if p.y != Fr::zero() {
+            Fr::from(3) * p.x.square() * (Fr::from(2) * p.y).invert().unwrap()
+        } else {
+            Fr::zero()
+        };
+        let delta = if p.x == q.x { invert_or_zero(q.y + p.y) } else { Fr::zero() };
+        for (column, value) in [
+            (self.config.lambda, lambda),
+            (self.config.alpha, invert_or_zero(q.x - p.x)),
+            (self.config.beta, invert_or_zero(p.x)),
+            (self.config.gamma, invert_or_zero(q.x)),
+            (self.config.delta, delta),
+        ] {
+            region.assign_advice(|| "addition witness", column, offset, || Value::known(value))?;
+        }
+
+        let assigned = self.assign_point(region, offset, p)?;
+        let x_q = region.assign_advice(|| "x_q", self.config.x_q, offset, || Value::known(q.x))?;
+        let y_q = region.assign_advice(|| "y_q", self.config.y_q, offset, || Value::known(q.y))?;
+
+        Ok((assigned.x, assigned.y, x_q, y_q))
+    }
+
+    fn assign_point(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: usize,
+        point: GrumpkinPoint,
+    ) -> Result<AssignedPoint, Error> {
+        let x = region.assign_advice(|| "x", self.config.x, offset, || Value::known(point.x))?;
+        let y = region.assign_advice(|| "y", self.config.y, offset, || Value::known(point.y))?;
+        Ok(AssignedPoint { x, y, value: point })
+    }
+
+    fn assign_scalar(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: usize,
+        scalar: Fr,
+        tight: bool,
+    ) -> Result<(AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>), Error> {
+        let scalar = region.assign_advice(
+            || "scalar",
+            self.config.scalar,
+            offset,
+            || Value::known(scalar),
+        )?;
+        let tight = region.assign_advice(
+            || "tight",
+            self.config.tight,
+            offset,
+            || Value::known(Fr::from(tight as u64)),
+        )?;
+        Ok((scalar, tight))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GrumpkinChip, GrumpkinConfig, GrumpkinPoint};
+    use pse_halo2wrong::{
+        curves::group::ff::Field,
+        halo2::{
+            circuit::{Layouter, SimpleFloorPlanner},
+            dev::MockProver,
+            halo2curves::bn256::Fr,
+            plonk::{Circuit, ConstraintSystem, Error},
+        },
+    };
+
+    #[derive(Clone)]
+    struct GrumpkinTestCircuit {
+        scalars: [Fr; 2],
+        sum: GrumpkinPoint,
+    }
+
+    impl Circuit<Fr> for GrumpkinTestCircuit {
+        type Config = GrumpkinConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> GrumpkinConfig {
+            GrumpkinChip::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let grumpkin_chip = GrumpkinChip::new(config);
+            layouter.assign_region(
+                || "grumpkin",
+                |mut region| {
+                    let mut offset = 0;
+                    let generator = GrumpkinPoint::generator();
+                    let (_, p) = grumpkin_chip.mul_fixed(
+                        &mut region,
+                        &mut offset,
+                        self.scalars[0],
+                        generator,
+                    )?;
+                    let (_, q) = grumpkin_chip.mul_fixed(
+                        &mut region,
+                        &mut offset,
+                        self.scalars[1],
+                        generator,
+                    )?;
+                    let sum = grumpkin_chip.add(&mut region, &mut offset, &p, &q)?;
+                    // constrain the sum to the expected point
+                    region.constrain_constant(sum.x.cell(), self.sum.x)?;
+                    region.constrain_constant(sum.y.cell(), self.sum.y)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn run_sum(scalars: [Fr; 2], sum: GrumpkinPoint) -> Result<(), Vec<String>> {
+        let circuit = GrumpkinTestCircuit { scalars, sum };
+        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+        prover.verify().map_err(|failures| failures.iter().map(|f| f.to_string()).collect())
+    }
+
+    #[test]
+    fn test_grumpkin_generator_on_curve() {
+        let generator = GrumpkinPoint::generator();
+        assert_eq!(generator.y.square(), generator.x.square() * generator.x - Fr::from(17));
+    }
+
+    #[test]
+    fn test_grumpkin_fixed_base_mul() {
+        let generator = GrumpkinPoint::generator();
+        for scalar in [Fr::one(), Fr::from(0x1234_5678_9abc_def0), -Fr::one()] {
+            assert_eq!(run_sum([scalar, Fr::zero()], generator.mul(&scalar)), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_grumpkin_double() {
+        let scalar = Fr::from(7);
+        let sum = GrumpkinPoint::generator().mul(&Fr::from(14));
+        assert_eq!(run_sum([scalar, scalar], sum), Ok(()));
+    }
+
+    #[test]
+    fn test_grumpkin_add_opposite() {
+        let scalar = Fr::from(7);
+        assert_eq!(run_sum([scalar, -scalar], GrumpkinPoint::identity()), Ok(()));
+    }
+
+    #[test]
+    fn test_grumpkin_wrong_product() {
+        let sum = GrumpkinPoint::generator().mul(&Fr::from(4));
+        assert!(run_sum([Fr::from(3), Fr::zero()], sum).is_err());
+    }
+}
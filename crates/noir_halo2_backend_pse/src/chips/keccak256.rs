@@ -0,0 +1,813 @@
+use pse_halo2wrong::halo2::{
+    circuit::{AssignedCell, Region, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, VirtualCells},
+    poly::Rotation,
+};
+
+/// Number of message bytes absorbed per Keccak-f[1600] permutation
+const RATE: usize = 136;
+
+/// Keccak-f[1600] round constants
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rho rotation offsets of the lane at `(x, y)`, indexed by `x + 5 * y`
+const ROTATION_OFFSETS: [usize; 25] =
+    [0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14];
+
+/// An assigned 64-bit lane along with its native value
+#[derive(Clone, Debug)]
+struct Lane {
+    cell: AssignedCell<Fr, Fr>,
+    value: u64,
+}
+
+/// Cells and native values of the padded message
+struct Padding {
+    message_cells: Vec<AssignedCell<Fr, Fr>>,
+    length_cell: AssignedCell<Fr, Fr>,
+    bytes: Vec<(AssignedCell<Fr, Fr>, u8)>,
+    last_block: Vec<(AssignedCell<Fr, Fr>, bool)>,
+}
+
+/// Assigned message bytes, message length and digest bytes of a Keccak256 call
+pub(crate) type AssignedKeccak256 =
+    (Vec<AssignedCell<Fr, Fr>>, AssignedCell<Fr, Fr>, Vec<AssignedCell<Fr, Fr>>);
+
+/// Configuration of the Keccak256 chip.
+///
+/// Every 64-bit lane of the state is laid out on a row as its value and its 64
+/// boolean bits, so that the rotations of theta and rho are free and each step of
+/// the permutation is a gate over the bits of consecutive rows.
+///
+/// The message is padded in circuit against a length witness, one byte per row,
+/// so that fixed and variable length inputs share the same layout: the input is
+/// absorbed up to its maximal number of blocks and the digest is selected from the
+/// state after the block holding the end of the message.
+#[derive(Clone, Debug)]
+pub struct Keccak256Config {
+    bits: [Column<Advice>; 64],
+    lane: Column<Advice>,
+    bytes: [Column<Advice>; 8],
+    block_end: Column<Fixed>,
+
+    q_lane: Selector,
+    q_bytes: Selector,
+    q_xor3: Selector,
+    q_theta: Selector,
+    q_xor_rotate: [Selector; 25],
+    q_chi: Selector,
+    q_pad: Selector,
+    q_squeeze: Selector,
+}
+
+#[derive(Clone, Debug)]
+pub struct Keccak256Chip {
+    config: Keccak256Config,
+}
+
+fn query_bits(
+    meta: &mut VirtualCells<'_, Fr>,
+    bits: &[Column<Advice>; 64],
+    rotation: Rotation,
+) -> Vec<Expression<Fr>> {
+    bits.iter().map(|bit| meta.query_advice(*bit, rotation)).collect()
+}
+
+/// Little-endian composition of bits
+fn compose(bits: &[Expression<Fr>]) -> Expression<Fr> {
+    bits.iter().rev().fold(Expression::Constant(Fr::zero()), |acc, bit| {
+        acc * Expression::Constant(Fr::from(2)) + bit.clone()
+    })
+}
+
+fn xor2(x: Expression<Fr>, y: Expression<Fr>) -> Expression<Fr> {
+    x.clone() + y.clone() - Expression::Constant(Fr::from(2)) * x * y
+}
+
+fn xor3(x: Expression<Fr>, y: Expression<Fr>, z: Expression<Fr>) -> Expression<Fr> {
+    let two = Expression::Constant(Fr::from(2));
+    let four = Expression::Constant(Fr::from(4));
+    x.clone() + y.clone() + z.clone()
+        - two * (x.clone() * y.clone() + y.clone() * z.clone() + x.clone() * z.clone())
+        + four * x * y * z
+}
+
+impl Keccak256Chip {
+    pub fn new(config: Keccak256Config) -> Self {
+        Keccak256Chip { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Keccak256Config {
+        let bits = [(); 64].map(|_| meta.advice_column());
+        let lane = meta.advice_column();
+        let bytes = [(); 8].map(|_| meta.advice_column());
+        let block_end = meta.fixed_column();
+        let constants = meta.fixed_column();
+
+        meta.enable_equality(lane);
+        for byte in bytes {
+            meta.enable_equality(byte);
+        }
+        // initial state, round constants and padding flags are fixed
+        meta.enable_constant(constants);
+
+        let q_lane = meta.selector();
+        let q_bytes = meta.selector();
+        let q_xor3 = meta.selector();
+        let q_theta = meta.selector();
+        let q_xor_rotate = [(); 25].map(|_| meta.selector());
+        let q_chi = meta.selector();
+        let q_pad = meta.selector();
+        let q_squeeze = meta.selector();
+
+        let one = Expression::Constant(Fr::one());
+
+        meta.create_gate("keccak lane", |meta| {
+            let q = meta.query_selector(q_lane);
+            let lane = meta.query_advice(lane, Rotation::cur());
+            let bits = query_bits(meta, &bits, Rotation::cur());
+
+            let mut constraints: Vec<Expression<Fr>> = bits
+                .iter()
+                .map(|bit| q.clone() * bit.clone() * (one.clone() - bit.clone()))
+                .collect();
+            constraints.push(q * (compose(&bits) - lane));
+            constraints
+        });
+
+        // bytes are little-endian within the lane
+        meta.create_gate("keccak lane bytes", |meta| {
+            let q = meta.query_selector(q_bytes);
+            let bits = query_bits(meta, &bits, Rotation::cur());
+
+            bytes
+                .iter()
+                .enumerate()
+                .map(|(j, byte)| {
+                    let byte = meta.query_advice(*byte, Rotation::cur());
+                    q.clone() * (compose(&bits[8 * j..8 * j + 8]) - byte)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("keccak xor3", |meta| {
+            let q = meta.query_selector(q_xor3);
+            let a = query_bits(meta, &bits, Rotation::cur());
+            let b = query_bits(meta, &bits, Rotation::next());
+            let c = query_bits(meta, &bits, Rotation(2));
+            let out = query_bits(meta, &bits, Rotation(3));
+
+            (0..64)
+                .map(|i| {
+                    q.clone() * (out[i].clone() - xor3(a[i].clone(), b[i].clone(), c[i].clone()))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        // d = c_{x - 1} ^ rotl(c_{x + 1}, 1)
+        meta.create_gate("keccak theta", |meta| {
+            let q = meta.query_selector(q_theta);
+            let c_prev = query_bits(meta, &bits, Rotation::cur());
+            let c_next = query_bits(meta, &bits, Rotation::next());
+            let out = query_bits(meta, &bits, Rotation(2));
+
+            (0..64)
+                .map(|i| {
+                    q.clone()
+                        * (out[i].clone() - xor2(c_prev[i].clone(), c_next[(i + 63) % 64].clone()))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        // out = rotl(a ^ d, r) with the rho offset of each lane, the lane at (0, 0)
+        // is not rotated and its selector serves as a plain xor
+        for (q_xor_rotate, rotation) in q_xor_rotate.iter().zip(ROTATION_OFFSETS) {
+            meta.create_gate("keccak xor rotate", |meta| {
+                let q = meta.query_selector(*q_xor_rotate);
+                let a = query_bits(meta, &bits, Rotation::cur());
+                let d = query_bits(meta, &bits, Rotation::next());
+                let out = query_bits(meta, &bits, Rotation(2));
+
+                (0..64)
+                    .map(|i| {
+                        let j = (i + 64 - rotation) % 64;
+                        q.clone() * (out[i].clone() - xor2(a[j].clone(), d[j].clone()))
+                    })
+                    .collect::<Vec<_>>()
+            });
+        }
+
+        // out = b_x ^ (!b_{x + 1} & b_{x + 2})
+        meta.create_gate("keccak chi", |meta| {
+            let q = meta.query_selector(q_chi);
+            let b0 = query_bits(meta, &bits, Rotation::cur());
+            let b1 = query_bits(meta, &bits, Rotation::next());
+            let b2 = query_bits(meta, &bits, Rotation(2));
+            let out = query_bits(meta, &bits, Rotation(3));
+
+            (0..64)
+                .map(|i| {
+                    let and_not = (one.clone() - b1[i].clone()) * b2[i].clone();
+                    q.clone() * (out[i].clone() - xor2(b0[i].clone(), and_not))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        // one row per padded byte, `in_message` is set on a prefix of the rows of
+        // length `count`, the padded byte is the message byte within that prefix,
+        // gets 0x01 right after it and 0x80 at the end of the block holding it
+        let [message, in_message, padded, count, last_block, ended, _, _] = bytes;
+        meta.create_gate("keccak pad", |meta| {
+            let q = meta.query_selector(q_pad);
+            let block_end = meta.query_fixed(block_end, Rotation::cur());
+            let message = meta.query_advice(message, Rotation::cur());
+            let in_message_prev = meta.query_advice(in_message, Rotation::prev());
+            let in_message = meta.query_advice(in_message, Rotation::cur());
+            let padded = meta.query_advice(padded, Rotation::cur());
+            let count_prev = meta.query_advice(count, Rotation::prev());
+            let count = meta.query_advice(count, Rotation::cur());
+            let last_block = meta.query_advice(last_block, Rotation::cur());
+            let ended_prev = meta.query_advice(ended, Rotation::prev());
+            let ended = meta.query_advice(ended, Rotation::cur());
+
+            let past_message = one.clone() - in_message.clone();
+            let message_end = in_message_prev.clone() - in_message.clone();
+
+            vec![
+                q.clone() * in_message.clone() * past_message.clone(),
+                q.clone() * in_message.clone() * (one.clone() - in_message_prev),
+                q.clone() * (count - count_prev - in_message.clone()),
+                // `ended` holds whether the message ended by the last block end
+                q.clone()
+                    * (last_block.clone()
+                        - block_end.clone() * (past_message.clone() - ended_prev.clone())),
+                q.clone()
+                    * (ended
+                        - block_end.clone() * past_message
+                        - (one.clone() - block_end) * ended_prev),
+                q * (padded
+                    - in_message * message
+                    - message_end
+                    - last_block * Expression::Constant(Fr::from(0x80))),
+            ]
+        });
+
+        // accumulates the lane of the last block into the digest lane
+        meta.create_gate("keccak squeeze", |meta| {
+            let q = meta.query_selector(q_squeeze);
+            let lane = meta.query_advice(lane, Rotation::cur());
+            let last_block = meta.query_advice(last_block, Rotation::cur());
+            let count_prev = meta.query_advice(count, Rotation::prev());
+            let count = meta.query_advice(count, Rotation::cur());
+
+            vec![q * (count - count_prev - last_block * lane)]
+        });
+
+        Keccak256Config {
+            bits,
+            lane,
+            bytes,
+            block_end,
+            q_lane,
+            q_bytes,
+            q_xor3,
+            q_theta,
+            q_xor_rotate,
+            q_chi,
+            q_pad,
+            q_squeeze,
+        }
+    }
+
+    /// Assign the Keccak256 digest of the first `length` bytes of `message`,
+    /// returning the cells holding the message bytes, the length and the 32
+    /// digest bytes
+    pub(crate) fn digest(
+        &self,
+        region: &mut Region<'_, Fr>,
+        message: &[u8],
+        length: usize,
+    ) -> Result<AssignedKeccak256, Error> {
+        let mut offset = 0;
+        let padding = self.pad(region, &mut offset, message, length)?;
+
+        let mut state = Vec::with_capacity(25);
+        for _ in 0..25 {
+            let lane = self.assign_lane(region, &mut offset, 0)?;
+            region.constrain_constant(lane.cell.cell(), Fr::zero())?;
+            state.push(lane);
+        }
+
+        let mut squeezed = Vec::with_capacity(padding.last_block.len());
+        for block in padding.bytes.chunks(RATE) {
+            for (k, lane_bytes) in block.chunks(8).enumerate() {
+                let value =
+                    lane_bytes.iter().rev().fold(0u64, |acc, (_, byte)| (acc << 8) | *byte as u64);
+                let byte_cells = self.assign_bytes(region, offset, value)?;
+                for (byte_cell, (padded_cell, _)) in byte_cells.iter().zip(lane_bytes) {
+                    region.constrain_equal(byte_cell.cell(), padded_cell.cell())?;
+                }
+                let lane = self.assign_lane(region, &mut offset, value)?;
+                state[k] = self.xor_rotate(region, &mut offset, 0, &state[k], &lane)?;
+            }
+            state = self.permute(region, &mut offset, state)?;
+            squeezed.push(state[..4].to_vec());
+        }
+
+        let mut digest_cells = Vec::with_capacity(32);
+        for k in 0..4 {
+            let candidates: Vec<&Lane> = squeezed.iter().map(|lanes| &lanes[k]).collect();
+            digest_cells.extend(self.squeeze(
+                region,
+                &mut offset,
+                &padding.last_block,
+                &candidates,
+            )?);
+        }
+
+        Ok((padding.message_cells, padding.length_cell, digest_cells))
+    }
+
+    /// Keccak-f[1600] permutation of the state
+    fn permute(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        mut state: Vec<Lane>,
+    ) -> Result<Vec<Lane>, Error> {
+        for round_constant in ROUND_CONSTANTS {
+            // theta
+            let mut c = Vec::with_capacity(5);
+            for x in 0..5 {
+                let partial =
+                    self.xor3(region, offset, &state[x], &state[x + 5], &state[x + 10])?;
+                c.push(self.xor3(region, offset, &partial, &state[x + 15], &state[x + 20])?);
+            }
+            let mut d = Vec::with_capacity(5);
+            for x in 0..5 {
+                d.push(self.theta(region, offset, &c[(x + 4) % 5], &c[(x + 1) % 5])?);
+            }
+
+            // theta, rho and pi, the lane at (x, y) moves to (y, 2x + 3y)
+            let mut b = Vec::with_capacity(25);
+            for i in 0..25 {
+                let (x, y) = ((i % 5 + 3 * (i / 5)) % 5, i % 5);
+                b.push(self.xor_rotate(region, offset, x + 5 * y, &state[x + 5 * y], &d[x])?);
+            }
+
+            // chi
+            let mut next = Vec::with_capacity(25);
+            for y in 0..5 {
+                for x in 0..5 {
+                    next.push(self.chi(
+                        region,
+                        offset,
+                        &b[x + 5 * y],
+                        &b[(x + 1) % 5 + 5 * y],
+                        &b[(x + 2) % 5 + 5 * y],
+                    )?);
+                }
+            }
+
+            // iota
+            let round_constant_lane = self.assign_lane(region, offset, round_constant)?;
+            region.constrain_constant(round_constant_lane.cell.cell(), Fr::from(round_constant))?;
+            next[0] = self.xor_rotate(region, offset, 0, &next[0], &round_constant_lane)?;
+
+            state = next;
+        }
+
+        Ok(state)
+    }
+
+    /// Pad the first `length` bytes of `message` over the maximal number of blocks
+    /// the message can span
+    fn pad(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        message: &[u8],
+        length: usize,
+    ) -> Result<Padding, Error> {
+        let [message_col, in_message_col, padded_col, count_col, last_block_col, ended_col, _, _] =
+            self.config.bytes;
+        let num_bytes = (message.len() / RATE + 1) * RATE;
+
+        // the row above the first byte starts the message, with nothing counted
+        let mut in_message_prev = true;
+        let mut count = 0;
+        let mut ended = false;
+        for (column, value) in [(in_message_col, 1u64), (count_col, 0), (ended_col, 0)] {
+            let cell = region.assign_advice(
+                || "pad start",
+                column,
+                *offset,
+                || Value::known(Fr::from(value)),
+            )?;
+            region.constrain_constant(cell.cell(), Fr::from(value))?;
+        }
+        *offset += 1;
+
+        let mut message_cells = Vec::with_capacity(message.len());
+        let mut bytes = Vec::with_capacity(num_bytes);
+        let mut last_block_cells = Vec::with_capacity(num_bytes / RATE);
+        let mut length_cell = None;
+        for i in 0..num_bytes {
+            let is_block_end = i % RATE == RATE - 1;
+            let message_byte = message.get(i).copied().unwrap_or(0);
+            let in_message = i < length;
+            let last_block = is_block_end && !in_message && !ended;
+            let mut padded = if in_message { message_byte } else { 0 };
+            if in_message_prev && !in_message {
+                padded |= 0x01;
+            }
+            if last_block {
+                padded |= 0x80;
+            }
+            count += in_message as u64;
+            if is_block_end {
+                ended = !in_message;
+            }
+
+            self.config.q_pad.enable(region, *offset)?;
+            region.assign_fixed(
+                || "block end",
+                self.config.block_end,
+                *offset,
+                || Value::known(Fr::from(is_block_end as u64)),
+            )?;
+            let message_cell = region.assign_advice(
+                || "message byte",
+                message_col,
+                *offset,
+                || Value::known(Fr::from(message_byte as u64)),
+            )?;
+            if i < message.len() {
+                message_cells.push(message_cell);
+            } else {
+                region.constrain_constant(message_cell.cell(), Fr::zero())?;
+            }
+            region.assign_advice(
+                || "in message",
+                in_message_col,
+                *offset,
+                || Value::known(Fr::from(in_message as u64)),
+            )?;
+            let padded_cell = region.assign_advice(
+                || "padded byte",
+                padded_col,
+                *offset,
+                || Value::known(Fr::from(padded as u64)),
+            )?;
+            bytes.push((padded_cell, padded));
+            length_cell = Some(region.assign_advice(
+                || "count",
+                count_col,
+                *offset,
+                || Value::known(Fr::from(count)),
+            )?);
+            let last_block_cell = region.assign_advice(
+                || "last block",
+                last_block_col,
+                *offset,
+                || Value::known(Fr::from(last_block as u64)),
+            )?;
+            if is_block_end {
+                last_block_cells.push((last_block_cell, last_block));
+            }
+            region.assign_advice(
+                || "ended",
+                ended_col,
+                *offset,
+                || Value::known(Fr::from(ended as u64)),
+            )?;
+
+            in_message_prev = in_message;
+            *offset += 1;
+        }
+
+        Ok(Padding {
+            message_cells,
+            length_cell: length_cell.expect("at least one block is padded"),
+            bytes,
+            last_block: last_block_cells,
+        })
+    }
+
+    /// Select the candidate lane of the block holding the end of the message and
+    /// decompose it into its little-endian bytes
+    fn squeeze(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        last_block: &[(AssignedCell<Fr, Fr>, bool)],
+        candidates: &[&Lane],
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        let [_, _, _, count_col, last_block_col, _, _, _] = self.config.bytes;
+
+        let mut acc = region.assign_advice(
+            || "squeeze start",
+            count_col,
+            *offset,
+            || Value::known(Fr::zero()),
+        )?;
+        region.constrain_constant(acc.cell(), Fr::zero())?;
+        *offset += 1;
+
+        let mut value = 0;
+        for ((flag, is_last), candidate) in last_block.iter().zip(candidates) {
+            if *is_last {
+                value = candidate.value;
+            }
+
+            self.config.q_squeeze.enable(region, *offset)?;
+            candidate.cell.copy_advice(|| "candidate", region, self.config.lane, *offset)?;
+            flag.copy_advice(|| "last block", region, last_block_col, *offset)?;
+            acc = region.assign_advice(
+                || "squeeze",
+                count_col,
+                *offset,
+                || Value::known(Fr::from(value)),
+            )?;
+            *offset += 1;
+        }
+
+        let byte_cells = self.assign_bytes(region, *offset, value)?;
+        let lane = self.assign_lane(region, offset, value)?;
+        region.constrain_equal(lane.cell.cell(), acc.cell())?;
+        Ok(byte_cells)
+    }
+
+    /// Assign a lane along with its bits
+    fn assign_lane(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        value: u64,
+    ) -> Result<Lane, Error> {
+        self.config.q_lane.enable(region, *offset)?;
+        for (i, bit) in self.config.bits.iter().enumerate() {
+            region.assign_advice(
+                || "bit",
+                *bit,
+                *offset,
+                || Value::known(Fr::from((value >> i) & 1)),
+            )?;
+        }
+        let cell = region.assign_advice(
+            || "lane",
+            self.config.lane,
+            *offset,
+            || Value::known(Fr::from(value)),
+        )?;
+        *offset += 1;
+
+        Ok(Lane { cell, value })
+    }
+
+    /// Copy a lane into a new row along with its bits
+    fn copy_lane(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        lane: &Lane,
+    ) -> Result<Lane, Error> {
+        let copy = self.assign_lane(region, offset, lane.value)?;
+        region.constrain_equal(copy.cell.cell(), lane.cell.cell())?;
+        Ok(copy)
+    }
+
+    /// Assign the little-endian bytes of the lane at `offset`
+    fn assign_bytes(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: usize,
+        value: u64,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        self.config.q_bytes.enable(region, offset)?;
+        value
+            .to_le_bytes()
+            .iter()
+            .zip(self.config.bytes.iter())
+            .map(|(byte, column)| {
+                region.assign_advice(
+                    || "byte",
+                    *column,
+                    offset,
+                    || Value::known(Fr::from(*byte as u64)),
+                )
+            })
+            .collect()
+    }
+
+    fn xor3(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        a: &Lane,
+        b: &Lane,
+        c: &Lane,
+    ) -> Result<Lane, Error> {
+        self.config.q_xor3.enable(region, *offset)?;
+        for lane in [a, b, c] {
+            self.copy_lane(region, offset, lane)?;
+        }
+        self.assign_lane(region, offset, a.value ^ b.value ^ c.value)
+    }
+
+    fn theta(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        c_prev: &Lane,
+        c_next: &Lane,
+    ) -> Result<Lane, Error> {
+        self.config.q_theta.enable(region, *offset)?;
+        for lane in [c_prev, c_next] {
+            self.copy_lane(region, offset, lane)?;
+        }
+        self.assign_lane(region, offset, c_prev.value ^ c_next.value.rotate_left(1))
+    }
+
+    /// Xor two lanes and rotate the result by the rho offset of the lane at `index`
+    fn xor_rotate(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        index: usize,
+        a: &Lane,
+        d: &Lane,
+    ) -> Result<Lane, Error> {
+        self.config.q_xor_rotate[index].enable(region, *offset)?;
+        for lane in [a, d] {
+            self.copy_lane(region, offset, lane)?;
+        }
+        self.assign_lane(
+            region,
+            offset,
+            (a.value ^ d.value).rotate_left(ROTATION_OFFSETS[index] as u32),
+        )
+    }
+
+    fn chi(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        b0: &Lane,
+        b1: &Lane,
+        b2: &Lane,
+    ) -> Result<Lane, Error> {
+        self.config.q_chi.enable(region, *offset)?;
+        for lane in [b0, b1, b2] {
+            self.copy_lane(region, offset, lane)?;
+        }
+        self.assign_lane(region, offset, b0.value ^ (!b1.value & b2.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Keccak256Chip, Keccak256Config};
+    use pse_halo2wrong::halo2::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+
+    #[derive(Clone, Default)]
+    struct Keccak256TestCircuit {
+        message: Vec<u8>,
+        length: usize,
+        digest: Vec<u8>,
+    }
+
+    impl Circuit<Fr> for Keccak256TestCircuit {
+        type Config = Keccak256Config;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Keccak256Config {
+            Keccak256Chip::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let keccak256_chip = Keccak256Chip::new(config);
+            layouter.assign_region(
+                || "keccak256",
+                |mut region| {
+                    let (_, length_cell, digest_cells) =
+                        keccak256_chip.digest(&mut region, &self.message, self.length)?;
+                    // constrain the length and digest to the expected test vector
+                    region.constrain_constant(length_cell.cell(), Fr::from(self.length as u64))?;
+                    for (cell, byte) in digest_cells.iter().zip(self.digest.iter()) {
+                        region.constrain_constant(cell.cell(), Fr::from(*byte as u64))?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn run_test_vector(message: &[u8], length: usize, digest: &str) -> Result<(), Vec<String>> {
+        let circuit = Keccak256TestCircuit {
+            message: message.to_vec(),
+            length,
+            digest: hex::decode(digest).unwrap(),
+        };
+        let prover = MockProver::run(14, &circuit, vec![]).unwrap();
+        prover.verify().map_err(|failures| failures.iter().map(|f| f.to_string()).collect())
+    }
+
+    #[test]
+    fn test_keccak256_empty_message() {
+        assert_eq!(
+            run_test_vector(
+                b"",
+                0,
+                "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_keccak256_abc() {
+        assert_eq!(
+            run_test_vector(
+                b"abc",
+                3,
+                "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_keccak256_variable_length() {
+        assert_eq!(
+            run_test_vector(
+                b"abcdefgh",
+                3,
+                "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_keccak256_two_blocks() {
+        assert_eq!(
+            run_test_vector(
+                &[b'a'; 200],
+                200,
+                "96ea54061def936c4be90b518992fdc6f12f535068a256229aca54267b4d084d"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_keccak256_wrong_digest() {
+        assert!(run_test_vector(
+            b"abc",
+            3,
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c46"
+        )
+        .is_err());
+    }
+}
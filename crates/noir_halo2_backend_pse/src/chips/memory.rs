@@ -0,0 +1,458 @@
+use pse_halo2wrong::halo2::{
+    circuit::{AssignedCell, Region, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+/// A memory access of a RAM trace
+#[derive(Clone, Debug)]
+pub(crate) struct MemoryAccess {
+    pub(crate) index: u64,
+    pub(crate) value: Fr,
+    pub(crate) is_write: bool,
+}
+
+/// Assigned index and value of every access of a trace, in trace order
+pub(crate) type AssignedAccesses = Vec<(AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>)>;
+
+/// Configuration of the memory chip.
+///
+/// Every block is tagged with its id plus one so that lookups never mix the
+/// rows of two blocks, or match the zeroes of rows where the selectors are off.
+/// Rows looked up into carry the tag in their own fixed column, which keeps
+/// the table expressions of degree one.
+///
+/// A ROM block lays out its initial values at fixed positions, and every read
+/// is a lookup of its index and value into them.
+///
+/// A RAM block lays out its accesses with their position in the trace as a
+/// fixed timestamp, followed by the same accesses sorted by index and then by
+/// timestamp. The sorted accesses are looked up in the trace, and since the
+/// sorted accesses are strictly increasing and the timestamps of the trace are
+/// unique, both hold the same accesses. Consecutive sorted accesses then check
+/// that a read returns the last value written at its index, and that every
+/// index is written before it is read. The sorting is enforced by range
+/// checking the increase of the index, or of the timestamp within an index,
+/// and the caller is left to range check these differences.
+#[derive(Clone, Debug)]
+pub struct MemoryConfig {
+    tag: Column<Fixed>,
+    table_tag: Column<Fixed>,
+    // table index of a ROM, or timestamp of a RAM access
+    position: Column<Fixed>,
+    access_write: Column<Fixed>,
+
+    index: Column<Advice>,
+    value: Column<Advice>,
+    write: Column<Advice>,
+    time: Column<Advice>,
+    same_index: Column<Advice>,
+    diff: Column<Advice>,
+
+    q_read: Selector,
+    q_sorted: Selector,
+    q_first: Selector,
+    q_step: Selector,
+}
+
+#[derive(Clone, Debug)]
+pub struct MemoryChip {
+    config: MemoryConfig,
+}
+
+impl MemoryChip {
+    pub fn new(config: MemoryConfig) -> Self {
+        MemoryChip { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> MemoryConfig {
+        let tag = meta.fixed_column();
+        let table_tag = meta.fixed_column();
+        let position = meta.fixed_column();
+        let access_write = meta.fixed_column();
+        let index = meta.advice_column();
+        let value = meta.advice_column();
+        let write = meta.advice_column();
+        let time = meta.advice_column();
+        let same_index = meta.advice_column();
+        let diff = meta.advice_column();
+
+        for column in [index, value, diff] {
+            meta.enable_equality(column);
+        }
+
+        let q_read = meta.complex_selector();
+        let q_sorted = meta.complex_selector();
+        let q_first = meta.selector();
+        let q_step = meta.selector();
+
+        meta.lookup_any("rom read", |meta| {
+            let q_read = meta.query_selector(q_read);
+            let tag = meta.query_fixed(tag, Rotation::cur());
+            let table_tag = meta.query_fixed(table_tag, Rotation::cur());
+            let position = meta.query_fixed(position, Rotation::cur());
+            let index = meta.query_advice(index, Rotation::cur());
+            let value = meta.query_advice(value, Rotation::cur());
+
+            vec![
+                (q_read.clone() * tag, table_tag),
+                (q_read.clone() * index, position),
+                (q_read * value.clone(), value),
+            ]
+        });
+
+        meta.lookup_any("ram sorted access", |meta| {
+            let q_sorted = meta.query_selector(q_sorted);
+            let tag = meta.query_fixed(tag, Rotation::cur());
+            let table_tag = meta.query_fixed(table_tag, Rotation::cur());
+            let position = meta.query_fixed(position, Rotation::cur());
+            let access_write = meta.query_fixed(access_write, Rotation::cur());
+            let index = meta.query_advice(index, Rotation::cur());
+            let value = meta.query_advice(value, Rotation::cur());
+            let write = meta.query_advice(write, Rotation::cur());
+            let time = meta.query_advice(time, Rotation::cur());
+
+            vec![
+                (q_sorted.clone() * tag, table_tag),
+                (q_sorted.clone() * index.clone(), index),
+                (q_sorted.clone() * time, position),
+                (q_sorted.clone() * value.clone(), value),
+                (q_sorted * write, access_write),
+            ]
+        });
+
+        meta.create_gate("ram first access", |meta| {
+            let q = meta.query_selector(q_first);
+            let write = meta.query_advice(write, Rotation::cur());
+
+            vec![q * (Expression::Constant(Fr::one()) - write)]
+        });
+
+        meta.create_gate("ram sorted step", |meta| {
+            let q = meta.query_selector(q_step);
+            let one = Expression::Constant(Fr::one());
+            let index_prev = meta.query_advice(index, Rotation::prev());
+            let index = meta.query_advice(index, Rotation::cur());
+            let time_prev = meta.query_advice(time, Rotation::prev());
+            let time = meta.query_advice(time, Rotation::cur());
+            let value_prev = meta.query_advice(value, Rotation::prev());
+            let value = meta.query_advice(value, Rotation::cur());
+            let write = meta.query_advice(write, Rotation::cur());
+            let same = meta.query_advice(same_index, Rotation::cur());
+            let diff = meta.query_advice(diff, Rotation::cur());
+
+            let increase = same.clone() * (time - time_prev - one.clone())
+                + (one.clone() - same.clone()) * (index.clone() - index_prev.clone() - one.clone());
+            vec![
+                q.clone() * same.clone() * (one.clone() - same.clone()),
+                q.clone() * same.clone() * (index - index_prev),
+                q.clone() * (diff - increase),
+                // a new index starts with a write, so a read always has a previous access
+                q.clone() * (one.clone() - write.clone()) * (one.clone() - same),
+                q * (one - write) * (value - value_prev),
+            ]
+        });
+
+        MemoryConfig {
+            tag,
+            table_tag,
+            position,
+            access_write,
+            index,
+            value,
+            write,
+            time,
+            same_index,
+            diff,
+            q_read,
+            q_sorted,
+            q_first,
+            q_step,
+        }
+    }
+
+    /// Assign a ROM block holding `init` and the `reads` of it, returning the
+    /// cells of the initial values and the index and value cells of the reads
+    pub(crate) fn rom(
+        &self,
+        region: &mut Region<'_, Fr>,
+        block_id: u32,
+        init: &[Fr],
+        reads: &[(u64, Fr)],
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, AssignedAccesses), Error> {
+        let tag = Fr::from(block_id as u64 + 1);
+        let mut offset = 0;
+
+        let mut init_cells = Vec::with_capacity(init.len());
+        for (i, value) in init.iter().enumerate() {
+            region.assign_fixed(
+                || "table tag",
+                self.config.table_tag,
+                offset,
+                || Value::known(tag),
+            )?;
+            region.assign_fixed(
+                || "position",
+                self.config.position,
+                offset,
+                || Value::known(Fr::from(i as u64)),
+            )?;
+            init_cells.push(region.assign_advice(
+                || "value",
+                self.config.value,
+                offset,
+                || Value::known(*value),
+            )?);
+            offset += 1;
+        }
+
+        let mut read_cells = Vec::with_capacity(reads.len());
+        for (index, value) in reads {
+            self.config.q_read.enable(region, offset)?;
+            region.assign_fixed(|| "tag", self.config.tag, offset, || Value::known(tag))?;
+            let index = region.assign_advice(
+                || "index",
+                self.config.index,
+                offset,
+                || Value::known(Fr::from(*index)),
+            )?;
+            let value = region.assign_advice(
+                || "value",
+                self.config.value,
+                offset,
+                || Value::known(*value),
+            )?;
+            read_cells.push((index, value));
+            offset += 1;
+        }
+
+        Ok((init_cells, read_cells))
+    }
+
+    /// Assign a RAM block and its `accesses`, returning the index and value
+    /// cells of the accesses and the differences between consecutive sorted
+    /// accesses, which have to be range checked
+    pub(crate) fn ram(
+        &self,
+        region: &mut Region<'_, Fr>,
+        block_id: u32,
+        accesses: &[MemoryAccess],
+    ) -> Result<(AssignedAccesses, Vec<AssignedCell<Fr, Fr>>), Error> {
+        let tag = Fr::from(block_id as u64 + 1);
+        let mut offset = 0;
+
+        let mut access_cells = Vec::with_capacity(accesses.len());
+        for (time, access) in accesses.iter().enumerate() {
+            region.assign_fixed(
+                || "table tag",
+                self.config.table_tag,
+                offset,
+                || Value::known(tag),
+            )?;
+            // timestamps start at one as rows without a selector look up zeroes
+            region.assign_fixed(
+                || "timestamp",
+                self.config.position,
+                offset,
+                || Value::known(Fr::from(time as u64 + 1)),
+            )?;
+            region.assign_fixed(
+                || "write",
+                self.config.access_write,
+                offset,
+                || Value::known(Fr::from(access.is_write as u64)),
+            )?;
+            let index = region.assign_advice(
+                || "index",
+                self.config.index,
+                offset,
+                || Value::known(Fr::from(access.index)),
+            )?;
+            let value = region.assign_advice(
+                || "value",
+                self.config.value,
+                offset,
+                || Value::known(access.value),
+            )?;
+            access_cells.push((index, value));
+            offset += 1;
+        }
+
+        let mut sorted: Vec<(usize, &MemoryAccess)> =
+            accesses.iter().enumerate().map(|(time, access)| (time + 1, access)).collect();
+        sorted.sort_by(|(time_a, a), (time_b, b)| (a.index, time_a).cmp(&(b.index, time_b)));
+
+        let mut diff_cells = Vec::with_capacity(sorted.len().saturating_sub(1));
+        let mut previous: Option<(usize, &MemoryAccess)> = None;
+        for (time, access) in sorted {
+            self.config.q_sorted.enable(region, offset)?;
+            region.assign_fixed(|| "tag", self.config.tag, offset, || Value::known(tag))?;
+            region.assign_advice(
+                || "index",
+                self.config.index,
+                offset,
+                || Value::known(Fr::from(access.index)),
+            )?;
+            region.assign_advice(
+                || "value",
+                self.config.value,
+                offset,
+                || Value::known(access.value),
+            )?;
+            region.assign_advice(
+                || "write",
+                self.config.write,
+                offset,
+                || Value::known(Fr::from(access.is_write as u64)),
+            )?;
+            region.assign_advice(
+                || "timestamp",
+                self.config.time,
+                offset,
+                || Value::known(Fr::from(time as u64)),
+            )?;
+
+            match previous {
+                None => self.config.q_first.enable(region, offset)?,
+                Some((previous_time, previous_access)) => {
+                    self.config.q_step.enable(region, offset)?;
+                    let same = previous_access.index == access.index;
+                    let diff = if same {
+                        (time - previous_time - 1) as u64
+                    } else {
+                        access.index - previous_access.index - 1
+                    };
+                    region.assign_advice(
+                        || "same index",
+                        self.config.same_index,
+                        offset,
+                        || Value::known(Fr::from(same as u64)),
+                    )?;
+                    diff_cells.push(region.assign_advice(
+                        || "diff",
+                        self.config.diff,
+                        offset,
+                        || Value::known(Fr::from(diff)),
+                    )?);
+                }
+            }
+            previous = Some((time, access));
+            offset += 1;
+        }
+
+        Ok((access_cells, diff_cells))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MemoryAccess, MemoryChip, MemoryConfig};
+    use pse_halo2wrong::halo2::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+
+    #[derive(Clone, Default)]
+    struct MemoryTestCircuit {
+        rom_init: Vec<Fr>,
+        rom_reads: Vec<(u64, Fr)>,
+        ram_accesses: Vec<MemoryAccess>,
+    }
+
+    impl Circuit<Fr> for MemoryTestCircuit {
+        type Config = MemoryConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> MemoryConfig {
+            MemoryChip::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let memory_chip = MemoryChip::new(config);
+            layouter.assign_region(
+                || "rom",
+                |mut region| {
+                    memory_chip.rom(&mut region, 0, &self.rom_init, &self.rom_reads)?;
+                    Ok(())
+                },
+            )?;
+            layouter.assign_region(
+                || "ram",
+                |mut region| {
+                    memory_chip.ram(&mut region, 1, &self.ram_accesses)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn write(index: u64, value: u64) -> MemoryAccess {
+        MemoryAccess { index, value: Fr::from(value), is_write: true }
+    }
+
+    fn read(index: u64, value: u64) -> MemoryAccess {
+        MemoryAccess { index, value: Fr::from(value), is_write: false }
+    }
+
+    fn run(circuit: MemoryTestCircuit) -> Result<(), Vec<String>> {
+        let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+        prover.verify().map_err(|failures| failures.iter().map(|f| f.to_string()).collect())
+    }
+
+    fn rom_circuit(reads: &[(u64, u64)]) -> MemoryTestCircuit {
+        MemoryTestCircuit {
+            rom_init: [10, 20, 30].map(Fr::from).to_vec(),
+            rom_reads: reads.iter().map(|(index, value)| (*index, Fr::from(*value))).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn ram_circuit(accesses: &[MemoryAccess]) -> MemoryTestCircuit {
+        let mut ram_accesses = vec![write(0, 10), write(1, 20), write(2, 30)];
+        ram_accesses.extend_from_slice(accesses);
+        MemoryTestCircuit { ram_accesses, ..Default::default() }
+    }
+
+    #[test]
+    fn test_rom_reads() {
+        assert_eq!(run(rom_circuit(&[(2, 30), (0, 10), (2, 30)])), Ok(()));
+    }
+
+    #[test]
+    fn test_rom_wrong_read() {
+        assert!(run(rom_circuit(&[(1, 30)])).is_err());
+    }
+
+    #[test]
+    fn test_rom_read_out_of_bounds() {
+        assert!(run(rom_circuit(&[(3, 0)])).is_err());
+    }
+
+    #[test]
+    fn test_ram_reads_and_writes() {
+        let accesses = [read(1, 20), write(1, 25), read(1, 25), read(0, 10), write(2, 5)];
+        assert_eq!(run(ram_circuit(&accesses)), Ok(()));
+    }
+
+    #[test]
+    fn test_ram_stale_read() {
+        assert!(run(ram_circuit(&[write(1, 25), read(1, 20)])).is_err());
+    }
+
+    #[test]
+    fn test_ram_read_before_write() {
+        assert!(run(ram_circuit(&[read(3, 0)])).is_err());
+    }
+}
@@ -0,0 +1,8 @@
+pub(crate) mod aggregation;
+pub(crate) mod bitwise;
+pub(crate) mod blake2s;
+pub(crate) mod ecdsa;
+pub(crate) mod grumpkin;
+pub(crate) mod keccak256;
+pub(crate) mod memory;
+pub(crate) mod sha256;
@@ -0,0 +1,676 @@
+use pse_halo2wrong::halo2::{
+    circuit::{AssignedCell, Region, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, VirtualCells},
+    poly::Rotation,
+};
+
+/// SHA256 round constants
+const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA256 initial hash values
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Shifts xor-ed together by the sigma functions
+#[derive(Clone, Copy, Debug)]
+enum Shift {
+    Rotr(usize),
+    Shr(usize),
+}
+
+impl Shift {
+    fn apply(&self, x: u32) -> u32 {
+        match *self {
+            Shift::Rotr(n) => x.rotate_right(n as u32),
+            Shift::Shr(n) => x >> n,
+        }
+    }
+
+    /// Expression of bit `i` of the shifted word
+    fn bit(&self, bits: &[Expression<Fr>], i: usize) -> Expression<Fr> {
+        match *self {
+            Shift::Rotr(n) => bits[(i + n) % 32].clone(),
+            Shift::Shr(n) if i + n < 32 => bits[i + n].clone(),
+            Shift::Shr(_) => Expression::Constant(Fr::zero()),
+        }
+    }
+}
+
+const BIG_SIGMA0: [Shift; 3] = [Shift::Rotr(2), Shift::Rotr(13), Shift::Rotr(22)];
+const BIG_SIGMA1: [Shift; 3] = [Shift::Rotr(6), Shift::Rotr(11), Shift::Rotr(25)];
+const SMALL_SIGMA0: [Shift; 3] = [Shift::Rotr(7), Shift::Rotr(18), Shift::Shr(3)];
+const SMALL_SIGMA1: [Shift; 3] = [Shift::Rotr(17), Shift::Rotr(19), Shift::Shr(10)];
+
+fn sigma(shifts: &[Shift; 3], x: u32) -> u32 {
+    shifts.iter().fold(0, |acc, shift| acc ^ shift.apply(x))
+}
+
+/// An assigned 32-bit word along with its native value
+#[derive(Clone, Debug)]
+struct Word {
+    cell: AssignedCell<Fr, Fr>,
+    value: u32,
+}
+
+/// Assigned message bytes and digest bytes of a SHA256 call
+pub(crate) type AssignedSha256 = (Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>);
+
+/// Configuration of the SHA256 chip.
+///
+/// Every 32-bit word is laid out on a row as its value and its 32 boolean bits,
+/// so that rotations and shifts are free and the sigma, choose and majority
+/// functions are gates over the bits of consecutive rows. Modular additions
+/// are checked on the word values with a 3-bit carry.
+#[derive(Clone, Debug)]
+pub struct Sha256Config {
+    bits: [Column<Advice>; 32],
+    word: Column<Advice>,
+    carry: [Column<Advice>; 3],
+    bytes: [Column<Advice>; 4],
+    round_constant: Column<Fixed>,
+
+    q_word: Selector,
+    q_bytes: Selector,
+    q_big_sigma0: Selector,
+    q_big_sigma1: Selector,
+    q_small_sigma0: Selector,
+    q_small_sigma1: Selector,
+    q_ch: Selector,
+    q_maj: Selector,
+    q_add2: Selector,
+    q_add3: Selector,
+    q_add4: Selector,
+}
+
+#[derive(Clone, Debug)]
+pub struct Sha256Chip {
+    config: Sha256Config,
+}
+
+fn query_bits(
+    meta: &mut VirtualCells<'_, Fr>,
+    bits: &[Column<Advice>; 32],
+    rotation: Rotation,
+) -> Vec<Expression<Fr>> {
+    bits.iter().map(|bit| meta.query_advice(*bit, rotation)).collect()
+}
+
+/// Little-endian composition of bits
+fn compose(bits: &[Expression<Fr>]) -> Expression<Fr> {
+    bits.iter().rev().fold(Expression::Constant(Fr::zero()), |acc, bit| {
+        acc * Expression::Constant(Fr::from(2)) + bit.clone()
+    })
+}
+
+fn xor3(x: Expression<Fr>, y: Expression<Fr>, z: Expression<Fr>) -> Expression<Fr> {
+    let two = Expression::Constant(Fr::from(2));
+    let four = Expression::Constant(Fr::from(4));
+    x.clone() + y.clone() + z.clone()
+        - two * (x.clone() * y.clone() + y.clone() * z.clone() + x.clone() * z.clone())
+        + four * x * y * z
+}
+
+impl Sha256Chip {
+    pub fn new(config: Sha256Config) -> Self {
+        Sha256Chip { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Sha256Config {
+        let bits = [(); 32].map(|_| meta.advice_column());
+        let word = meta.advice_column();
+        let carry = [(); 3].map(|_| meta.advice_column());
+        let bytes = [(); 4].map(|_| meta.advice_column());
+        let round_constant = meta.fixed_column();
+        let constants = meta.fixed_column();
+
+        meta.enable_equality(word);
+        for byte in bytes {
+            meta.enable_equality(byte);
+        }
+        // padding bytes and initial hash values are fixed
+        meta.enable_constant(constants);
+
+        let q_word = meta.selector();
+        let q_bytes = meta.selector();
+        let q_big_sigma0 = meta.selector();
+        let q_big_sigma1 = meta.selector();
+        let q_small_sigma0 = meta.selector();
+        let q_small_sigma1 = meta.selector();
+        let q_ch = meta.selector();
+        let q_maj = meta.selector();
+        let q_add2 = meta.selector();
+        let q_add3 = meta.selector();
+        let q_add4 = meta.selector();
+
+        let one = Expression::Constant(Fr::one());
+
+        meta.create_gate("sha256 word", |meta| {
+            let q = meta.query_selector(q_word);
+            let word = meta.query_advice(word, Rotation::cur());
+            let bits = query_bits(meta, &bits, Rotation::cur());
+
+            let mut constraints: Vec<Expression<Fr>> = bits
+                .iter()
+                .map(|bit| q.clone() * bit.clone() * (one.clone() - bit.clone()))
+                .collect();
+            constraints.push(q * (compose(&bits) - word));
+            constraints
+        });
+
+        // bytes are big-endian within the word
+        meta.create_gate("sha256 bytes", |meta| {
+            let q = meta.query_selector(q_bytes);
+            let bits = query_bits(meta, &bits, Rotation::cur());
+
+            bytes
+                .iter()
+                .enumerate()
+                .map(|(j, byte)| {
+                    let byte = meta.query_advice(*byte, Rotation::cur());
+                    let low = 8 * (3 - j);
+                    q.clone() * (compose(&bits[low..low + 8]) - byte)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for (name, q_sigma, shifts) in [
+            ("sha256 big sigma0", q_big_sigma0, BIG_SIGMA0),
+            ("sha256 big sigma1", q_big_sigma1, BIG_SIGMA1),
+            ("sha256 small sigma0", q_small_sigma0, SMALL_SIGMA0),
+            ("sha256 small sigma1", q_small_sigma1, SMALL_SIGMA1),
+        ] {
+            meta.create_gate(name, |meta| {
+                let q = meta.query_selector(q_sigma);
+                let x = query_bits(meta, &bits, Rotation::cur());
+                let out = query_bits(meta, &bits, Rotation::next());
+
+                (0..32)
+                    .map(|i| {
+                        let [s0, s1, s2] = shifts.map(|shift| shift.bit(&x, i));
+                        q.clone() * (out[i].clone() - xor3(s0, s1, s2))
+                    })
+                    .collect::<Vec<_>>()
+            });
+        }
+
+        // ch(e, f, g) = (e & f) ^ (!e & g)
+        meta.create_gate("sha256 ch", |meta| {
+            let q = meta.query_selector(q_ch);
+            let e = query_bits(meta, &bits, Rotation::cur());
+            let f = query_bits(meta, &bits, Rotation::next());
+            let g = query_bits(meta, &bits, Rotation(2));
+            let out = query_bits(meta, &bits, Rotation(3));
+
+            (0..32)
+                .map(|i| {
+                    let ch =
+                        e[i].clone() * f[i].clone() + (one.clone() - e[i].clone()) * g[i].clone();
+                    q.clone() * (out[i].clone() - ch)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        // maj(a, b, c) = (a & b) ^ (a & c) ^ (b & c)
+        meta.create_gate("sha256 maj", |meta| {
+            let q = meta.query_selector(q_maj);
+            let a = query_bits(meta, &bits, Rotation::cur());
+            let b = query_bits(meta, &bits, Rotation::next());
+            let c = query_bits(meta, &bits, Rotation(2));
+            let out = query_bits(meta, &bits, Rotation(3));
+
+            (0..32)
+                .map(|i| {
+                    let (a, b, c) = (a[i].clone(), b[i].clone(), c[i].clone());
+                    let maj = a.clone() * b.clone() + a.clone() * c.clone() + b.clone() * c.clone()
+                        - Expression::Constant(Fr::from(2)) * a * b * c;
+                    q.clone() * (out[i].clone() - maj)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        // the terms of an addition sit on the rows right above its result
+        for (q_add, arity) in [(q_add2, 2), (q_add3, 3), (q_add4, 4)] {
+            meta.create_gate("sha256 add", |meta| {
+                let q = meta.query_selector(q_add);
+                let sum = (1..=arity)
+                    .fold(meta.query_fixed(round_constant, Rotation::cur()), |acc, j| {
+                        acc + meta.query_advice(word, Rotation(-j))
+                    });
+                let result = meta.query_advice(word, Rotation::cur());
+                let carry: Vec<_> =
+                    carry.iter().map(|bit| meta.query_advice(*bit, Rotation::cur())).collect();
+
+                let mut constraints: Vec<Expression<Fr>> = carry
+                    .iter()
+                    .map(|bit| q.clone() * bit.clone() * (one.clone() - bit.clone()))
+                    .collect();
+                constraints.push(
+                    q * (sum
+                        - result
+                        - compose(&carry) * Expression::Constant(Fr::from(1u64 << 32))),
+                );
+                constraints
+            });
+        }
+
+        Sha256Config {
+            bits,
+            word,
+            carry,
+            bytes,
+            round_constant,
+            q_word,
+            q_bytes,
+            q_big_sigma0,
+            q_big_sigma1,
+            q_small_sigma0,
+            q_small_sigma1,
+            q_ch,
+            q_maj,
+            q_add2,
+            q_add3,
+            q_add4,
+        }
+    }
+
+    /// Assign the SHA256 digest of `message`, returning the cells holding the
+    /// message bytes and the 32 digest bytes
+    pub(crate) fn digest(
+        &self,
+        region: &mut Region<'_, Fr>,
+        message: &[u8],
+    ) -> Result<AssignedSha256, Error> {
+        let mut offset = 0;
+        let padded = pad(message);
+
+        let mut state = Vec::with_capacity(8);
+        for iv in IV {
+            let word = self.assign_word(region, &mut offset, iv)?;
+            region.constrain_constant(word.cell.cell(), Fr::from(iv as u64))?;
+            state.push(word);
+        }
+
+        let mut message_cells = Vec::with_capacity(message.len());
+        for (block_index, block) in padded.chunks(64).enumerate() {
+            // message schedule
+            let mut schedule = Vec::with_capacity(64);
+            for (i, word_bytes) in block.chunks(4).enumerate() {
+                let (word, byte_cells) = self.assign_bytes_word(region, &mut offset, word_bytes)?;
+                for (j, cell) in byte_cells.into_iter().enumerate() {
+                    if block_index * 64 + i * 4 + j < message.len() {
+                        message_cells.push(cell);
+                    } else {
+                        region.constrain_constant(cell.cell(), Fr::from(word_bytes[j] as u64))?;
+                    }
+                }
+                schedule.push(word);
+            }
+            for t in 16..64 {
+                let s0 = self.sigma(
+                    region,
+                    &mut offset,
+                    self.config.q_small_sigma0,
+                    &SMALL_SIGMA0,
+                    &schedule[t - 15],
+                )?;
+                let s1 = self.sigma(
+                    region,
+                    &mut offset,
+                    self.config.q_small_sigma1,
+                    &SMALL_SIGMA1,
+                    &schedule[t - 2],
+                )?;
+                let word = self.add(
+                    region,
+                    &mut offset,
+                    &[&s1, &schedule[t - 7], &s0, &schedule[t - 16]],
+                    0,
+                )?;
+                schedule.push(word);
+            }
+
+            // compression
+            let mut vars = state.clone();
+            for (t, round_constant) in ROUND_CONSTANTS.iter().enumerate() {
+                let ch = self.ch(region, &mut offset, &vars[4], &vars[5], &vars[6])?;
+                let s1 = self.sigma(
+                    region,
+                    &mut offset,
+                    self.config.q_big_sigma1,
+                    &BIG_SIGMA1,
+                    &vars[4],
+                )?;
+                let maj = self.maj(region, &mut offset, &vars[0], &vars[1], &vars[2])?;
+                let s0 = self.sigma(
+                    region,
+                    &mut offset,
+                    self.config.q_big_sigma0,
+                    &BIG_SIGMA0,
+                    &vars[0],
+                )?;
+
+                let t1 = self.add(
+                    region,
+                    &mut offset,
+                    &[&vars[7], &s1, &ch, &schedule[t]],
+                    *round_constant,
+                )?;
+                let e = self.add(region, &mut offset, &[&vars[3], &t1], 0)?;
+                let a = self.add(region, &mut offset, &[&t1, &s0, &maj], 0)?;
+
+                vars.pop();
+                vars.insert(0, a);
+                vars[4] = e;
+            }
+
+            let mut next_state = Vec::with_capacity(8);
+            for (h, var) in state.iter().zip(vars.iter()) {
+                next_state.push(self.add(region, &mut offset, &[h, var], 0)?);
+            }
+            state = next_state;
+        }
+
+        let mut digest_cells = Vec::with_capacity(32);
+        for word in state.iter() {
+            digest_cells.extend(self.word_to_bytes(region, &mut offset, word)?);
+        }
+
+        Ok((message_cells, digest_cells))
+    }
+
+    /// Assign a word along with its bits
+    fn assign_word(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        value: u32,
+    ) -> Result<Word, Error> {
+        self.config.q_word.enable(region, *offset)?;
+        for (i, bit) in self.config.bits.iter().enumerate() {
+            region.assign_advice(
+                || "bit",
+                *bit,
+                *offset,
+                || Value::known(Fr::from(((value >> i) & 1) as u64)),
+            )?;
+        }
+        let cell = region.assign_advice(
+            || "word",
+            self.config.word,
+            *offset,
+            || Value::known(Fr::from(value as u64)),
+        )?;
+        *offset += 1;
+
+        Ok(Word { cell, value })
+    }
+
+    /// Copy a word into a new row along with its bits
+    fn copy_word(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        word: &Word,
+    ) -> Result<Word, Error> {
+        let copy = self.assign_word(region, offset, word.value)?;
+        region.constrain_equal(copy.cell.cell(), word.cell.cell())?;
+        Ok(copy)
+    }
+
+    /// Assign the big-endian bytes of the word at `offset`
+    fn assign_bytes(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: usize,
+        value: u32,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        self.config.q_bytes.enable(region, offset)?;
+        value
+            .to_be_bytes()
+            .iter()
+            .zip(self.config.bytes.iter())
+            .map(|(byte, column)| {
+                region.assign_advice(
+                    || "byte",
+                    *column,
+                    offset,
+                    || Value::known(Fr::from(*byte as u64)),
+                )
+            })
+            .collect()
+    }
+
+    /// Assign a message word from its big-endian bytes
+    fn assign_bytes_word(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        bytes: &[u8],
+    ) -> Result<(Word, Vec<AssignedCell<Fr, Fr>>), Error> {
+        let value = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let byte_cells = self.assign_bytes(region, *offset, value)?;
+        let word = self.assign_word(region, offset, value)?;
+        Ok((word, byte_cells))
+    }
+
+    /// Decompose a word into its big-endian bytes
+    fn word_to_bytes(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        word: &Word,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        let byte_cells = self.assign_bytes(region, *offset, word.value)?;
+        self.copy_word(region, offset, word)?;
+        Ok(byte_cells)
+    }
+
+    fn sigma(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        selector: Selector,
+        shifts: &[Shift; 3],
+        x: &Word,
+    ) -> Result<Word, Error> {
+        selector.enable(region, *offset)?;
+        self.copy_word(region, offset, x)?;
+        self.assign_word(region, offset, sigma(shifts, x.value))
+    }
+
+    fn ch(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        e: &Word,
+        f: &Word,
+        g: &Word,
+    ) -> Result<Word, Error> {
+        self.config.q_ch.enable(region, *offset)?;
+        for word in [e, f, g] {
+            self.copy_word(region, offset, word)?;
+        }
+        self.assign_word(region, offset, (e.value & f.value) ^ (!e.value & g.value))
+    }
+
+    fn maj(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        a: &Word,
+        b: &Word,
+        c: &Word,
+    ) -> Result<Word, Error> {
+        self.config.q_maj.enable(region, *offset)?;
+        for word in [a, b, c] {
+            self.copy_word(region, offset, word)?;
+        }
+        self.assign_word(
+            region,
+            offset,
+            (a.value & b.value) ^ (a.value & c.value) ^ (b.value & c.value),
+        )
+    }
+
+    /// Add `terms` and a constant modulo 2^32
+    fn add(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        terms: &[&Word],
+        constant: u32,
+    ) -> Result<Word, Error> {
+        let selector = match terms.len() {
+            2 => self.config.q_add2,
+            3 => self.config.q_add3,
+            4 => self.config.q_add4,
+            _ => unreachable!("sha256 additions have between 2 and 4 terms"),
+        };
+        for term in terms {
+            term.cell.copy_advice(|| "term", region, self.config.word, *offset)?;
+            *offset += 1;
+        }
+
+        let sum = terms.iter().map(|term| term.value as u64).sum::<u64>() + constant as u64;
+        let carry = sum >> 32;
+
+        selector.enable(region, *offset)?;
+        region.assign_fixed(
+            || "round constant",
+            self.config.round_constant,
+            *offset,
+            || Value::known(Fr::from(constant as u64)),
+        )?;
+        for (i, bit) in self.config.carry.iter().enumerate() {
+            region.assign_advice(
+                || "carry",
+                *bit,
+                *offset,
+                || Value::known(Fr::from((carry >> i) & 1)),
+            )?;
+        }
+        self.assign_word(region, offset, sum as u32)
+    }
+}
+
+/// Pad a message to a multiple of 64 bytes
+fn pad(message: &[u8]) -> Vec<u8> {
+    let bit_len = (message.len() as u64) * 8;
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend(bit_len.to_be_bytes());
+    padded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sha256Chip, Sha256Config};
+    use pse_halo2wrong::halo2::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+
+    #[derive(Clone, Default)]
+    struct Sha256TestCircuit {
+        message: Vec<u8>,
+        digest: Vec<u8>,
+    }
+
+    impl Circuit<Fr> for Sha256TestCircuit {
+        type Config = Sha256Config;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Sha256Config {
+            Sha256Chip::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let sha256_chip = Sha256Chip::new(config);
+            layouter.assign_region(
+                || "sha256",
+                |mut region| {
+                    let (_, digest_cells) = sha256_chip.digest(&mut region, &self.message)?;
+                    // constrain the digest to the expected test vector
+                    for (cell, byte) in digest_cells.iter().zip(self.digest.iter()) {
+                        region.constrain_constant(cell.cell(), Fr::from(*byte as u64))?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn run_test_vector(message: &[u8], digest: &str) -> Result<(), Vec<String>> {
+        let circuit =
+            Sha256TestCircuit { message: message.to_vec(), digest: hex::decode(digest).unwrap() };
+        let prover = MockProver::run(13, &circuit, vec![]).unwrap();
+        prover.verify().map_err(|failures| failures.iter().map(|f| f.to_string()).collect())
+    }
+
+    #[test]
+    fn test_sha256_empty_message() {
+        assert_eq!(
+            run_test_vector(
+                b"",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_sha256_abc() {
+        assert_eq!(
+            run_test_vector(
+                b"abc",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_sha256_two_blocks() {
+        assert_eq!(
+            run_test_vector(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_sha256_wrong_digest() {
+        assert!(run_test_vector(
+            b"abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ae"
+        )
+        .is_err());
+    }
+}
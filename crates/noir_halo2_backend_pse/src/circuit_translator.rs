@@ -1,17 +1,43 @@
 use super::halo2_plonk_api::OpcodeFlags;
-use crate::{assigned_map::AssignedMap, halo2_plonk_api::PlonkConfig};
-use acvm::acir::{
-    circuit::{opcodes::BlackBoxFuncCall, Circuit as NoirCircuit, Opcode},
-    native_types::WitnessMap,
+use crate::{
+    acvm_interop::pwg::halo2_field_to_noir_field,
+    assigned_map::AssignedMap,
+    chips::{
+        aggregation::{aggregate_native, proof_from_fields, protocol_from_fields, AggregationChip},
+        bitwise::BitwiseChip,
+        blake2s::Blake2sChip,
+        ecdsa::EcdsaChip,
+        grumpkin::GrumpkinChip,
+        keccak256::Keccak256Chip,
+        memory::MemoryChip,
+        sha256::Sha256Chip,
+    },
+    halo2_plonk_api::PlonkConfig,
+};
+use acvm::{
+    acir::{
+        circuit::{
+            opcodes::{BlackBoxFuncCall, FunctionInput},
+            Circuit as NoirCircuit, Opcode,
+        },
+        native_types::{Witness, WitnessMap},
+    },
+    FieldElement,
 };
 use core::panic;
+use noir_halo2_backend_common::noir_field_to_halo2_field;
 use pse_halo2wrong::halo2::{
     circuit::SimpleFloorPlanner,
-    halo2curves::bn256::Fr,
-    plonk::{Circuit as Halo2PlonkCircuit, ConstraintSystem},
+    halo2curves::{
+        bn256::{Fr, G1Affine},
+        secp256k1::Secp256k1Affine,
+        secp256r1::Secp256r1Affine,
+    },
+    plonk::{Circuit as Halo2PlonkCircuit, ConstraintSystem, Error},
 };
 use pse_maingate::{RangeChip, RangeInstructions};
-use std::marker::PhantomData;
+use pse_snark_verifier::verifier::plonk::PlonkProtocol;
+use std::{collections::BTreeMap, marker::PhantomData};
 
 /// Concrete Halo2 Circuit
 #[derive(Clone, Default, Debug)]
@@ -57,106 +83,210 @@ impl Halo2PlonkCircuit<Fr> for NoirHalo2Translator<Fr> {
         &self,
         config: Self::Config,
         mut layouter: impl pse_halo2wrong::halo2::circuit::Layouter<Fr>,
-    ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
+    ) -> Result<(), Error> {
         let mut witness_assignments = AssignedMap::<Fr>::new();
-        let range_chip = RangeChip::<Fr>::new(config.range_config.clone());
+        let range_chip = config.range_config.clone().map(RangeChip::<Fr>::new);
+        let bitwise_chip = config.bitwise_config.clone().map(BitwiseChip::new);
+        let sha256_chip = config.sha256_config.clone().map(Sha256Chip::new);
+        let blake2s_chip = config.blake2s_config.clone().map(Blake2sChip::new);
+        let keccak256_chip = config.keccak256_config.clone().map(Keccak256Chip::new);
+        let ecdsa_secp256k1_chip =
+            config.ecdsa_config.clone().map(EcdsaChip::<Secp256k1Affine>::new);
+        let ecdsa_secp256r1_chip =
+            config.ecdsa_config.clone().map(EcdsaChip::<Secp256r1Affine>::new);
+        let grumpkin_chip = config.grumpkin_config.clone().map(GrumpkinChip::new);
+        let memory_chip = config.memory_config.clone().map(MemoryChip::new);
+        let aggregation_chip = config.aggregation_config.clone().map(AggregationChip::new);
+        // runs of opcodes assigned row by row share a single region
+        let mut run = Vec::new();
         for gate in self.circuit.opcodes.iter() {
+            if !is_packed(gate) && !run.is_empty() {
+                self.add_packed_constrains(
+                    &run,
+                    &config,
+                    range_chip.as_ref(),
+                    bitwise_chip.as_ref(),
+                    &mut layouter,
+                    &mut witness_assignments,
+                )?;
+                run.clear();
+            }
             match gate {
-                Opcode::Arithmetic(expression) => {
-                    self.add_arithmetic_constrains(
-                        expression,
-                        &config,
-                        &mut layouter,
-                        &mut witness_assignments,
-                    )?;
-                }
+                Opcode::Arithmetic(_) => run.push(gate),
                 Opcode::BlackBoxFuncCall(gadget_call) => {
                     match gadget_call {
-                        BlackBoxFuncCall::RANGE { input } => self.add_range_constrain(
-                            input.witness,
-                            input.num_bits,
-                            &range_chip,
+                        BlackBoxFuncCall::RANGE { .. }
+                        | BlackBoxFuncCall::AND { .. }
+                        | BlackBoxFuncCall::XOR { .. } => run.push(gate),
+                        BlackBoxFuncCall::SHA256 { inputs, outputs } => self.add_sha256_constrain(
+                            inputs,
+                            outputs,
+                            sha256_chip.as_ref().ok_or(Error::Synthesis)?,
+                            &mut layouter,
+                            &mut witness_assignments,
+                        )?,
+                        BlackBoxFuncCall::Blake2s { inputs, outputs } => self
+                            .add_blake2s_constrain(
+                                inputs,
+                                outputs,
+                                blake2s_chip.as_ref().ok_or(Error::Synthesis)?,
+                                &mut layouter,
+                                &mut witness_assignments,
+                            )?,
+                        BlackBoxFuncCall::SchnorrVerify {
+                            public_key_x,
+                            public_key_y,
+                            signature,
+                            message,
+                            output,
+                        } => self.add_schnorr_verify_constrain(
+                            public_key_x,
+                            public_key_y,
+                            signature,
+                            message,
+                            *output,
+                            grumpkin_chip.as_ref().ok_or(Error::Synthesis)?,
+                            blake2s_chip.as_ref().ok_or(Error::Synthesis)?,
+                            &config,
                             &mut layouter,
                             &mut witness_assignments,
                         )?,
-                        BlackBoxFuncCall::AND { lhs, rhs, output }
-                        | BlackBoxFuncCall::XOR { lhs, rhs, output } => {
-                            assert_eq!(lhs.num_bits, rhs.num_bits);
-
-                            match gadget_call {
-                                BlackBoxFuncCall::AND { .. } => self.add_and_constrain(
-                                    lhs.witness,
-                                    rhs.witness,
-                                    *output,
-                                    &config,
-                                    &mut layouter,
-                                    &mut witness_assignments,
-                                )?,
-                                BlackBoxFuncCall::XOR { .. } => {
-                                    panic!("xor has not yet been implemented")
-                                }
-                                _ => unreachable!("expected either an AND or XOR opcode"),
-                            }
-                        }
-                        BlackBoxFuncCall::SHA256 { .. } => {
-                            panic!("sha256 has not yet been implemented")
-                        }
-                        BlackBoxFuncCall::Blake2s { .. } => {
-                            panic!("blake2s has not yet been implemented")
-                        }
-                        BlackBoxFuncCall::SchnorrVerify { .. } => {
-                            panic!("schnorrverify has not yet been implemented")
-                        }
-                        BlackBoxFuncCall::Pedersen { .. } => {
-                            panic!("pedersen has not yet been implemented")
-                        }
-                        BlackBoxFuncCall::HashToField128Security { .. } => {
-                            panic!("hash to field has not yet been implemented")
-                        }
+                        BlackBoxFuncCall::Pedersen { inputs, domain_separator, outputs } => self
+                            .add_pedersen_constrain(
+                                inputs,
+                                *domain_separator,
+                                *outputs,
+                                grumpkin_chip.as_ref().ok_or(Error::Synthesis)?,
+                                &mut layouter,
+                                &mut witness_assignments,
+                            )?,
+                        BlackBoxFuncCall::HashToField128Security { inputs, output } => self
+                            .add_hash_to_field_constrain(
+                                inputs,
+                                *output,
+                                blake2s_chip.as_ref().ok_or(Error::Synthesis)?,
+                                &config,
+                                &mut layouter,
+                                &mut witness_assignments,
+                            )?,
                         BlackBoxFuncCall::EcdsaSecp256k1 {
-                            public_key_x: _public_key_x_inputs,
-                            public_key_y: _public_key_y_inputs,
-                            signature: _signature_inputs,
-                            hashed_message: _hashed_message_inputs,
-                            output: _,
-                        } => {
-                            panic!("secp256k1 has not yet been implemented")
-                        }
-                        BlackBoxFuncCall::EcdsaSecp256r1 { .. } => {
-                            panic!("secp256r1 has not yet been implemented")
-                        }
-                        BlackBoxFuncCall::FixedBaseScalarMul { .. } => {
-                            todo!()
-                        }
-                        BlackBoxFuncCall::Keccak256 { inputs: _, outputs: _ } => {
-                            panic!("keccak256 has not yet been implemented")
-                        }
+                            public_key_x,
+                            public_key_y,
+                            signature,
+                            hashed_message,
+                            output,
+                        } => self.add_ecdsa_constrain(
+                            public_key_x,
+                            public_key_y,
+                            signature,
+                            hashed_message,
+                            *output,
+                            ecdsa_secp256k1_chip.as_ref().ok_or(Error::Synthesis)?,
+                            &mut layouter,
+                            &mut witness_assignments,
+                        )?,
+                        BlackBoxFuncCall::EcdsaSecp256r1 {
+                            public_key_x,
+                            public_key_y,
+                            signature,
+                            hashed_message,
+                            output,
+                        } => self.add_ecdsa_constrain(
+                            public_key_x,
+                            public_key_y,
+                            signature,
+                            hashed_message,
+                            *output,
+                            ecdsa_secp256r1_chip.as_ref().ok_or(Error::Synthesis)?,
+                            &mut layouter,
+                            &mut witness_assignments,
+                        )?,
+                        BlackBoxFuncCall::FixedBaseScalarMul { input, outputs } => self
+                            .add_fixed_base_scalar_mul_constrain(
+                                input,
+                                *outputs,
+                                grumpkin_chip.as_ref().ok_or(Error::Synthesis)?,
+                                &mut layouter,
+                                &mut witness_assignments,
+                            )?,
+                        BlackBoxFuncCall::Keccak256 { inputs, outputs } => self
+                            .add_keccak256_constrain(
+                                inputs,
+                                None,
+                                outputs,
+                                keccak256_chip.as_ref().ok_or(Error::Synthesis)?,
+                                &mut layouter,
+                                &mut witness_assignments,
+                            )?,
                         BlackBoxFuncCall::Keccak256VariableLength {
-                            inputs: _,
-                            var_message_size: _,
-                            outputs: _,
-                        } => todo!(),
+                            inputs,
+                            var_message_size,
+                            outputs,
+                        } => self.add_keccak256_constrain(
+                            inputs,
+                            Some(var_message_size),
+                            outputs,
+                            keccak256_chip.as_ref().ok_or(Error::Synthesis)?,
+                            &mut layouter,
+                            &mut witness_assignments,
+                        )?,
                         BlackBoxFuncCall::RecursiveAggregation {
-                            verification_key: _,
-                            proof: _,
-                            public_inputs: _,
-                            key_hash: _,
-                            input_aggregation_object: _,
-                            output_aggregation_object: _,
-                        } => todo!(),
+                            verification_key,
+                            proof,
+                            public_inputs,
+                            key_hash,
+                            input_aggregation_object,
+                            output_aggregation_object,
+                        } => self.add_recursive_aggregation_constrain(
+                            verification_key,
+                            proof,
+                            public_inputs,
+                            key_hash,
+                            input_aggregation_object.as_deref(),
+                            output_aggregation_object,
+                            aggregation_chip.as_ref().ok_or(Error::Synthesis)?,
+                            &mut layouter,
+                            &mut witness_assignments,
+                        )?,
                     };
                 }
                 Opcode::Directive(_) | Opcode::Brillig(_) => {
                     // Directives/ Brillig are only needed by the pwg
                 }
-                // memory managed by acvm
-                Opcode::Block(_) | Opcode::ROM(_) | Opcode::RAM(_) => {
-                    todo!()
-                }
+                Opcode::ROM(block) => self.add_rom_constrain(
+                    block,
+                    memory_chip.as_ref().ok_or(Error::Synthesis)?,
+                    &config,
+                    &mut layouter,
+                    &mut witness_assignments,
+                )?,
+                // blocks are read and written like a ram
+                Opcode::Block(block) | Opcode::RAM(block) => self.add_ram_constrain(
+                    block,
+                    memory_chip.as_ref().ok_or(Error::Synthesis)?,
+                    &config,
+                    &mut layouter,
+                    &mut witness_assignments,
+                )?,
             }
         }
+        if !run.is_empty() {
+            self.add_packed_constrains(
+                &run,
+                &config,
+                range_chip.as_ref(),
+                bitwise_chip.as_ref(),
+                &mut layouter,
+                &mut witness_assignments,
+            )?;
+        }
 
-        range_chip.load_table(&mut layouter)?;
+        if let Some(range_chip) = &range_chip {
+            range_chip.load_table(&mut layouter)?;
+        }
+        if let Some(bitwise_chip) = &bitwise_chip {
+            bitwise_chip.load_table(&mut layouter)?;
+        }
 
         // synthesize public io
         self.expose_public(&config, &mut layouter, &witness_assignments)?;
@@ -164,3 +294,106 @@ impl Halo2PlonkCircuit<Fr> for NoirHalo2Translator<Fr> {
         Ok(())
     }
 }
+
+impl NoirHalo2Translator<Fr> {
+    /// Values of the witnesses pinned to a constant by an arithmetic opcode
+    /// `q * w + c = 0`, which are known before solving the circuit
+    pub(crate) fn pinned_constants(&self) -> BTreeMap<Witness, FieldElement> {
+        self.circuit
+            .opcodes
+            .iter()
+            .filter_map(|opcode| match opcode {
+                Opcode::Arithmetic(expression)
+                    if expression.mul_terms.is_empty()
+                        && expression.linear_combinations.len() == 1 =>
+                {
+                    let (q, witness) = expression.linear_combinations[0];
+                    (!q.is_zero()).then(|| (witness, -expression.q_c / q))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Protocol of the proofs verified by a recursive aggregation. The circuit
+    /// depends on it, so the verification key must be a constant of the program.
+    pub(crate) fn aggregation_protocol(
+        &self,
+        verification_key: &[FunctionInput],
+        num_instance: usize,
+        constants: &BTreeMap<Witness, FieldElement>,
+    ) -> Result<PlonkProtocol<G1Affine>, Error> {
+        let fields = verification_key
+            .iter()
+            .map(|input| {
+                constants.get(&input.witness).map(|value| noir_field_to_halo2_field(*value))
+            })
+            .collect::<Option<Vec<Fr>>>()
+            .ok_or(Error::Synthesis)?;
+        protocol_from_fields(&fields, num_instance).ok_or(Error::Synthesis)
+    }
+
+    /// Solve the output aggregation objects of the recursive aggregations,
+    /// which the pwg leaves zeroed for the backend to compute
+    pub(crate) fn solve_aggregation_objects(&mut self) -> Result<(), Error> {
+        let constants = self.pinned_constants();
+        for opcode in &self.circuit.opcodes {
+            if let Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RecursiveAggregation {
+                verification_key,
+                proof,
+                public_inputs,
+                input_aggregation_object,
+                output_aggregation_object,
+                ..
+            }) = opcode
+            {
+                let protocol =
+                    self.aggregation_protocol(verification_key, public_inputs.len(), &constants)?;
+                let values = |inputs: &[FunctionInput]| -> Vec<Fr> {
+                    inputs
+                        .iter()
+                        .map(|input| {
+                            noir_field_to_halo2_field(
+                                *self
+                                    .witness_values
+                                    .get(&input.witness)
+                                    .unwrap_or(&FieldElement::zero()),
+                            )
+                        })
+                        .collect()
+                };
+                let proof = proof_from_fields(&values(proof), public_inputs.len())
+                    .ok_or(Error::Synthesis)?;
+                let input_aggregation_object = input_aggregation_object.as_deref().map(values);
+                let output = aggregate_native(
+                    &protocol,
+                    &values(public_inputs),
+                    &proof,
+                    input_aggregation_object.as_deref(),
+                )
+                .ok_or(Error::Synthesis)?;
+
+                for (witness, limb) in output_aggregation_object.iter().zip(output) {
+                    self.witness_values.insert(*witness, halo2_field_to_noir_field(limb));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether an opcode is assigned on consecutive rows of a region shared with
+/// the neighbouring packed opcodes
+fn is_packed(opcode: &Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::Arithmetic(_)
+            | Opcode::BlackBoxFuncCall(
+                BlackBoxFuncCall::RANGE { .. }
+                    | BlackBoxFuncCall::AND { .. }
+                    | BlackBoxFuncCall::XOR { .. }
+            )
+    )
+}
+
+noir_field_to_halo2_field!(Fr);
@@ -1,113 +1,513 @@
 use super::halo2_plonk_api::{NoirConstraint, PlonkConfig};
-use crate::{assigned_map::AssignedMap, circuit_translator::NoirHalo2Translator};
+use crate::{
+    assigned_map::AssignedMap,
+    chips::{
+        aggregation::{proof_from_fields, AggregationChip},
+        bitwise::{BitwiseChip, BitwiseOp},
+        blake2s::Blake2sChip,
+        ecdsa::EcdsaChip,
+        grumpkin::{schnorr_challenge_message, schnorr_nonce, GrumpkinChip, GrumpkinPoint},
+        keccak256::Keccak256Chip,
+        memory::{MemoryAccess, MemoryChip},
+        sha256::Sha256Chip,
+    },
+    circuit_translator::NoirHalo2Translator,
+};
 use acvm::{
-    acir::native_types::{Expression, Witness},
+    acir::{
+        circuit::{
+            opcodes::{BlackBoxFuncCall, FunctionInput, MemOp, MemoryBlock},
+            Opcode,
+        },
+        native_types::{Expression, Witness},
+    },
     FieldElement,
 };
 use noir_halo2_backend_common::noir_field_to_halo2_field;
 use pse_halo2wrong::{
     halo2::{
-        circuit::{Layouter, Value},
-        halo2curves::bn256::Fr,
+        circuit::{AssignedCell, Layouter, Value},
+        halo2curves::{bn256::Fr, group::ff::PrimeField, CurveAffine},
     },
     RegionCtx,
 };
 use pse_maingate::{
-    CombinationOption, MainGate, MainGateColumn, MainGateInstructions, RangeChip,
-    RangeInstructions, Term,
+    CombinationOption, MainGate, MainGateInstructions, RangeChip, RangeInstructions, Term,
 };
 
 impl NoirHalo2Translator<Fr> {
-    pub(crate) fn add_arithmetic_constrains(
+    /// Assign a run of arithmetic, range and bitwise opcodes at increasing
+    /// offsets of a single region. Consecutive arithmetic gates sharing a
+    /// witness read it from the next row rather than copying it.
+    pub(crate) fn add_packed_constrains(
         &self,
-        gate: &Expression,
+        run: &[&Opcode],
         config: &PlonkConfig,
+        range_chip: Option<&RangeChip<Fr>>,
+        bitwise_chip: Option<&BitwiseChip>,
         layouter: &mut impl Layouter<Fr>,
         witness_assignments: &mut AssignedMap<Fr>,
     ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
-        let mut noir_cs = NoirConstraint::default();
-        // check mul gate
-        if !gate.mul_terms.is_empty() {
-            let mul_term = &gate.mul_terms[0];
-            noir_cs.qm = mul_term.0;
+        let mut noir_constraints: Vec<Option<NoirConstraint>> = run
+            .iter()
+            .map(|opcode| match opcode {
+                Opcode::Arithmetic(expression) => Some(NoirConstraint::from(expression)),
+                _ => None,
+            })
+            .collect();
+        let mut pinned = false;
+        for i in 1..noir_constraints.len() {
+            let (previous, next) = noir_constraints.split_at_mut(i);
+            pinned = match (previous[i - 1].as_mut(), next[0].as_mut()) {
+                (Some(noir_cs), Some(next)) => noir_cs.flow_into(next, pinned),
+                _ => false,
+            };
+        }
 
-            // Get wL term
-            let wl = &mul_term.1;
-            noir_cs.a = wl.witness_index() as i32;
+        layouter.assign_region(
+            || "region packed opcodes",
+            |region| {
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+                let main_gate = MainGate::<Fr>::new(config.main_gate_config.clone());
 
-            // Get wR term
-            let wr = &mul_term.2;
-            noir_cs.b = wr.witness_index() as i32;
-        }
+                for (opcode, noir_cs) in run.iter().zip(noir_constraints.iter()) {
+                    match (opcode, noir_cs) {
+                        (_, Some(noir_cs)) => self.add_arithmetic_constrains(
+                            noir_cs,
+                            &main_gate,
+                            ctx,
+                            witness_assignments,
+                        )?,
+                        (Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }), _) => self
+                            .add_range_constrain(
+                                input.witness,
+                                input.num_bits,
+                                range_chip.ok_or(pse_halo2wrong::halo2::plonk::Error::Synthesis)?,
+                                ctx,
+                                witness_assignments,
+                            )?,
+                        (
+                            Opcode::BlackBoxFuncCall(
+                                gadget_call @ (BlackBoxFuncCall::AND { lhs, rhs, output }
+                                | BlackBoxFuncCall::XOR { lhs, rhs, output }),
+                            ),
+                            _,
+                        ) => {
+                            assert_eq!(lhs.num_bits, rhs.num_bits);
 
-        for term in &gate.linear_combinations {
-            noir_cs.set_linear_term(term.0, term.1.witness_index() as i32);
-        }
+                            let op = match gadget_call {
+                                BlackBoxFuncCall::AND { .. } => BitwiseOp::And,
+                                _ => BitwiseOp::Xor,
+                            };
+                            self.add_bitwise_constrain(
+                                op,
+                                lhs.witness,
+                                rhs.witness,
+                                *output,
+                                lhs.num_bits,
+                                bitwise_chip
+                                    .ok_or(pse_halo2wrong::halo2::plonk::Error::Synthesis)?,
+                                ctx,
+                                witness_assignments,
+                            )?
+                        }
+                        _ => unreachable!("expected an arithmetic, range or bitwise opcode"),
+                    }
+                }
 
-        // Add the qc term
-        noir_cs.qc = gate.q_c;
+                Ok(())
+            },
+        )?;
 
-        let a = Value::known(noir_field_to_halo2_field(
-            *self.witness_values.get_index(noir_cs.a as u32).unwrap_or(&FieldElement::zero()),
-        ));
-        let b = Value::known(noir_field_to_halo2_field(
-            *self.witness_values.get_index(noir_cs.b as u32).unwrap_or(&FieldElement::zero()),
-        ));
-        let c = Value::known(noir_field_to_halo2_field(
-            *self.witness_values.get_index(noir_cs.c as u32).unwrap_or(&FieldElement::zero()),
-        ));
+        Ok(())
+    }
+
+    pub(crate) fn add_arithmetic_constrains(
+        &self,
+        noir_cs: &NoirConstraint,
+        main_gate: &MainGate<Fr>,
+        ctx: &mut RegionCtx<'_, Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
+        let value = |witness: i32| {
+            Value::known(noir_field_to_halo2_field(
+                *self.witness_values.get_index(witness as u32).unwrap_or(&FieldElement::zero()),
+            ))
+        };
 
         let qm = noir_field_to_halo2_field(noir_cs.qm);
-        let ql = noir_field_to_halo2_field(noir_cs.ql);
-        let qr = noir_field_to_halo2_field(noir_cs.qr);
-        let qo = noir_field_to_halo2_field(noir_cs.qo);
         let qc = noir_field_to_halo2_field(noir_cs.qc);
+        let qnext = noir_field_to_halo2_field(noir_cs.qnext);
+
+        let witnesses = [noir_cs.a, noir_cs.b, noir_cs.c, noir_cs.d, noir_cs.e];
+        let terms = witnesses
+            .iter()
+            .zip([noir_cs.ql, noir_cs.qr, noir_cs.qo, noir_cs.qd, noir_cs.qe])
+            .map(|(witness, coefficient)| {
+                Term::Unassigned(value(*witness), noir_field_to_halo2_field(coefficient))
+            });
+
+        // all five terms share the row of the gate, so that a witness flowed into
+        // the next row is read from the E cell of the next constraint
+        let cells = main_gate.apply(
+            ctx,
+            terms,
+            qc,
+            CombinationOption::Common(
+                pse_maingate::CombinationOptionCommon::CombineToNextScaleMul(qnext, qm),
+            ),
+        )?;
+
+        // copy the cells of the witnesses assigned by previous gates, storing each
+        // before checking the next so that a witness used twice in a gate is copied too
+        for (witness, cell) in witnesses.into_iter().zip(cells) {
+            witness_assignments.check_and_copy(ctx, witness as u32, &cell)?;
+            witness_assignments.insert(Witness(witness as u32), cell);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn add_range_constrain(
+        &self,
+        witness: Witness,
+        num_bits: u32,
+        range_chip: &RangeChip<Fr>,
+        ctx: &mut RegionCtx<'_, Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
+        let input = noir_field_to_halo2_field(
+            *self.witness_values.get(&witness).unwrap_or(&FieldElement::zero()),
+        );
+
+        let value = Value::known(input);
+        let limb_bit_len = 8;
+        let bit_len = num_bits as usize;
+
+        let cell = range_chip.assign(ctx, value, limb_bit_len, bit_len)?;
+        witness_assignments.check_and_copy(ctx, witness.0, &cell)?;
+
+        // add to assignment map
+        witness_assignments.insert(witness, cell);
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn add_bitwise_constrain(
+        &self,
+        op: BitwiseOp,
+        lhs: Witness,
+        rhs: Witness,
+        output: Witness,
+        num_bits: u32,
+        bitwise_chip: &BitwiseChip,
+        ctx: &mut RegionCtx<'_, Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
+        let lhs_v = noir_field_to_halo2_field(
+            *self.witness_values.get(&lhs).unwrap_or(&FieldElement::zero()),
+        );
+
+        let rhs_v = noir_field_to_halo2_field(
+            *self.witness_values.get(&rhs).unwrap_or(&FieldElement::zero()),
+        );
+
+        let (lhs_cell, rhs_cell, output_cell) =
+            bitwise_chip.assign(ctx, op, lhs_v, rhs_v, num_bits)?;
+        witness_assignments.check_and_copy(ctx, lhs.0, &lhs_cell)?;
+        witness_assignments.check_and_copy(ctx, rhs.0, &rhs_cell)?;
+        witness_assignments.check_and_copy(ctx, output.0, &output_cell)?;
+
+        // add to assignment map
+        witness_assignments.insert(lhs, lhs_cell);
+        witness_assignments.insert(rhs, rhs_cell);
+        witness_assignments.insert(output, output_cell);
+
+        Ok(())
+    }
+
+    pub(crate) fn add_sha256_constrain(
+        &self,
+        inputs: &[FunctionInput],
+        outputs: &[Witness],
+        sha256_chip: &Sha256Chip,
+        layouter: &mut impl Layouter<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
+        // the chip hashes a message of bytes into a 32 byte digest
+        if inputs.iter().any(|input| input.num_bits > 8) || outputs.len() != 32 {
+            return Err(pse_halo2wrong::halo2::plonk::Error::Synthesis);
+        }
+
+        let message: Vec<u8> = inputs
+            .iter()
+            .map(|input| {
+                self.witness_values.get(&input.witness).unwrap_or(&FieldElement::zero()).to_u128()
+                    as u8
+            })
+            .collect();
 
         layouter.assign_region(
-            || "region 0",
+            || "region sha256",
+            |mut region| {
+                let (message_cells, digest_cells) = sha256_chip.digest(&mut region, &message)?;
+
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+                for (input, cell) in inputs.iter().zip(message_cells) {
+                    witness_assignments.check_and_copy(ctx, input.witness.0, &cell)?;
+                    witness_assignments.insert(input.witness, cell);
+                }
+                for (output, cell) in outputs.iter().zip(digest_cells) {
+                    witness_assignments.check_and_copy(ctx, output.0, &cell)?;
+                    witness_assignments.insert(*output, cell);
+                }
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// Constrain a blake2s call, the input bytes being hashed into a 32 byte
+    /// digest
+    pub(crate) fn add_blake2s_constrain(
+        &self,
+        inputs: &[FunctionInput],
+        outputs: &[Witness],
+        blake2s_chip: &Blake2sChip,
+        layouter: &mut impl Layouter<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
+        // the chip hashes a message of bytes into a 32 byte digest
+        if inputs.iter().any(|input| input.num_bits > 8) || outputs.len() != 32 {
+            return Err(pse_halo2wrong::halo2::plonk::Error::Synthesis);
+        }
+
+        let message: Vec<u8> = inputs
+            .iter()
+            .map(|input| {
+                self.witness_values.get(&input.witness).unwrap_or(&FieldElement::zero()).to_u128()
+                    as u8
+            })
+            .collect();
+
+        layouter.assign_region(
+            || "region blake2s",
+            |mut region| {
+                let (message_cells, digest_cells) = blake2s_chip.digest(&mut region, &message)?;
+
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+                for (input, cell) in inputs.iter().zip(message_cells) {
+                    witness_assignments.check_and_copy(ctx, input.witness.0, &cell)?;
+                    witness_assignments.insert(input.witness, cell);
+                }
+                for (output, cell) in outputs.iter().zip(digest_cells) {
+                    witness_assignments.check_and_copy(ctx, output.0, &cell)?;
+                    witness_assignments.insert(*output, cell);
+                }
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub(crate) fn add_hash_to_field_constrain(
+        &self,
+        inputs: &[FunctionInput],
+        output: Witness,
+        blake2s_chip: &Blake2sChip,
+        config: &PlonkConfig,
+        layouter: &mut impl Layouter<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
+        // like the acvm solver, every input contributes its bits rounded up to
+        // bytes in little-endian order to the hashed message
+        let input_bytes: Vec<Vec<u8>> = inputs
+            .iter()
+            .map(|input| {
+                let mut bytes = self
+                    .witness_values
+                    .get(&input.witness)
+                    .unwrap_or(&FieldElement::zero())
+                    .to_be_bytes();
+                bytes.reverse();
+                bytes.truncate(((input.num_bits + 7) / 8) as usize);
+                bytes
+            })
+            .collect();
+        let message = input_bytes.concat();
+
+        let (message_cells, digest_cells) = layouter.assign_region(
+            || "region hash to field digest",
+            |mut region| blake2s_chip.digest(&mut region, &message),
+        )?;
+
+        layouter.assign_region(
+            || "region hash to field",
             |region| {
-                let offset: usize = 0;
+                let offset = 0;
                 let ctx = &mut RegionCtx::new(region, offset);
                 let main_gate = MainGate::<Fr>::new(config.main_gate_config.clone());
+                let range_chip = RangeChip::<Fr>::new(
+                    config
+                        .range_config
+                        .clone()
+                        .ok_or(pse_halo2wrong::halo2::plonk::Error::Synthesis)?,
+                );
+
+                let mut message_cells = message_cells.iter();
+                for (input, bytes) in inputs.iter().zip(input_bytes.iter()) {
+                    let cells: Vec<_> = message_cells.by_ref().take(bytes.len()).cloned().collect();
+                    let cell = match cells.len() {
+                        1 => cells[0].clone(),
+                        32 => compose_field_bytes(&main_gate, &range_chip, ctx, &cells, bytes)?,
+                        _ => compose_bytes(&main_gate, ctx, &cells)?,
+                    };
+                    witness_assignments.check_and_copy(ctx, input.witness.0, &cell)?;
+                    witness_assignments.insert(input.witness, cell);
+                }
+
+                // the digest is read as a big-endian integer reduced into the field
+                let digest_cells: Vec<_> = digest_cells.iter().rev().cloned().collect();
+                let cell = compose_bytes(&main_gate, ctx, &digest_cells)?;
+                witness_assignments.check_and_copy(ctx, output.0, &cell)?;
+                witness_assignments.insert(output, cell);
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// Constrain a keccak256 call, fixed length inputs are hashed in full and
+    /// variable length ones up to `var_message_size`
+    pub(crate) fn add_keccak256_constrain(
+        &self,
+        inputs: &[FunctionInput],
+        var_message_size: Option<&FunctionInput>,
+        outputs: &[Witness],
+        keccak256_chip: &Keccak256Chip,
+        layouter: &mut impl Layouter<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
+        // the chip hashes a message of bytes into a 32 byte digest
+        if inputs.iter().any(|input| input.num_bits > 8) || outputs.len() != 32 {
+            return Err(pse_halo2wrong::halo2::plonk::Error::Synthesis);
+        }
 
-                let mut terms = Vec::new();
+        let message: Vec<u8> = inputs
+            .iter()
+            .map(|input| {
+                self.witness_values.get(&input.witness).unwrap_or(&FieldElement::zero()).to_u128()
+                    as u8
+            })
+            .collect();
+        // an out of range length fails the copy constraint on the length cell
+        let length = match var_message_size {
+            Some(size) => {
+                (self.witness_values.get(&size.witness).unwrap_or(&FieldElement::zero()).to_u128()
+                    as usize)
+                    .min(message.len())
+            }
+            None => message.len(),
+        };
 
-                let a = main_gate.assign_to_column(ctx, a, MainGateColumn::A)?;
-                witness_assignments.check_and_copy(ctx, noir_cs.a as u32, &a)?;
-                terms.push(Term::Assigned(&a, ql));
+        layouter.assign_region(
+            || "region keccak256",
+            |mut region| {
+                let (message_cells, length_cell, digest_cells) =
+                    keccak256_chip.digest(&mut region, &message, length)?;
+                if var_message_size.is_none() {
+                    region
+                        .constrain_constant(length_cell.cell(), Fr::from(message.len() as u64))?;
+                }
 
-                let b = main_gate.assign_to_column(ctx, b, MainGateColumn::B)?;
-                witness_assignments.check_and_copy(ctx, noir_cs.b as u32, &b)?;
-                terms.push(Term::Assigned(&b, qr));
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+                for (input, cell) in inputs.iter().zip(message_cells) {
+                    witness_assignments.check_and_copy(ctx, input.witness.0, &cell)?;
+                    witness_assignments.insert(input.witness, cell);
+                }
+                if let Some(size) = var_message_size {
+                    witness_assignments.check_and_copy(ctx, size.witness.0, &length_cell)?;
+                    witness_assignments.insert(size.witness, length_cell);
+                }
+                for (output, cell) in outputs.iter().zip(digest_cells) {
+                    witness_assignments.check_and_copy(ctx, output.0, &cell)?;
+                    witness_assignments.insert(*output, cell);
+                }
 
-                let c = main_gate.assign_to_column(ctx, c, MainGateColumn::C)?;
-                witness_assignments.check_and_copy(ctx, noir_cs.c as u32, &c)?;
-                terms.push(Term::Assigned(&c, qo));
+                Ok(())
+            },
+        )?;
 
-                let d =
-                    main_gate.assign_to_column(ctx, Value::known(Fr::zero()), MainGateColumn::D)?;
-                terms.push(Term::Assigned(&d, Fr::zero()));
+        Ok(())
+    }
 
-                let e =
-                    main_gate.assign_to_column(ctx, Value::known(Fr::zero()), MainGateColumn::E)?;
-                terms.push(Term::Assigned(&e, Fr::zero()));
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn add_ecdsa_constrain<C: CurveAffine>(
+        &self,
+        public_key_x: &[FunctionInput],
+        public_key_y: &[FunctionInput],
+        signature: &[FunctionInput],
+        hashed_message: &[FunctionInput],
+        output: Witness,
+        ecdsa_chip: &EcdsaChip<C>,
+        layouter: &mut impl Layouter<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
+        // every input is an array of bytes
+        let to_bytes = |inputs: &[FunctionInput]| -> Vec<u8> {
+            inputs
+                .iter()
+                .map(|input| {
+                    self.witness_values
+                        .get(&input.witness)
+                        .unwrap_or(&FieldElement::zero())
+                        .to_u128() as u8
+                })
+                .collect()
+        };
+        let public_key_x_bytes = to_bytes(public_key_x);
+        let public_key_y_bytes = to_bytes(public_key_y);
+        let signature_bytes = to_bytes(signature);
+        let hashed_message_bytes = to_bytes(hashed_message);
 
-                main_gate.apply(
+        layouter.assign_region(
+            || "region ecdsa",
+            |region| {
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+
+                let assigned = ecdsa_chip.verify(
                     ctx,
-                    terms,
-                    qc,
-                    CombinationOption::Common(
-                        pse_maingate::CombinationOptionCommon::CombineToNextScaleMul(
-                            Fr::zero(),
-                            qm,
-                        ),
-                    ),
+                    &public_key_x_bytes,
+                    &public_key_y_bytes,
+                    &signature_bytes,
+                    &hashed_message_bytes,
                 )?;
 
-                // store assignments to a, b, c
-                witness_assignments.insert(Witness(noir_cs.a as u32), a);
-                witness_assignments.insert(Witness(noir_cs.b as u32), b);
-                witness_assignments.insert(Witness(noir_cs.c as u32), c);
+                for (inputs, cells) in [
+                    (public_key_x, assigned.public_key_x),
+                    (public_key_y, assigned.public_key_y),
+                    (signature, assigned.signature),
+                    (hashed_message, assigned.hashed_message),
+                ] {
+                    for (input, cell) in inputs.iter().zip(cells) {
+                        witness_assignments.check_and_copy(ctx, input.witness.0, &cell)?;
+                        witness_assignments.insert(input.witness, cell);
+                    }
+                }
+                witness_assignments.check_and_copy(ctx, output.0, &assigned.result)?;
+                witness_assignments.insert(output, assigned.result);
 
                 Ok(())
             },
@@ -116,33 +516,237 @@ impl NoirHalo2Translator<Fr> {
         Ok(())
     }
 
-    pub(crate) fn add_range_constrain(
+    pub(crate) fn add_fixed_base_scalar_mul_constrain(
         &self,
-        witness: Witness,
-        num_bits: u32,
-        range_chip: &RangeChip<Fr>,
+        input: &FunctionInput,
+        outputs: (Witness, Witness),
+        grumpkin_chip: &GrumpkinChip,
         layouter: &mut impl Layouter<Fr>,
         witness_assignments: &mut AssignedMap<Fr>,
     ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
-        let input = noir_field_to_halo2_field(
-            *self.witness_values.get(&witness).unwrap_or(&FieldElement::zero()),
+        let scalar = noir_field_to_halo2_field(
+            *self.witness_values.get(&input.witness).unwrap_or(&FieldElement::zero()),
         );
 
         layouter.assign_region(
-            || "region 1",
+            || "region fixed base scalar mul",
+            |mut region| {
+                let mut offset = 0;
+                let (scalar_cell, product) = grumpkin_chip.mul_fixed(
+                    &mut region,
+                    &mut offset,
+                    scalar,
+                    GrumpkinPoint::generator(),
+                )?;
+
+                let ctx = &mut RegionCtx::new(region, 0);
+                for (witness, cell) in
+                    [(input.witness, scalar_cell), (outputs.0, product.x), (outputs.1, product.y)]
+                {
+                    witness_assignments.check_and_copy(ctx, witness.0, &cell)?;
+                    witness_assignments.insert(witness, cell);
+                }
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// Constrain a pedersen commitment as the sum of the fixed base
+    /// multiplications of every input by its generator
+    pub(crate) fn add_pedersen_constrain(
+        &self,
+        inputs: &[FunctionInput],
+        domain_separator: u32,
+        outputs: (Witness, Witness),
+        grumpkin_chip: &GrumpkinChip,
+        layouter: &mut impl Layouter<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
+        let scalars: Vec<Fr> = inputs
+            .iter()
+            .map(|input| {
+                noir_field_to_halo2_field(
+                    *self.witness_values.get(&input.witness).unwrap_or(&FieldElement::zero()),
+                )
+            })
+            .collect();
+
+        layouter.assign_region(
+            || "region pedersen",
+            |mut region| {
+                let mut offset = 0;
+                let (scalar_cells, commitment) =
+                    grumpkin_chip.pedersen(&mut region, &mut offset, &scalars, domain_separator)?;
+
+                let ctx = &mut RegionCtx::new(region, 0);
+                for (input, cell) in inputs.iter().zip(scalar_cells) {
+                    witness_assignments.check_and_copy(ctx, input.witness.0, &cell)?;
+                    witness_assignments.insert(input.witness, cell);
+                }
+                for (witness, cell) in [(outputs.0, commitment.x), (outputs.1, commitment.y)] {
+                    witness_assignments.check_and_copy(ctx, witness.0, &cell)?;
+                    witness_assignments.insert(witness, cell);
+                }
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// Constrain a Schnorr verification of a signature `s || e` on Grumpkin.
+    ///
+    /// The nonce `s * G + e * public_key` is computed from the bits of both
+    /// halves of the signature, which compose its bytes. The signature is valid
+    /// if the nonce is not infinity and `e` is the Blake2s digest of the
+    /// Pedersen commitment to the nonce and the public key, followed by the
+    /// message.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn add_schnorr_verify_constrain(
+        &self,
+        public_key_x: &FunctionInput,
+        public_key_y: &FunctionInput,
+        signature: &[FunctionInput],
+        message: &[FunctionInput],
+        output: Witness,
+        grumpkin_chip: &GrumpkinChip,
+        blake2s_chip: &Blake2sChip,
+        config: &PlonkConfig,
+        layouter: &mut impl Layouter<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
+        // the signature is 64 bytes and the message is hashed bytewise
+        if signature.len() != 64 || message.iter().any(|input| input.num_bits > 8) {
+            return Err(pse_halo2wrong::halo2::plonk::Error::Synthesis);
+        }
+
+        let value = |input: &FunctionInput| {
+            *self.witness_values.get(&input.witness).unwrap_or(&FieldElement::zero())
+        };
+        let to_bytes = |inputs: &[FunctionInput]| -> Vec<u8> {
+            inputs.iter().map(|input| value(input).to_u128() as u8).collect()
+        };
+        let public_key = GrumpkinPoint {
+            x: noir_field_to_halo2_field(value(public_key_x)),
+            y: noir_field_to_halo2_field(value(public_key_y)),
+        };
+        let signature_bytes = to_bytes(signature);
+        let message_bytes = to_bytes(message);
+        let nonce = schnorr_nonce(&public_key, &signature_bytes);
+        let challenge_message = schnorr_challenge_message(&public_key, &nonce, &message_bytes);
+
+        let (public_key_cells, bit_cells, nonce_x, commitment_x) = layouter.assign_region(
+            || "region schnorr nonce",
+            |mut region| {
+                let mut offset = 0;
+                let generator = grumpkin_chip.load_constant(
+                    &mut region,
+                    &mut offset,
+                    GrumpkinPoint::generator(),
+                )?;
+                let public_key_cells =
+                    grumpkin_chip.load_point(&mut region, &mut offset, public_key)?;
+                let (bit_cells, nonce_cells) = grumpkin_chip.mul_bits(
+                    &mut region,
+                    &mut offset,
+                    &[
+                        (&signature_bytes[..32], &generator),
+                        (&signature_bytes[32..], &public_key_cells),
+                    ],
+                )?;
+
+                // the nonce x coordinate and the public key are committed to
+                let (scalar_cells, commitment) = grumpkin_chip.pedersen(
+                    &mut region,
+                    &mut offset,
+                    &[nonce.x, public_key.x, public_key.y],
+                    0,
+                )?;
+                for (cell, scalar_cell) in
+                    [&nonce_cells.x, &public_key_cells.x, &public_key_cells.y]
+                        .into_iter()
+                        .zip(scalar_cells)
+                {
+                    region.constrain_equal(cell.cell(), scalar_cell.cell())?;
+                }
+
+                Ok((public_key_cells, bit_cells, nonce_cells.x, commitment.x))
+            },
+        )?;
+
+        let (challenge_cells, digest_cells) = layouter.assign_region(
+            || "region schnorr challenge",
+            |mut region| blake2s_chip.digest(&mut region, &challenge_message),
+        )?;
+
+        layouter.assign_region(
+            || "region schnorr",
             |region| {
                 let offset = 0;
                 let ctx = &mut RegionCtx::new(region, offset);
+                let main_gate = MainGate::<Fr>::new(config.main_gate_config.clone());
+                let range_chip = RangeChip::<Fr>::new(
+                    config
+                        .range_config
+                        .clone()
+                        .ok_or(pse_halo2wrong::halo2::plonk::Error::Synthesis)?,
+                );
+
+                // the bytes of the signature are composed from the boolean bits
+                // of the multiplications, most significant first
+                let signature_cells = bit_cells
+                    .concat()
+                    .chunks(8)
+                    .map(|bits| {
+                        let terms: Vec<Term<Fr>> = bits
+                            .iter()
+                            .enumerate()
+                            .map(|(i, bit)| Term::Assigned(bit, Fr::from(1u64 << (7 - i))))
+                            .collect();
+                        main_gate.compose(ctx, &terms, Fr::zero())
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
 
-                let value = Value::known(input);
-                let limb_bit_len = 8;
-                let bit_len = num_bits as usize;
+                // the challenge message starts with the canonical big-endian bytes
+                // of the commitment x coordinate
+                let commitment_cells: Vec<_> =
+                    challenge_cells[..32].iter().rev().cloned().collect();
+                let commitment_bytes: Vec<u8> =
+                    challenge_message[..32].iter().rev().copied().collect();
+                let composed = compose_field_bytes(
+                    &main_gate,
+                    &range_chip,
+                    ctx,
+                    &commitment_cells,
+                    &commitment_bytes,
+                )?;
+                main_gate.assert_equal(ctx, &composed, &commitment_x)?;
 
-                let cell = range_chip.assign(ctx, value, limb_bit_len, bit_len)?;
-                witness_assignments.check_and_copy(ctx, witness.0, &cell)?;
+                // only infinity has a zero x coordinate
+                let nonce_is_infinity = main_gate.is_zero(ctx, &nonce_x)?;
+                let mut result = main_gate.not(ctx, &nonce_is_infinity)?;
+                for (e, digest) in signature_cells[32..].iter().zip(digest_cells.iter()) {
+                    let byte_equal = main_gate.is_equal(ctx, e, digest)?;
+                    result = main_gate.and(ctx, &result, &byte_equal)?;
+                }
 
-                // add to assignment map
-                witness_assignments.insert(witness, cell);
+                for (input, cell) in [
+                    (public_key_x, public_key_cells.x.clone()),
+                    (public_key_y, public_key_cells.y.clone()),
+                ]
+                .into_iter()
+                .chain(signature.iter().zip(signature_cells))
+                .chain(message.iter().zip(challenge_cells[32..].iter().cloned()))
+                {
+                    witness_assignments.check_and_copy(ctx, input.witness.0, &cell)?;
+                    witness_assignments.insert(input.witness, cell);
+                }
+                witness_assignments.check_and_copy(ctx, output.0, &result)?;
+                witness_assignments.insert(output, result);
 
                 Ok(())
             },
@@ -151,50 +755,301 @@ impl NoirHalo2Translator<Fr> {
         Ok(())
     }
 
-    pub(crate) fn add_and_constrain(
+    pub(crate) fn add_rom_constrain(
         &self,
-        lhs: Witness,
-        rhs: Witness,
-        output: Witness,
+        block: &MemoryBlock,
+        memory_chip: &MemoryChip,
         config: &PlonkConfig,
         layouter: &mut impl Layouter<Fr>,
         witness_assignments: &mut AssignedMap<Fr>,
     ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
-        let lhs_v = Value::known(noir_field_to_halo2_field(
-            *self.witness_values.get(&lhs).unwrap_or(&FieldElement::zero()),
-        ));
+        // the trace starts with the writes of the initial values at indices
+        // 0..len, which must be constant, followed by reads
+        let len = block.len as usize;
+        if block.trace.len() < len {
+            return Err(pse_halo2wrong::halo2::plonk::Error::Synthesis);
+        }
+        for (i, op) in block.trace.iter().enumerate() {
+            let is_init = op.index.is_const() && op.index.q_c == FieldElement::from(i as u128);
+            if memory_op_is_write(op)? != (i < len) || (i < len && !is_init) {
+                return Err(pse_halo2wrong::halo2::plonk::Error::Synthesis);
+            }
+        }
 
-        let rhs_v = Value::known(noir_field_to_halo2_field(
-            *self.witness_values.get(&rhs).unwrap_or(&FieldElement::zero()),
-        ));
+        let (init, reads) = layouter.assign_region(
+            || "region rom accesses",
+            |region| {
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+                let main_gate = MainGate::<Fr>::new(config.main_gate_config.clone());
 
-        let output_v = Value::known(noir_field_to_halo2_field(
-            *self.witness_values.get(&output).unwrap_or(&FieldElement::zero()),
-        ));
+                let mut init = Vec::with_capacity(len);
+                for op in &block.trace[..len] {
+                    init.push(self.assign_expression(
+                        &op.value,
+                        &main_gate,
+                        ctx,
+                        witness_assignments,
+                    )?);
+                }
+                let mut reads = Vec::with_capacity(block.trace.len() - len);
+                for op in &block.trace[len..] {
+                    let index =
+                        self.assign_expression(&op.index, &main_gate, ctx, witness_assignments)?;
+                    let value =
+                        self.assign_expression(&op.value, &main_gate, ctx, witness_assignments)?;
+                    reads.push((index, value));
+                }
+
+                Ok((init, reads))
+            },
+        )?;
+
+        let init_values: Vec<Fr> =
+            init.iter().map(|(_, value)| noir_field_to_halo2_field(*value)).collect();
+        let read_values: Vec<(u64, Fr)> = reads
+            .iter()
+            .map(|((_, index), (_, value))| {
+                (index.to_u128() as u64, noir_field_to_halo2_field(*value))
+            })
+            .collect();
 
         layouter.assign_region(
-            || "region 0",
+            || "region rom",
+            |mut region| {
+                let (init_cells, read_cells) =
+                    memory_chip.rom(&mut region, block.id.0, &init_values, &read_values)?;
+
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+                for ((cell, _), init_cell) in init.iter().zip(init_cells) {
+                    ctx.constrain_equal(cell.cell(), init_cell.cell())?;
+                }
+                for (((index, _), (value, _)), (index_cell, value_cell)) in
+                    reads.iter().zip(read_cells)
+                {
+                    ctx.constrain_equal(index.cell(), index_cell.cell())?;
+                    ctx.constrain_equal(value.cell(), value_cell.cell())?;
+                }
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub(crate) fn add_ram_constrain(
+        &self,
+        block: &MemoryBlock,
+        memory_chip: &MemoryChip,
+        config: &PlonkConfig,
+        layouter: &mut impl Layouter<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
+        let is_write: Vec<bool> =
+            block.trace.iter().map(memory_op_is_write).collect::<Result<_, _>>()?;
+
+        let accesses = layouter.assign_region(
+            || "region ram accesses",
             |region| {
                 let offset = 0;
                 let ctx = &mut RegionCtx::new(region, offset);
                 let main_gate = MainGate::<Fr>::new(config.main_gate_config.clone());
 
-                let lhs_cell = main_gate.assign_to_column(ctx, lhs_v, MainGateColumn::A)?;
-                witness_assignments.check_and_copy(ctx, lhs.0, &lhs_cell)?;
+                let mut accesses = Vec::with_capacity(block.trace.len());
+                for op in &block.trace {
+                    let index =
+                        self.assign_expression(&op.index, &main_gate, ctx, witness_assignments)?;
+                    let value =
+                        self.assign_expression(&op.value, &main_gate, ctx, witness_assignments)?;
+                    accesses.push((index, value));
+                }
+
+                Ok(accesses)
+            },
+        )?;
+
+        let access_values: Vec<MemoryAccess> = accesses
+            .iter()
+            .zip(is_write)
+            .map(|(((_, index), (_, value)), is_write)| MemoryAccess {
+                index: index.to_u128() as u64,
+                value: noir_field_to_halo2_field(*value),
+                is_write,
+            })
+            .collect();
+
+        let diff_cells = layouter.assign_region(
+            || "region ram",
+            |mut region| {
+                let (access_cells, diff_cells) =
+                    memory_chip.ram(&mut region, block.id.0, &access_values)?;
+
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+                for (((index, _), (value, _)), (index_cell, value_cell)) in
+                    accesses.iter().zip(access_cells)
+                {
+                    ctx.constrain_equal(index.cell(), index_cell.cell())?;
+                    ctx.constrain_equal(value.cell(), value_cell.cell())?;
+                }
+
+                Ok(diff_cells)
+            },
+        )?;
+
+        // the sorted accesses are increasing as long as their differences
+        // are small, which leaves no room to wrap around the field
+        layouter.assign_region(
+            || "region ram sorting",
+            |region| {
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+                let range_chip = RangeChip::<Fr>::new(
+                    config
+                        .range_config
+                        .clone()
+                        .ok_or(pse_halo2wrong::halo2::plonk::Error::Synthesis)?,
+                );
+
+                for diff in &diff_cells {
+                    let limb_bit_len = 8;
+                    let bit_len = 32;
+                    let cell =
+                        range_chip.assign(ctx, diff.value().copied(), limb_bit_len, bit_len)?;
+                    ctx.constrain_equal(diff.cell(), cell.cell())?;
+                }
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// Assign a linear expression of witnesses through the main gate, copy
+    /// constraining its witnesses, and return its cell along with its value
+    fn assign_expression(
+        &self,
+        expression: &Expression,
+        main_gate: &MainGate<Fr>,
+        ctx: &mut RegionCtx<'_, Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(AssignedCell<Fr, Fr>, FieldElement), pse_halo2wrong::halo2::plonk::Error> {
+        if !expression.mul_terms.is_empty() {
+            return Err(pse_halo2wrong::halo2::plonk::Error::Synthesis);
+        }
+
+        let mut value = expression.q_c;
+        let mut cells = Vec::with_capacity(expression.linear_combinations.len());
+        for (coefficient, witness) in &expression.linear_combinations {
+            let witness_value = *self.witness_values.get(witness).unwrap_or(&FieldElement::zero());
+            value = value + *coefficient * witness_value;
 
-                let rhs_cell = main_gate.assign_to_column(ctx, rhs_v, MainGateColumn::B)?;
-                witness_assignments.check_and_copy(ctx, rhs.0, &rhs_cell)?;
+            let cell = main_gate
+                .assign_value(ctx, Value::known(noir_field_to_halo2_field(witness_value)))?;
+            witness_assignments.check_and_copy(ctx, witness.0, &cell)?;
+            witness_assignments.insert(*witness, cell.clone());
+            cells.push((cell, noir_field_to_halo2_field(*coefficient)));
+        }
+
+        let cell = match cells.as_slice() {
+            [] => main_gate.assign_constant(ctx, noir_field_to_halo2_field(expression.q_c))?,
+            // a lone witness is the expression itself
+            [(cell, coefficient)] if *coefficient == Fr::one() && expression.q_c.is_zero() => {
+                cell.clone()
+            }
+            _ => {
+                let terms: Vec<Term<Fr>> = cells
+                    .iter()
+                    .map(|(cell, coefficient)| Term::Assigned(cell, *coefficient))
+                    .collect();
+                main_gate.compose(ctx, &terms, noir_field_to_halo2_field(expression.q_c))?
+            }
+        };
+
+        Ok((cell, value))
+    }
+
+    /// Constrain the verification of a proof and the folding of its KZG
+    /// accumulator into the output aggregation object. The proof is advice of
+    /// the prover and isn't constrained, only its verification is.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn add_recursive_aggregation_constrain(
+        &self,
+        verification_key: &[FunctionInput],
+        proof: &[FunctionInput],
+        public_inputs: &[FunctionInput],
+        key_hash: &FunctionInput,
+        input_aggregation_object: Option<&[FunctionInput]>,
+        output_aggregation_object: &[Witness],
+        aggregation_chip: &AggregationChip,
+        layouter: &mut impl Layouter<Fr>,
+        witness_assignments: &mut AssignedMap<Fr>,
+    ) -> Result<(), pse_halo2wrong::halo2::plonk::Error> {
+        let protocol = self.aggregation_protocol(
+            verification_key,
+            public_inputs.len(),
+            &self.pinned_constants(),
+        )?;
 
-                let output_cell = main_gate.assign_to_column(ctx, output_v, MainGateColumn::C)?;
-                witness_assignments.check_and_copy(ctx, output.0, &output_cell)?;
+        // values are unknown without a witness, as when generating keys
+        let value = |input: &FunctionInput| match self.witness_values.get(&input.witness) {
+            Some(value) => Value::known(noir_field_to_halo2_field(*value)),
+            None => Value::unknown(),
+        };
+        let public_input_values: Vec<Value<Fr>> = public_inputs.iter().map(value).collect();
+        let input_aggregation_values: Option<Vec<Value<Fr>>> =
+            input_aggregation_object.map(|inputs| inputs.iter().map(value).collect());
+        let proof_bytes = proof
+            .iter()
+            .map(|input| {
+                self.witness_values
+                    .get(&input.witness)
+                    .map(|value| noir_field_to_halo2_field(*value))
+            })
+            .collect::<Option<Vec<Fr>>>()
+            .and_then(|fields| proof_from_fields(&fields, public_inputs.len()));
+        let proof_bytes = match &proof_bytes {
+            Some(bytes) => Value::known(&bytes[..]),
+            None => Value::unknown(),
+        };
 
-                let result = main_gate.and(ctx, &lhs_cell, &rhs_cell)?;
-                main_gate.assert_equal(ctx, &output_cell, &result)?;
+        layouter.assign_region(
+            || "region recursive aggregation",
+            |region| {
+                let offset = 0;
+                let ctx = RegionCtx::new(region, offset);
+                let (mut ctx, assigned) = aggregation_chip.aggregate(
+                    ctx,
+                    &protocol,
+                    &public_input_values,
+                    proof_bytes,
+                    input_aggregation_values.as_deref(),
+                )?;
 
-                // add to assignment map
-                witness_assignments.insert(lhs, lhs_cell);
-                witness_assignments.insert(rhs, rhs_cell);
-                witness_assignments.insert(output, output_cell);
+                let mut copies = vec![(key_hash.witness, assigned.key_hash)];
+                copies.extend(
+                    public_inputs.iter().map(|input| input.witness).zip(assigned.public_inputs),
+                );
+                copies.extend(
+                    input_aggregation_object
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|input| input.witness)
+                        .zip(assigned.input_aggregation_object),
+                );
+                copies.extend(
+                    output_aggregation_object
+                        .iter()
+                        .copied()
+                        .zip(assigned.output_aggregation_object),
+                );
+                for (witness, cell) in copies {
+                    witness_assignments.check_and_copy(&mut ctx, witness.0, &cell)?;
+                    witness_assignments.insert(witness, cell);
+                }
 
                 Ok(())
             },
@@ -227,4 +1082,87 @@ impl NoirHalo2Translator<Fr> {
     }
 }
 
+/// Whether a memory operation is a write, operations being the constants 0
+/// for a read and 1 for a write
+fn memory_op_is_write(op: &MemOp) -> Result<bool, pse_halo2wrong::halo2::plonk::Error> {
+    if op.operation.is_const() && op.operation.q_c.is_zero() {
+        Ok(false)
+    } else if op.operation.is_const() && op.operation.q_c == FieldElement::one() {
+        Ok(true)
+    } else {
+        Err(pse_halo2wrong::halo2::plonk::Error::Synthesis)
+    }
+}
+
+/// Compose little-endian byte cells into a single cell
+fn compose_bytes(
+    main_gate: &MainGate<Fr>,
+    ctx: &mut RegionCtx<'_, Fr>,
+    bytes: &[AssignedCell<Fr, Fr>],
+) -> Result<AssignedCell<Fr, Fr>, pse_halo2wrong::halo2::plonk::Error> {
+    let mut base = Fr::one();
+    let terms: Vec<Term<Fr>> = bytes
+        .iter()
+        .map(|byte| {
+            let term = Term::Assigned(byte, base);
+            base *= Fr::from(256);
+            term
+        })
+        .collect();
+    main_gate.compose(ctx, &terms, Fr::zero())
+}
+
+/// Compose the 32 little-endian bytes of a field element, constraining them
+/// to encode an integer below the modulus so that the encoding is unique.
+///
+/// With the bytes split in 128-bit halves, `hi * 2^128 + lo <= r - 1` holds
+/// if both `(r - 1)_lo - lo + borrow * 2^128` and `(r - 1)_hi - hi - borrow`
+/// fit in 128 bits.
+fn compose_field_bytes(
+    main_gate: &MainGate<Fr>,
+    range_chip: &RangeChip<Fr>,
+    ctx: &mut RegionCtx<'_, Fr>,
+    cells: &[AssignedCell<Fr, Fr>],
+    bytes: &[u8],
+) -> Result<AssignedCell<Fr, Fr>, pse_halo2wrong::halo2::plonk::Error> {
+    let to_u128 = |bytes: &[u8]| u128::from_le_bytes(bytes.try_into().unwrap());
+    let to_field = |value: u128| Fr::from_raw([value as u64, (value >> 64) as u64, 0, 0]);
+    let shift = Fr::from_raw([0, 0, 1, 0]);
+
+    let max = (-Fr::one()).to_repr();
+    let (max_lo, max_hi) = (to_u128(&max[..16]), to_u128(&max[16..]));
+    let (lo_value, hi_value) = (to_u128(&bytes[..16]), to_u128(&bytes[16..]));
+    let borrow_value = Fr::from((lo_value > max_lo) as u64);
+
+    let lo = compose_bytes(main_gate, ctx, &cells[..16])?;
+    let hi = compose_bytes(main_gate, ctx, &cells[16..])?;
+    let borrow = main_gate.assign_bit(ctx, Value::known(borrow_value))?;
+
+    let diff_lo = main_gate.compose(
+        ctx,
+        &[Term::Assigned(&lo, -Fr::one()), Term::Assigned(&borrow, shift)],
+        to_field(max_lo),
+    )?;
+    let diff_hi = main_gate.compose(
+        ctx,
+        &[Term::Assigned(&hi, -Fr::one()), Term::Assigned(&borrow, -Fr::one())],
+        to_field(max_hi),
+    )?;
+    for (diff, value) in [
+        (diff_lo, to_field(max_lo) - to_field(lo_value) + borrow_value * shift),
+        (diff_hi, to_field(max_hi) - to_field(hi_value) - borrow_value),
+    ] {
+        let limb_bit_len = 8;
+        let bit_len = 128;
+        let range_checked = range_chip.assign(ctx, Value::known(value), limb_bit_len, bit_len)?;
+        main_gate.assert_equal(ctx, &diff, &range_checked)?;
+    }
+
+    main_gate.compose(
+        ctx,
+        &[Term::Assigned(&lo, Fr::one()), Term::Assigned(&hi, shift)],
+        Fr::zero(),
+    )
+}
+
 noir_field_to_halo2_field!(Fr);
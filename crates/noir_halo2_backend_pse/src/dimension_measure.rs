@@ -60,7 +60,7 @@ impl DimensionMeasurement {
 
     pub fn measure<F: PrimeField, C: Circuit<F>>(circuit: &C) -> Result<Dimension, Error> {
         let mut cs = ConstraintSystem::default();
-        let config = C::configure(&mut cs);
+        let config = C::configure_with_params(&mut cs, circuit.params());
         let mut measurement = Self::default();
         C::FloorPlanner::synthesize(&mut measurement, circuit, config, cs.constants().to_vec())?;
         Ok(Dimension {
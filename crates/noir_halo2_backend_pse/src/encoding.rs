@@ -0,0 +1,275 @@
+use crate::chips::ecdsa::{BIT_LEN_LIMB, NUMBER_OF_LIMBS};
+use pse_halo2wrong::{
+    curves::{
+        bn256::{Fq, Fr, G1Affine},
+        group::{ff::PrimeField, GroupEncoding},
+        CurveAffine,
+    },
+    halo2::{
+        plonk::VerifyingKey,
+        transcript::{EncodedChallenge, Transcript, TranscriptRead, TranscriptWrite},
+    },
+};
+use pse_snark_verifier::util::arithmetic::{fe_from_limbs, fe_to_limbs};
+use std::io::{self, Read, Write};
+
+/// Number of fields of a point, the limbs of its two coordinates
+pub(crate) const POINT_LEN: usize = 2 * NUMBER_OF_LIMBS;
+/// Number of bytes packed in every field of a byte encoding
+const BYTES_PER_FIELD: usize = 31;
+
+/// Element of a proof transcript
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ProofElement {
+    Point(G1Affine),
+    Scalar(Fr),
+}
+
+/// Limbs of the coordinates of a point, x first
+pub(crate) fn point_to_limbs(point: &G1Affine) -> Vec<Fr> {
+    [point.x, point.y].map(fe_to_limbs::<Fq, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>).concat()
+}
+
+/// Point of the limbs of its coordinates, if every limb fits in its bit length
+/// and the point is on the curve
+pub(crate) fn point_from_limbs(limbs: &[Fr]) -> Option<G1Affine> {
+    if limbs.len() != POINT_LEN || !limbs.iter().all(|limb| fits_limb(limb)) {
+        return None;
+    }
+    let x = fe_from_limbs::<Fr, Fq, NUMBER_OF_LIMBS, BIT_LEN_LIMB>(
+        limbs[..NUMBER_OF_LIMBS].try_into().unwrap(),
+    );
+    let y = fe_from_limbs::<Fr, Fq, NUMBER_OF_LIMBS, BIT_LEN_LIMB>(
+        limbs[NUMBER_OF_LIMBS..].try_into().unwrap(),
+    );
+    Option::from(G1Affine::from_xy(x, y))
+}
+
+fn fits_limb(limb: &Fr) -> bool {
+    let repr = limb.to_repr();
+    let bytes = repr.as_ref();
+    bytes[BIT_LEN_LIMB / 8] >> (BIT_LEN_LIMB % 8) == 0
+        && bytes[BIT_LEN_LIMB / 8 + 1..].iter().all(|byte| *byte == 0)
+}
+
+/// Field of 32 big-endian bytes, if canonical
+fn field_from_be_bytes<F: PrimeField>(bytes: &[u8]) -> Option<F> {
+    let mut repr = F::Repr::default();
+    for (byte, value) in repr.as_mut().iter_mut().zip(bytes.iter().rev()) {
+        *byte = *value;
+    }
+    Option::from(F::from_repr(repr))
+}
+
+fn field_to_be_bytes<F: PrimeField>(field: &F) -> Vec<u8> {
+    field.to_repr().as_ref().iter().rev().copied().collect()
+}
+
+/// Elements of a proof laid out as by the EVM transcript, points as the 64
+/// big-endian bytes of their coordinates and scalars as 32 big-endian bytes.
+/// A pair of scalars is a point on the curve with negligible probability, so
+/// points are told apart from scalars without the verifying key.
+pub(crate) fn proof_elements(proof: &[u8]) -> Option<Vec<ProofElement>> {
+    let mut elements = Vec::new();
+    let mut offset = 0;
+    while offset < proof.len() {
+        let point = proof.get(offset..offset + 64).and_then(|bytes| {
+            let x = field_from_be_bytes::<Fq>(&bytes[..32])?;
+            let y = field_from_be_bytes::<Fq>(&bytes[32..])?;
+            Option::<G1Affine>::from(G1Affine::from_xy(x, y))
+        });
+        if let Some(point) = point {
+            elements.push(ProofElement::Point(point));
+            offset += 64;
+        } else {
+            let scalar = field_from_be_bytes::<Fr>(proof.get(offset..offset + 32)?)?;
+            elements.push(ProofElement::Scalar(scalar));
+            offset += 32;
+        }
+    }
+    Some(elements)
+}
+
+/// Fields of proof elements, points decomposed into the limbs of their
+/// coordinates and scalars as they are
+pub(crate) fn elements_to_fields(elements: &[ProofElement]) -> Vec<Fr> {
+    elements
+        .iter()
+        .flat_map(|element| match element {
+            ProofElement::Point(point) => point_to_limbs(point),
+            ProofElement::Scalar(scalar) => vec![*scalar],
+        })
+        .collect()
+}
+
+/// Proof elements of their fields. A scalar fits in a limb with negligible
+/// probability, so limbs of a point on the curve are told apart from scalars.
+pub(crate) fn fields_to_elements(fields: &[Fr]) -> Vec<ProofElement> {
+    let mut elements = Vec::new();
+    let mut offset = 0;
+    while offset < fields.len() {
+        let point = fields.get(offset..offset + POINT_LEN).and_then(point_from_limbs);
+        if let Some(point) = point {
+            elements.push(ProofElement::Point(point));
+            offset += POINT_LEN;
+        } else {
+            elements.push(ProofElement::Scalar(fields[offset]));
+            offset += 1;
+        }
+    }
+    elements
+}
+
+/// Bytes of proof elements as read by the Poseidon transcript, compressed
+/// points and little-endian scalars
+pub(crate) fn poseidon_proof_bytes(elements: &[ProofElement]) -> Vec<u8> {
+    elements
+        .iter()
+        .flat_map(|element| match element {
+            ProofElement::Point(point) => point.to_bytes().as_ref().to_vec(),
+            ProofElement::Scalar(scalar) => scalar.to_repr().as_ref().to_vec(),
+        })
+        .collect()
+}
+
+/// Fields of a verifying key: `k`, the numbers of fixed and permutation
+/// commitments, then the limbs of these commitments in transcript order
+pub(crate) fn vk_to_fields(vk: &VerifyingKey<G1Affine>) -> Vec<Fr> {
+    let fixed_commitments = vk.fixed_commitments();
+    let permutation_commitments = vk.permutation().commitments();
+    let mut fields = vec![
+        Fr::from(vk.get_domain().k() as u64),
+        Fr::from(fixed_commitments.len() as u64),
+        Fr::from(permutation_commitments.len() as u64),
+    ];
+    fields.extend(fixed_commitments.iter().chain(permutation_commitments).flat_map(point_to_limbs));
+    fields
+}
+
+/// Split the fields of a verifying key from the fields following them,
+/// returning its commitments in transcript order
+pub(crate) fn vk_commitments_from_fields(fields: &[Fr]) -> Option<(Vec<G1Affine>, &[Fr])> {
+    let count = |field: &Fr| -> Option<usize> {
+        let repr = field.to_repr();
+        let (low, high) = repr.as_ref().split_at(8);
+        high.iter()
+            .all(|byte| *byte == 0)
+            .then(|| u64::from_le_bytes(low.try_into().unwrap()))
+            .map(|count| count as usize)
+    };
+    let num_commitments = count(fields.get(1)?)? + count(fields.get(2)?)?;
+    let end = 3 + num_commitments * POINT_LEN;
+    let commitments =
+        fields.get(3..end)?.chunks(POINT_LEN).map(point_from_limbs).collect::<Option<Vec<_>>>()?;
+    Some((commitments, &fields[end..]))
+}
+
+/// Pack bytes into fields, the byte length followed by big-endian chunks of
+/// 31 bytes so that every chunk fits in a field element
+pub(crate) fn bytes_to_fields(bytes: &[u8]) -> Vec<Fr> {
+    let mut fields = vec![Fr::from(bytes.len() as u64)];
+    fields.extend(bytes.chunks(BYTES_PER_FIELD).map(|chunk| {
+        let mut repr = <Fr as PrimeField>::Repr::default();
+        for (byte, value) in repr.as_mut().iter_mut().zip(chunk.iter().rev()) {
+            *byte = *value;
+        }
+        Fr::from_repr(repr).unwrap()
+    }));
+    fields
+}
+
+/// Unpack the bytes packed by `bytes_to_fields`
+pub(crate) fn fields_to_bytes(fields: &[Fr]) -> Option<Vec<u8>> {
+    let (len, chunks) = fields.split_first()?;
+    let len = u64::from_le_bytes(len.to_repr().as_ref()[..8].try_into().unwrap()) as usize;
+    if chunks.len() != (len + BYTES_PER_FIELD - 1) / BYTES_PER_FIELD {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(len);
+    for (i, chunk) in chunks.iter().enumerate() {
+        let chunk_len = BYTES_PER_FIELD.min(len - i * BYTES_PER_FIELD);
+        bytes.extend(chunk.to_repr().as_ref()[..chunk_len].iter().rev());
+    }
+    Some(bytes)
+}
+
+/// Transcript laying its proof out as the EVM transcript does while hashing
+/// with the wrapped transcript, so that proofs share a single encoding
+/// whatever their transcript
+pub struct EvmLayoutTranscript<T, S> {
+    transcript: T,
+    stream: S,
+}
+
+impl<T, S> EvmLayoutTranscript<T, S> {
+    pub fn new(transcript: T, stream: S) -> Self {
+        EvmLayoutTranscript { transcript, stream }
+    }
+
+    pub fn finalize(self) -> S {
+        self.stream
+    }
+}
+
+impl<E, T, S> Transcript<G1Affine, E> for EvmLayoutTranscript<T, S>
+where
+    E: EncodedChallenge<G1Affine>,
+    T: Transcript<G1Affine, E>,
+{
+    fn squeeze_challenge(&mut self) -> E {
+        self.transcript.squeeze_challenge()
+    }
+
+    fn common_point(&mut self, point: G1Affine) -> io::Result<()> {
+        self.transcript.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: Fr) -> io::Result<()> {
+        self.transcript.common_scalar(scalar)
+    }
+}
+
+impl<E, T, W> TranscriptWrite<G1Affine, E> for EvmLayoutTranscript<T, W>
+where
+    E: EncodedChallenge<G1Affine>,
+    T: Transcript<G1Affine, E>,
+    W: Write,
+{
+    fn write_point(&mut self, point: G1Affine) -> io::Result<()> {
+        self.common_point(point)?;
+        self.stream.write_all(&field_to_be_bytes(&point.x))?;
+        self.stream.write_all(&field_to_be_bytes(&point.y))
+    }
+
+    fn write_scalar(&mut self, scalar: Fr) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        self.stream.write_all(&field_to_be_bytes(&scalar))
+    }
+}
+
+impl<E, T, R> TranscriptRead<G1Affine, E> for EvmLayoutTranscript<T, R>
+where
+    E: EncodedChallenge<G1Affine>,
+    T: Transcript<G1Affine, E>,
+    R: Read,
+{
+    fn read_point(&mut self) -> io::Result<G1Affine> {
+        let mut bytes = [0u8; 64];
+        self.stream.read_exact(&mut bytes)?;
+        let point = field_from_be_bytes::<Fq>(&bytes[..32])
+            .zip(field_from_be_bytes::<Fq>(&bytes[32..]))
+            .and_then(|(x, y)| Option::<G1Affine>::from(G1Affine::from_xy(x, y)))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid point"))?;
+        self.common_point(point)?;
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<Fr> {
+        let mut bytes = [0u8; 32];
+        self.stream.read_exact(&mut bytes)?;
+        let scalar = field_from_be_bytes::<Fr>(&bytes)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid scalar"))?;
+        self.common_scalar(scalar)?;
+        Ok(scalar)
+    }
+}
@@ -21,7 +21,7 @@ use std::io::Write;
 pub(crate) async fn constuct_halo2_params_from_aztec_crs(
     translator: impl pse_halo2wrong::halo2::plonk::Circuit<Fr>,
 ) -> Result<ParamsKZG<Bn256>, Error> {
-    let dimension = DimensionMeasurement::measure(&translator).unwrap();
+    let dimension = DimensionMeasurement::measure(&translator).map_err(Error::synthesis)?;
 
     let k = dimension.k();
     let n = 1 << k;
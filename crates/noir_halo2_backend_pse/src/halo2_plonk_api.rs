@@ -1,12 +1,30 @@
-use crate::circuit_translator::NoirHalo2Translator;
+use crate::{
+    chips::{
+        aggregation::{AggregationChip, AggregationConfig, PoseidonTranscript},
+        bitwise::{BitwiseChip, BitwiseConfig, BITWISE_LIMB_BITS, BYTE_LIMB_BITS},
+        blake2s::{Blake2sChip, Blake2sConfig},
+        ecdsa::{EcdsaChip, EcdsaConfig, BIT_LEN_LIMB, NUMBER_OF_LIMBS},
+        grumpkin::{GrumpkinChip, GrumpkinConfig},
+        keccak256::{Keccak256Chip, Keccak256Config},
+        memory::{MemoryChip, MemoryConfig},
+        sha256::{Sha256Chip, Sha256Config},
+    },
+    circuit_translator::NoirHalo2Translator,
+    encoding::EvmLayoutTranscript,
+};
 use acvm::{
-    acir::circuit::{opcodes::BlackBoxFuncCall, Opcode},
+    acir::{
+        circuit::{opcodes::BlackBoxFuncCall, Opcode},
+        native_types::Expression,
+    },
     FieldElement,
 };
 use pse_halo2wrong::halo2::{
     halo2curves::{
         bn256::{Bn256, Fr, G1Affine, G1},
         group::cofactor::CofactorCurve,
+        secp256k1::Secp256k1Affine,
+        secp256r1::Secp256r1Affine,
     },
     plonk::{
         create_proof, keygen_pk, keygen_vk, verify_proof, ConstraintSystem, Error, ProvingKey,
@@ -18,22 +36,30 @@ use pse_halo2wrong::halo2::{
         strategy::SingleStrategy,
     },
     transcript::{TranscriptReadBuffer, TranscriptWriterBuffer},
+    SerdeFormat,
 };
 use pse_maingate::{MainGate, MainGateConfig, RangeChip, RangeConfig};
 
-use pse_snark_verifier::system::halo2::transcript::evm::EvmTranscript;
+use pse_snark_verifier::{
+    loader::native::NativeLoader,
+    system::halo2::transcript::{evm::EvmTranscript, halo2::ChallengeScalar},
+};
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
+use std::io;
 
 /// Generate Halo2 Proving and Verifying Keys
 pub fn halo2_keygen(
     circuit: &NoirHalo2Translator<Fr>,
     params: &ParamsKZG<Bn256>,
-) -> (ProvingKey<<G1 as CofactorCurve>::Affine>, VerifyingKey<<G1 as CofactorCurve>::Affine>) {
-    let vk = keygen_vk(params, circuit).expect("keygen_vk should not fail");
+) -> Result<
+    (ProvingKey<<G1 as CofactorCurve>::Affine>, VerifyingKey<<G1 as CofactorCurve>::Affine>),
+    Error,
+> {
+    let vk = keygen_vk(params, circuit)?;
     let vk_return = vk.clone();
-    let pk = keygen_pk(params, vk, circuit).expect("keygen_pk should not fail");
-    (pk, vk_return)
+    let pk = keygen_pk(params, vk, circuit)?;
+    Ok((pk, vk_return))
 }
 
 /// Generate Halo2 Proof
@@ -42,7 +68,7 @@ pub fn halo2_prove(
     params: &ParamsKZG<Bn256>,
     pk: &ProvingKey<<G1 as CofactorCurve>::Affine>,
     public_inputs: &[Fr],
-) -> Vec<u8> {
+) -> Result<Vec<u8>, Error> {
     let rng = OsRng;
     let mut transcript = TranscriptWriterBuffer::<_, G1Affine, _>::init(Vec::new());
 
@@ -53,9 +79,8 @@ pub fn halo2_prove(
         &[&[public_inputs]],
         rng,
         &mut transcript,
-    )
-    .expect("proof generation should not fail");
-    transcript.finalize()
+    )?;
+    Ok(transcript.finalize())
 }
 
 /// Verify Halo2 Proof
@@ -77,10 +102,102 @@ pub fn halo2_verify(
     )
 }
 
+/// Generate Halo2 Proof with a Poseidon transcript,
+/// which can be verified in circuit by recursive aggregation.
+/// The proof is laid out as an EVM transcript one, so that
+/// all proofs of the backend share their field encoding
+pub fn halo2_prove_recursive(
+    circuit: NoirHalo2Translator<Fr>,
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<<G1 as CofactorCurve>::Affine>,
+    public_inputs: &[Fr],
+) -> Result<Vec<u8>, Error> {
+    let rng = OsRng;
+    let mut transcript = EvmLayoutTranscript::new(
+        PoseidonTranscript::<NativeLoader, _>::new(Vec::new()),
+        Vec::new(),
+    );
+
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverGWC<_>, ChallengeScalar<G1Affine>, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&[public_inputs]],
+        rng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+/// Verify Halo2 Proof generated with a Poseidon transcript
+pub fn halo2_verify_recursive(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<<G1 as CofactorCurve>::Affine>,
+    proof: &[u8],
+    public_inputs: &[Fr],
+) -> Result<(), Error> {
+    let strategy = SingleStrategy::new(params);
+    let mut transcript =
+        EvmLayoutTranscript::new(PoseidonTranscript::<NativeLoader, _>::new(&[][..]), proof);
+
+    verify_proof::<_, VerifierGWC<_>, ChallengeScalar<G1Affine>, _, _>(
+        params,
+        vk,
+        strategy,
+        &[&[public_inputs]],
+        &mut transcript,
+    )
+}
+
+/// Serialize the Verifying Key prefixed with the
+/// opcode flags its circuit was configured with
+pub fn halo2_vk_to_bytes(
+    vk: &VerifyingKey<<G1 as CofactorCurve>::Affine>,
+    opcode_flags: &OpcodeFlags,
+) -> Vec<u8> {
+    let flags = serde_json::to_vec(opcode_flags).expect("opcode flags should serialize");
+    let mut bytes = (flags.len() as u32).to_le_bytes().to_vec();
+    bytes.extend(flags);
+    bytes.extend(vk.to_bytes(SerdeFormat::RawBytes));
+    bytes
+}
+
+/// Read a Verifying Key serialized by `halo2_vk_to_bytes`,
+/// configuring its circuit with the stored opcode flags
+pub fn halo2_vk_from_bytes(
+    bytes: &[u8],
+) -> io::Result<(VerifyingKey<<G1 as CofactorCurve>::Affine>, OpcodeFlags)> {
+    let truncated = io::Error::new(io::ErrorKind::UnexpectedEof, "truncated verifying key");
+    if bytes.len() < 4 {
+        return Err(truncated);
+    }
+    let (len, bytes) = bytes.split_at(4);
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+    if bytes.len() < len {
+        return Err(truncated);
+    }
+    let (flags, bytes) = bytes.split_at(len);
+    let opcode_flags: OpcodeFlags = serde_json::from_slice(flags)?;
+    let vk = VerifyingKey::from_bytes::<NoirHalo2Translator<Fr>>(
+        bytes,
+        SerdeFormat::RawBytes,
+        opcode_flags.clone(),
+    )?;
+    Ok((vk, opcode_flags))
+}
+
 #[derive(Clone, Debug)]
 pub struct PlonkConfig {
     pub(crate) main_gate_config: MainGateConfig,
-    pub(crate) range_config: RangeConfig,
+    pub(crate) range_config: Option<RangeConfig>,
+    pub(crate) bitwise_config: Option<BitwiseConfig>,
+    pub(crate) sha256_config: Option<Sha256Config>,
+    pub(crate) blake2s_config: Option<Blake2sConfig>,
+    pub(crate) keccak256_config: Option<Keccak256Config>,
+    pub(crate) ecdsa_config: Option<EcdsaConfig>,
+    pub(crate) grumpkin_config: Option<GrumpkinConfig>,
+    pub(crate) memory_config: Option<MemoryConfig>,
+    pub(crate) aggregation_config: Option<AggregationConfig>,
 }
 
 impl PlonkConfig {
@@ -88,8 +205,9 @@ impl PlonkConfig {
     pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
         let main_gate_config = MainGate::<Fr>::configure(meta);
 
-        let overflow_bit_lens: Vec<usize> = vec![1, 2, 3, 4, 5, 6, 7];
-        let composition_bit_lens = vec![8];
+        let mut overflow_bit_lens: Vec<usize> = vec![1, 2, 3, 4, 5, 6, 7];
+        let mut composition_bit_lens = vec![8];
+        non_native_range_bit_lens(true, true, &mut composition_bit_lens, &mut overflow_bit_lens);
 
         let range_config = RangeChip::<Fr>::configure(
             meta,
@@ -98,27 +216,141 @@ impl PlonkConfig {
             overflow_bit_lens,
         );
 
-        PlonkConfig { main_gate_config, range_config }
+        let bitwise_config = BitwiseChip::configure(meta, BYTE_LIMB_BITS);
+        let sha256_config = Some(Sha256Chip::configure(meta));
+        let blake2s_config = Some(Blake2sChip::configure(meta, &bitwise_config));
+        let bitwise_config = Some(bitwise_config);
+        let keccak256_config = Some(Keccak256Chip::configure(meta));
+        let ecdsa_config = Some(EcdsaConfig::new(main_gate_config.clone(), range_config.clone()));
+        let grumpkin_config = Some(GrumpkinChip::configure(meta));
+        let memory_config = Some(MemoryChip::configure(meta));
+        let aggregation_config =
+            Some(AggregationConfig::new(main_gate_config.clone(), range_config.clone()));
+        let range_config = Some(range_config);
+
+        PlonkConfig {
+            main_gate_config,
+            range_config,
+            bitwise_config,
+            sha256_config,
+            blake2s_config,
+            keccak256_config,
+            ecdsa_config,
+            grumpkin_config,
+            memory_config,
+            aggregation_config,
+        }
     }
 
     pub(crate) fn configure_with_params(
         meta: &mut ConstraintSystem<Fr>,
-        _opcodes_flags: OpcodeFlags,
+        opcode_flags: OpcodeFlags,
     ) -> Self {
         let main_gate_config = MainGate::<Fr>::configure(meta);
 
-        let overflow_bit_lens: Vec<usize> = vec![1, 2, 3, 4, 5, 6, 7];
-        let composition_bit_lens = vec![8];
+        let ecdsa = opcode_flags.ecdsa_secp256k1 || opcode_flags.ecdsa_secp256r1;
+        let aggregation = opcode_flags.recursive_aggregation;
+        // the range tables are only paid for by the opcodes range checking
+        // through them: hash to field checks its inputs are canonical, schnorr
+        // the bytes of its challenge, and ram checks the sorting of its accesses
+        let range = opcode_flags.range
+            || ecdsa
+            || aggregation
+            || opcode_flags.hash_to_field
+            || opcode_flags.schnorr_verify
+            || opcode_flags.ram;
+        let range_config = range.then(|| {
+            let mut overflow_bit_lens: Vec<usize> = vec![1, 2, 3, 4, 5, 6, 7];
+            let mut composition_bit_lens = vec![8];
+            non_native_range_bit_lens(
+                ecdsa,
+                aggregation,
+                &mut composition_bit_lens,
+                &mut overflow_bit_lens,
+            );
 
-        let range_config = RangeChip::<Fr>::configure(
-            meta,
-            &main_gate_config,
-            composition_bit_lens,
-            overflow_bit_lens,
-        );
+            RangeChip::<Fr>::configure(
+                meta,
+                &main_gate_config,
+                composition_bit_lens,
+                overflow_bit_lens,
+            )
+        });
+
+        // AND reuses the XOR lookup tables, and blake2s looks its xors up in them,
+        // which takes tables of byte limbs rather than the smaller ones of nibbles
+        let blake2s =
+            opcode_flags.blake2s || opcode_flags.hash_to_field || opcode_flags.schnorr_verify;
+        let limb_bits = if blake2s { BYTE_LIMB_BITS } else { BITWISE_LIMB_BITS };
+        let bitwise_config = (opcode_flags.and || opcode_flags.xor || blake2s)
+            .then(|| BitwiseChip::configure(meta, limb_bits));
+        let sha256_config = opcode_flags.sha256.then(|| Sha256Chip::configure(meta));
+        // hash to field digests its inputs with blake2s, and schnorr its challenge
+        let blake2s_config = bitwise_config
+            .as_ref()
+            .filter(|_| blake2s)
+            .map(|bitwise_config| Blake2sChip::configure(meta, bitwise_config));
+        // both keccak opcodes share the chip, fixed length inputs are padded
+        // against a constant length
+        let keccak256_config = (opcode_flags.keccak256 || opcode_flags.keccak256_variable_length)
+            .then(|| Keccak256Chip::configure(meta));
+        // ecdsa runs on the main gate and range tables
+        let ecdsa_config = range_config
+            .clone()
+            .filter(|_| ecdsa)
+            .map(|range_config| EcdsaConfig::new(main_gate_config.clone(), range_config));
+        // pedersen commits through fixed base multiplications on the embedded curve,
+        // on which schnorr also recovers its nonce
+        let grumpkin_config = (opcode_flags.fixed_base_scalar_mul
+            || opcode_flags.pedersen
+            || opcode_flags.schnorr_verify)
+            .then(|| GrumpkinChip::configure(meta));
+        // rom and ram blocks share the memory chip, told apart by their tags
+        let memory_config =
+            (opcode_flags.rom || opcode_flags.ram).then(|| MemoryChip::configure(meta));
+        // recursive aggregation verifies proofs on the main gate and range tables
+        let aggregation_config = range_config
+            .clone()
+            .filter(|_| aggregation)
+            .map(|range_config| AggregationConfig::new(main_gate_config.clone(), range_config));
+
+        PlonkConfig {
+            main_gate_config,
+            range_config,
+            bitwise_config,
+            sha256_config,
+            blake2s_config,
+            keccak256_config,
+            ecdsa_config,
+            grumpkin_config,
+            memory_config,
+            aggregation_config,
+        }
+    }
+}
 
-        PlonkConfig { main_gate_config, range_config }
+/// Add the bit lengths the range chip needs to range check the non-native
+/// field limbs of both ecdsa curves, and of the BN254 base field when
+/// verifying proofs
+fn non_native_range_bit_lens(
+    ecdsa: bool,
+    aggregation: bool,
+    composition_bit_lens: &mut Vec<usize>,
+    overflow_bit_lens: &mut Vec<usize>,
+) {
+    if !ecdsa && !aggregation {
+        return;
+    }
+    composition_bit_lens.push(BIT_LEN_LIMB / NUMBER_OF_LIMBS);
+    if ecdsa {
+        overflow_bit_lens.extend(EcdsaChip::<Secp256k1Affine>::overflow_bit_lens());
+        overflow_bit_lens.extend(EcdsaChip::<Secp256r1Affine>::overflow_bit_lens());
+    }
+    if aggregation {
+        overflow_bit_lens.extend(AggregationChip::overflow_bit_lens());
     }
+    overflow_bit_lens.sort_unstable();
+    overflow_bit_lens.dedup();
 }
 
 #[derive(Clone, Hash, Debug, Serialize, Deserialize)]
@@ -126,11 +358,17 @@ pub(crate) struct NoirConstraint {
     pub(crate) a: i32,
     pub(crate) b: i32,
     pub(crate) c: i32,
+    pub(crate) d: i32,
+    pub(crate) e: i32,
     pub(crate) qm: FieldElement,
     pub(crate) ql: FieldElement,
     pub(crate) qr: FieldElement,
     pub(crate) qo: FieldElement,
+    pub(crate) qd: FieldElement,
+    pub(crate) qe: FieldElement,
     pub(crate) qc: FieldElement,
+    // coefficient of the E column of the next row
+    pub(crate) qnext: FieldElement,
 }
 
 impl Default for NoirConstraint {
@@ -139,11 +377,16 @@ impl Default for NoirConstraint {
             a: 0,
             b: 0,
             c: 0,
+            d: 0,
+            e: 0,
             qm: FieldElement::zero(),
             ql: FieldElement::zero(),
             qr: FieldElement::zero(),
             qo: FieldElement::zero(),
+            qd: FieldElement::zero(),
+            qe: FieldElement::zero(),
             qc: FieldElement::zero(),
+            qnext: FieldElement::zero(),
         }
     }
 }
@@ -159,16 +402,102 @@ impl NoirConstraint {
         } else if self.c == 0 || self.c == witness {
             self.c = witness;
             self.qo = x;
+        } else if self.d == 0 || self.d == witness {
+            self.d = witness;
+            self.qd = x;
+        } else if self.e == 0 || self.e == witness {
+            self.e = witness;
+            self.qe = x;
         } else {
-            unreachable!("Cannot assign linear term to a constrain of width 3");
+            unreachable!("Cannot assign linear term to a constrain of width 5");
         }
     }
+
+    /// Hand a linear witness shared with the constraint of the next row over
+    /// to the E column of that row, which this row then reads through the
+    /// next row combination of the main gate instead of a copy constraint.
+    /// `pinned` tells whether the E column of this row is already read by the
+    /// previous row, and the returned flag whether the next one now is.
+    pub(crate) fn flow_into(&mut self, next: &mut NoirConstraint, pinned: bool) -> bool {
+        // the witnesses of the mul term stay in columns A and B
+        let first_linear = |noir_cs: &NoirConstraint| if noir_cs.qm.is_zero() { 0 } else { 2 };
+        let last = if pinned { 4 } else { 5 };
+
+        for i in first_linear(self)..last {
+            let (witness, coefficient) = self.slot(i);
+            if witness == 0 {
+                continue;
+            }
+            if let Some(j) = (first_linear(next)..5).find(|j| next.slot(*j).0 == witness) {
+                let e = next.slot(4);
+                next.set_slot(4, next.slot(j));
+                next.set_slot(j, e);
+                self.set_slot(i, (0, FieldElement::zero()));
+                self.qnext = coefficient;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Witness and coefficient of a main gate column
+    fn slot(&self, column: usize) -> (i32, FieldElement) {
+        match column {
+            0 => (self.a, self.ql),
+            1 => (self.b, self.qr),
+            2 => (self.c, self.qo),
+            3 => (self.d, self.qd),
+            4 => (self.e, self.qe),
+            _ => unreachable!("Constrains have a width of 5"),
+        }
+    }
+
+    fn set_slot(&mut self, column: usize, (witness, coefficient): (i32, FieldElement)) {
+        let (slot, q) = match column {
+            0 => (&mut self.a, &mut self.ql),
+            1 => (&mut self.b, &mut self.qr),
+            2 => (&mut self.c, &mut self.qo),
+            3 => (&mut self.d, &mut self.qd),
+            4 => (&mut self.e, &mut self.qe),
+            _ => unreachable!("Constrains have a width of 5"),
+        };
+        *slot = witness;
+        *q = coefficient;
+    }
+}
+
+impl From<&Expression> for NoirConstraint {
+    fn from(gate: &Expression) -> Self {
+        let mut noir_cs = NoirConstraint::default();
+        // check mul gate
+        if !gate.mul_terms.is_empty() {
+            let mul_term = &gate.mul_terms[0];
+            noir_cs.qm = mul_term.0;
+
+            // Get wL term
+            let wl = &mul_term.1;
+            noir_cs.a = wl.witness_index() as i32;
+
+            // Get wR term
+            let wr = &mul_term.2;
+            noir_cs.b = wr.witness_index() as i32;
+        }
+
+        for term in &gate.linear_combinations {
+            noir_cs.set_linear_term(term.0, term.1.witness_index() as i32);
+        }
+
+        // Add the qc term
+        noir_cs.qc = gate.q_c;
+
+        noir_cs
+    }
 }
 
 /// Opcode flags that shows which opcode
 /// is used given a circuit instance
 #[allow(dead_code)]
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct OpcodeFlags {
     pub(crate) arithmetic: bool,
     pub(crate) range: bool,
@@ -180,10 +509,13 @@ pub struct OpcodeFlags {
     pub(crate) pedersen: bool,
     pub(crate) hash_to_field: bool,
     pub(crate) ecdsa_secp256k1: bool,
+    pub(crate) ecdsa_secp256r1: bool,
     pub(crate) fixed_base_scalar_mul: bool,
     pub(crate) keccak256: bool,
     pub(crate) keccak256_variable_length: bool,
     pub(crate) recursive_aggregation: bool,
+    pub(crate) rom: bool,
+    pub(crate) ram: bool,
 }
 
 impl OpcodeFlags {
@@ -199,10 +531,13 @@ impl OpcodeFlags {
         let mut pedersen = false;
         let mut hash_to_field = false;
         let mut ecdsa_secp256k1 = false;
+        let mut ecdsa_secp256r1 = false;
         let mut fixed_base_scalar_mul = false;
         let mut keccak256 = false;
         let mut keccak256_variable_length = false;
         let mut recursive_aggregation = false;
+        let mut rom = false;
+        let mut ram = false;
         for opcode in opcodes {
             match opcode {
                 Opcode::Arithmetic(..) => arithmetic = true,
@@ -216,7 +551,7 @@ impl OpcodeFlags {
                     BlackBoxFuncCall::Pedersen { .. } => pedersen = true,
                     BlackBoxFuncCall::HashToField128Security { .. } => hash_to_field = true,
                     BlackBoxFuncCall::EcdsaSecp256k1 { .. } => ecdsa_secp256k1 = true,
-                    BlackBoxFuncCall::EcdsaSecp256r1 { .. } => ecdsa_secp256k1 = true,
+                    BlackBoxFuncCall::EcdsaSecp256r1 { .. } => ecdsa_secp256r1 = true,
                     BlackBoxFuncCall::FixedBaseScalarMul { .. } => fixed_base_scalar_mul = true,
                     BlackBoxFuncCall::Keccak256 { .. } => keccak256 = true,
                     BlackBoxFuncCall::Keccak256VariableLength { .. } => {
@@ -227,7 +562,9 @@ impl OpcodeFlags {
                 Opcode::Directive(_) | Opcode::Brillig(_) => {
                     // Directives are only needed by the pwg
                 }
-                Opcode::Block(_) | Opcode::ROM(_) | Opcode::RAM(_) => {}
+                Opcode::ROM(_) => rom = true,
+                // blocks are read and written like a ram
+                Opcode::Block(_) | Opcode::RAM(_) => ram = true,
             }
         }
 
@@ -242,10 +579,13 @@ impl OpcodeFlags {
             pedersen,
             hash_to_field,
             ecdsa_secp256k1,
+            ecdsa_secp256r1,
             fixed_base_scalar_mul,
             keccak256,
             keccak256_variable_length,
             recursive_aggregation,
+            rom,
+            ram,
         }
     }
 }
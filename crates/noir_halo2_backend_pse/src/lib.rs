@@ -1,5 +1,7 @@
 mod acvm_interop;
+mod chips;
 mod dimension_measure;
+mod encoding;
 
 mod assigned_map;
 mod circuit_translator;
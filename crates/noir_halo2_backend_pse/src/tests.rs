@@ -1,16 +1,55 @@
 #[cfg(test)]
 mod test {
-    use crate::{circuit_translator::NoirHalo2Translator, dimension_measure::DimensionMeasurement};
-    use acvm::{acir::native_types::Witness, FieldElement};
+    use crate::{
+        assigned_map::AssignedMap,
+        chips::{
+            aggregation::{
+                aggregate_native, decide, proof_from_fields, protocol_from_fields,
+                protocol_to_fields, AGGREGATION_OBJECT_LEN,
+            },
+            bitwise::{BITWISE_LIMB_BITS, BYTE_LIMB_BITS},
+            grumpkin::{schnorr_challenge_message, schnorr_verify, GrumpkinPoint},
+        },
+        circuit_translator::NoirHalo2Translator,
+        dimension_measure::DimensionMeasurement,
+        encoding::{
+            elements_to_fields, fields_to_elements, proof_elements, vk_to_fields, ProofElement,
+        },
+        halo2_plonk_api::{
+            halo2_keygen, halo2_prove, halo2_prove_recursive, halo2_verify_recursive,
+            halo2_vk_from_bytes, halo2_vk_to_bytes, NoirConstraint, OpcodeFlags, PlonkConfig,
+        },
+        PseHalo2,
+    };
+    use acvm::{
+        acir::{
+            circuit::{
+                opcodes::{BlackBoxFuncCall, BlockId, FunctionInput, MemOp, MemoryBlock},
+                Circuit as NoirCircuit, Opcode,
+            },
+            native_types::{Expression, Witness, WitnessMap},
+        },
+        FieldElement, ProofSystemCompiler,
+    };
     use noir_halo2_backend_common::test_helpers::build_artifacts;
     use pse_halo2wrong::{
-        curves::bn256::Fr,
+        curves::{
+            bn256::{Bn256, Fq, Fr},
+            group::ff::PrimeField,
+        },
         halo2::{
+            circuit::{Layouter, SimpleFloorPlanner},
             dev::{FailureLocation, MockProver, VerifyFailure},
-            plonk::Any,
+            plonk::{Any, Circuit, ConstraintSystem, Error},
+            poly::kzg::commitment::ParamsKZG,
+            SerdeFormat,
         },
+        RegionCtx,
     };
-    use std::marker::PhantomData;
+    use pse_maingate::MainGate;
+    use pse_snark_verifier::system::halo2::{compile, Config};
+    use rand::rngs::OsRng;
+    use std::{collections::BTreeMap, marker::PhantomData};
 
     #[test]
     fn test_public_io_circuit_success() {
@@ -43,25 +82,31 @@ mod test {
         // instance value (known to be 7, incorrectly set to 8)
         let instance = vec![Fr::from_raw([8u64, 0, 0, 0])];
 
-        // define permutation error expected when instance value is not set or incorrect
-        let permutation_error = Err(vec![
-            VerifyFailure::Permutation {
-                column: (Any::advice(), 0).into(),
-                location: FailureLocation::InRegion { region: (7, "region 0").into(), offset: 0 },
-            },
-            VerifyFailure::Permutation {
-                column: (Any::Instance, 0usize).into(),
-                location: FailureLocation::OutsideRegion { row: 0 },
-            },
-        ]);
+        // the public witness and the instance cell it is copied to fail their permutation,
+        // wherever the layout places the cell of the witness in the main gate
+        let assert_permutation_failure = |failures: Vec<VerifyFailure>| {
+            assert_eq!(failures.len(), 2);
+            assert!(failures.iter().any(|failure| matches!(
+                failure,
+                VerifyFailure::Permutation { column, location: FailureLocation::InRegion { .. } }
+                    if (0..5).any(|index| *column == (Any::advice(), index).into())
+            )));
+            assert!(failures.iter().any(|failure| matches!(
+                failure,
+                VerifyFailure::Permutation {
+                    column,
+                    location: FailureLocation::OutsideRegion { row: 0 },
+                } if *column == (Any::Instance, 0usize).into()
+            )));
+        };
 
         // run mock prover with incorrect instance expecting permutation failure
         let prover = MockProver::run(dimension.k(), &translator, vec![instance]).unwrap();
-        assert_eq!(prover.verify(), permutation_error);
+        assert_permutation_failure(prover.verify().unwrap_err());
 
         // run mock prover with no instance expecting permutation failure
         let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
-        assert_eq!(prover.verify(), permutation_error);
+        assert_permutation_failure(prover.verify().unwrap_err());
     }
 
     #[test]
@@ -80,33 +125,20 @@ mod test {
         // instance value (known to be 7)
         let instance = vec![Fr::from_raw([7u64, 0, 0, 0])];
 
-        // run mock prover expecting success
+        // run mock prover expecting the main gate holding the mutated witness to fail
         // expects [-1(5) + -1(4) + 1(7)] == 0, should be [-1(3) + -1(4) + 1(7)]
         let prover = MockProver::run(dimension.k(), &translator, vec![instance]).unwrap();
-        assert_eq!(
-            prover.verify(),
-            Err(vec![VerifyFailure::ConstraintNotSatisfied {
-                constraint: ((0, "main_gate").into(), 0, "").into(),
-                location: FailureLocation::InRegion { region: (5, "region 0").into(), offset: 5 },
-                cell_values: vec![
-                    (((Any::advice(), 0).into(), 0).into(), String::from("0x5")),
-                    (((Any::advice(), 1).into(), 0).into(), String::from("0x4")),
-                    (((Any::advice(), 2).into(), 0).into(), String::from("0x7")),
-                    (((Any::advice(), 3).into(), 0).into(), String::from("0")),
-                    (((Any::advice(), 4).into(), 0).into(), String::from("0")),
-                    (((Any::advice(), 4).into(), 1).into(), String::from("0")),
-                    (((Any::Fixed, 0).into(), 0).into(), String::from("-1")),
-                    (((Any::Fixed, 1).into(), 0).into(), String::from("-1")),
-                    (((Any::Fixed, 2).into(), 0).into(), String::from("1")),
-                    (((Any::Fixed, 3).into(), 0).into(), String::from("0")),
-                    (((Any::Fixed, 4).into(), 0).into(), String::from("0")),
-                    (((Any::Fixed, 5).into(), 0).into(), String::from("0")),
-                    (((Any::Fixed, 6).into(), 0).into(), String::from("0")),
-                    (((Any::Fixed, 7).into(), 0).into(), String::from("0")),
-                    (((Any::Fixed, 8).into(), 0).into(), String::from("0")),
-                ]
-            }])
-        );
+        let failures = prover.verify().unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(
+            &failures[0],
+            VerifyFailure::ConstraintNotSatisfied {
+                constraint,
+                location: FailureLocation::InRegion { .. },
+                cell_values,
+            } if *constraint == ((0, "main_gate").into(), 0, "").into()
+                && cell_values.iter().any(|(_, value)| value == "0x5")
+        ));
     }
 
     #[test]
@@ -121,6 +153,15 @@ mod test {
             "7_function",
             "8_bit_and",
             "9_poseidon",
+            "12_bit_xor",
+            "13_sha256",
+            "14_keccak256",
+            "15_ecdsa_secp256k1",
+            "16_fixed_base_scalar_mul",
+            "17_pedersen",
+            "18_blake2s",
+            "19_hash_to_field",
+            "20_memory",
         ];
         for program in test_dirs_names {
             // get circuit
@@ -136,4 +177,476 @@ mod test {
             assert_eq!(prover.verify(), Ok(()));
         }
     }
+
+    fn packed_circuit(result: u128) -> NoirHalo2Translator<Fr> {
+        // w1 + w2 - w3 = 0 and w3 + w4 - w5 = 0 share w3 between two rows
+        let gate = |a: u32, b: u32, c: u32| {
+            Opcode::Arithmetic(Expression {
+                mul_terms: vec![],
+                linear_combinations: vec![
+                    (FieldElement::one(), Witness(a)),
+                    (FieldElement::one(), Witness(b)),
+                    (-FieldElement::one(), Witness(c)),
+                ],
+                q_c: FieldElement::zero(),
+            })
+        };
+        let circuit = NoirCircuit {
+            current_witness_index: 5,
+            opcodes: vec![gate(1, 2, 3), gate(3, 4, 5)],
+            ..Default::default()
+        };
+        let witness_values = WitnessMap::from(BTreeMap::from_iter(
+            [1, 2, 3, 4, result]
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| (Witness(i as u32 + 1), FieldElement::from(value))),
+        ));
+        NoirHalo2Translator::<Fr> { circuit, witness_values, _marker: PhantomData::<Fr> }
+    }
+
+    /// Constraints of w1 + w2 - w3 = 0 and w3 + w4 - w5 = 0, w3 flowing
+    /// from the first row into the E column of the second
+    fn flowed_constraints() -> [NoirConstraint; 2] {
+        let mut noir_constraints = [(1, 2, 3), (3, 4, 5)].map(|(a, b, c)| {
+            let mut noir_cs = NoirConstraint::default();
+            noir_cs.set_linear_term(FieldElement::one(), a);
+            noir_cs.set_linear_term(FieldElement::one(), b);
+            noir_cs.set_linear_term(-FieldElement::one(), c);
+            noir_cs
+        });
+        let [first, second] = &mut noir_constraints;
+        assert!(first.flow_into(second, false));
+        noir_constraints
+    }
+
+    /// Circuit assigning each of the flowed constraints with the witnesses of
+    /// its own translator, as a prover assigning a different value to the E
+    /// cell of the next row than the one its previous row expects would
+    #[derive(Clone, Default)]
+    struct FlowCircuit {
+        translators: Vec<NoirHalo2Translator<Fr>>,
+    }
+
+    impl Circuit<Fr> for FlowCircuit {
+        type Config = PlonkConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> PlonkConfig {
+            PlonkConfig::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: PlonkConfig,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let noir_constraints = flowed_constraints();
+            layouter.assign_region(
+                || "region 0",
+                |region| {
+                    let ctx = &mut RegionCtx::new(region, 0);
+                    let main_gate = MainGate::<Fr>::new(config.main_gate_config.clone());
+                    let mut witness_assignments = AssignedMap::new();
+                    for (translator, noir_cs) in self.translators.iter().zip(&noir_constraints) {
+                        translator.add_arithmetic_constrains(
+                            noir_cs,
+                            &main_gate,
+                            ctx,
+                            &mut witness_assignments,
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_packed_arithmetic_flow() {
+        let [first, second] = flowed_constraints();
+
+        // w3 moves to the E column of the second row, read by the first row
+        assert_eq!((first.c, first.qnext), (0, -FieldElement::one()));
+        assert_eq!((second.a, second.e, second.qe), (0, 3, FieldElement::one()));
+
+        let translator = packed_circuit(7);
+        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+        let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let translator = packed_circuit(8);
+        let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_packed_arithmetic_flow_fail_next_row() {
+        // both rows assigned with the same witnesses hold
+        let circuit = FlowCircuit { translators: vec![packed_circuit(7), packed_circuit(7)] };
+        let dimension = DimensionMeasurement::measure(&circuit).unwrap();
+        let prover = MockProver::run(dimension.k(), &circuit, vec![vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // the second row holds with w3 = 4 and w4 = 3, but its E cell is
+        // not the w3 = 3 the first row reads from it
+        let mut tampered = packed_circuit(7);
+        tampered.witness_values.insert(Witness(3), FieldElement::from(4u128));
+        tampered.witness_values.insert(Witness(4), FieldElement::from(3u128));
+        let circuit = FlowCircuit { translators: vec![packed_circuit(7), tampered] };
+
+        // run mock prover expecting the main gate of the first row to fail
+        let prover = MockProver::run(dimension.k(), &circuit, vec![vec![]]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(
+            &failures[0],
+            VerifyFailure::ConstraintNotSatisfied {
+                constraint,
+                location: FailureLocation::InRegion { offset: 0, .. },
+                ..
+            } if *constraint == ((0, "main_gate").into(), 0, "").into()
+        ));
+    }
+
+    /// A block initialized to [w1, w2] whose index 1 is read into w3
+    fn block_circuit(read: u128) -> NoirHalo2Translator<Fr> {
+        let constant = |value: u128| Expression {
+            mul_terms: vec![],
+            linear_combinations: vec![],
+            q_c: FieldElement::from(value),
+        };
+        let witness = |index: u32| Expression {
+            mul_terms: vec![],
+            linear_combinations: vec![(FieldElement::one(), Witness(index))],
+            q_c: FieldElement::zero(),
+        };
+        let trace = vec![
+            MemOp { operation: constant(1), index: constant(0), value: witness(1) },
+            MemOp { operation: constant(1), index: constant(1), value: witness(2) },
+            MemOp { operation: constant(0), index: constant(1), value: witness(3) },
+        ];
+        let circuit = NoirCircuit {
+            current_witness_index: 3,
+            opcodes: vec![Opcode::Block(MemoryBlock { id: BlockId(0), len: 2, trace })],
+            ..Default::default()
+        };
+        let witness_values = WitnessMap::from(BTreeMap::from_iter(
+            [10, 20, read]
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| (Witness(i as u32 + 1), FieldElement::from(value))),
+        ));
+        NoirHalo2Translator::<Fr> { circuit, witness_values, _marker: PhantomData::<Fr> }
+    }
+
+    #[test]
+    fn test_block_circuit() {
+        let translator = block_circuit(20);
+        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+
+        // run mock prover expecting success
+        let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // index 1 holds 20, reading the 10 of index 0 fails
+        let translator = block_circuit(10);
+        let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_opcode_flags_drive_configuration() {
+        let translator = packed_circuit(7);
+        let opcode_flags = OpcodeFlags::new(&translator.circuit.opcodes);
+
+        // arithmetic only circuits skip the range and bitwise tables
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = PlonkConfig::configure_with_params(&mut meta, opcode_flags);
+        assert!(config.range_config.is_none() && config.bitwise_config.is_none());
+        assert!(meta.lookups().is_empty());
+
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let opcode_flags = OpcodeFlags { range: true, ..Default::default() };
+        let config = PlonkConfig::configure_with_params(&mut meta, opcode_flags);
+        assert!(config.range_config.is_some() && config.bitwise_config.is_none());
+
+        // bitwise opcodes look nibbles up, unless blake2s needs tables of bytes
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let opcode_flags = OpcodeFlags { and: true, xor: true, ..Default::default() };
+        let config = PlonkConfig::configure_with_params(&mut meta, opcode_flags);
+        assert_eq!(config.bitwise_config.unwrap().limb_bits, BITWISE_LIMB_BITS);
+
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let opcode_flags = OpcodeFlags { and: true, blake2s: true, ..Default::default() };
+        let config = PlonkConfig::configure_with_params(&mut meta, opcode_flags);
+        assert_eq!(config.bitwise_config.unwrap().limb_bits, BYTE_LIMB_BITS);
+    }
+
+    /// A single 8-bit AND or XOR of w1 and w2 into w3
+    fn bitwise_circuit(and: bool, output: u128) -> NoirHalo2Translator<Fr> {
+        let input = |index: u32| FunctionInput { witness: Witness(index), num_bits: 8 };
+        let (lhs, rhs, output_witness) = (input(1), input(2), Witness(3));
+        let call = if and {
+            BlackBoxFuncCall::AND { lhs, rhs, output: output_witness }
+        } else {
+            BlackBoxFuncCall::XOR { lhs, rhs, output: output_witness }
+        };
+        let circuit = NoirCircuit {
+            current_witness_index: 3,
+            opcodes: vec![Opcode::BlackBoxFuncCall(call)],
+            ..Default::default()
+        };
+        let witness_values = WitnessMap::from(BTreeMap::from_iter(
+            [0xb5, 0x3c, output]
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| (Witness(i as u32 + 1), FieldElement::from(value))),
+        ));
+        NoirHalo2Translator::<Fr> { circuit, witness_values, _marker: PhantomData::<Fr> }
+    }
+
+    #[test]
+    fn test_bitwise_circuit() {
+        for (and, output, wrong_output) in [(true, 0x34, 0x35), (false, 0x89, 0x34)] {
+            let translator = bitwise_circuit(and, output);
+            let dimension = DimensionMeasurement::measure(&translator).unwrap();
+            // the nibble tables fit circuits far smaller than those of bytes would
+            assert!(dimension.k() < 16);
+
+            // run mock prover expecting success
+            let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            // run mock prover with a wrong output expecting failure
+            let translator = bitwise_circuit(and, wrong_output);
+            let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    /// Schnorr signature `s || e` of `message` by `private_key` with `nonce`,
+    /// along with the public key
+    fn schnorr_sign(private_key: Fq, nonce: Fq, message: &[u8]) -> (GrumpkinPoint, Vec<u8>) {
+        let be_bytes = |scalar: Fq| {
+            let mut bytes = scalar.to_repr().as_ref().to_vec();
+            bytes.reverse();
+            bytes
+        };
+        let public_key = GrumpkinPoint::generator().mul_bytes(&be_bytes(private_key));
+        let nonce_point = GrumpkinPoint::generator().mul_bytes(&be_bytes(nonce));
+        let e = acvm_blackbox_solver::blake2s(&schnorr_challenge_message(
+            &public_key,
+            &nonce_point,
+            message,
+        ))
+        .unwrap();
+        let limb = |i: usize| u64::from_be_bytes(e[24 - 8 * i..32 - 8 * i].try_into().unwrap());
+        let e_scalar = Fq::from_raw([limb(0), limb(1), limb(2), limb(3)]);
+
+        let mut signature = be_bytes(nonce - e_scalar * private_key);
+        signature.extend(e);
+        (public_key, signature)
+    }
+
+    /// A Schnorr verification of `signature` of `message` under `public_key`
+    /// into `output`
+    fn schnorr_circuit(
+        public_key: GrumpkinPoint,
+        signature: &[u8],
+        message: &[u8],
+        output: bool,
+    ) -> NoirHalo2Translator<Fr> {
+        let input = |index: usize, num_bits: u32| FunctionInput {
+            witness: Witness(index as u32),
+            num_bits,
+        };
+        let signature_inputs = (0..64).map(|i| input(3 + i, 8)).collect();
+        let message_inputs = (0..message.len()).map(|i| input(67 + i, 8)).collect();
+        let output_witness = Witness(67 + message.len() as u32);
+        let circuit = NoirCircuit {
+            current_witness_index: output_witness.0,
+            opcodes: vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::SchnorrVerify {
+                public_key_x: input(1, 254),
+                public_key_y: input(2, 254),
+                signature: signature_inputs,
+                message: message_inputs,
+                output: output_witness,
+            })],
+            ..Default::default()
+        };
+
+        let to_noir_field = |value: Fr| {
+            FieldElement::from_be_bytes_reduce(&{
+                let mut bytes = value.to_repr().as_ref().to_vec();
+                bytes.reverse();
+                bytes
+            })
+        };
+        let values = [to_noir_field(public_key.x), to_noir_field(public_key.y)]
+            .into_iter()
+            .chain(signature.iter().chain(message).map(|byte| FieldElement::from(*byte as u128)))
+            .chain([FieldElement::from(output as u128)]);
+        let witness_values = WitnessMap::from(BTreeMap::from_iter(
+            values.enumerate().map(|(i, value)| (Witness(i as u32 + 1), value)),
+        ));
+        NoirHalo2Translator::<Fr> { circuit, witness_values, _marker: PhantomData::<Fr> }
+    }
+
+    #[test]
+    fn test_schnorr_verify_circuit() {
+        let message = b"hello world";
+        let (public_key, signature) =
+            schnorr_sign(Fq::from(0x2a2a_2a2a), Fq::from(0x1234_5678_9abc_def0), message);
+        assert!(schnorr_verify(&public_key, &signature, message));
+        assert!(!schnorr_verify(&public_key, &signature, b"hello worle"));
+
+        let translator = schnorr_circuit(public_key, &signature, message, true);
+        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+
+        // the output is constrained to the result of the verification, both for
+        // the signed message and for another one
+        for (message, valid) in [(&message[..], true), (&b"hello worle"[..], false)] {
+            let translator = schnorr_circuit(public_key, &signature, message, valid);
+            let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            let translator = schnorr_circuit(public_key, &signature, message, !valid);
+            let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_vk_stores_opcode_flags() {
+        let translator = packed_circuit(7);
+        let opcode_flags = OpcodeFlags::new(&translator.circuit.opcodes);
+        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+        let params = ParamsKZG::setup(dimension.k(), OsRng);
+        let (_, vk) = halo2_keygen(&translator, &params).unwrap();
+
+        let bytes = halo2_vk_to_bytes(&vk, &opcode_flags);
+        let (read_vk, read_flags) = halo2_vk_from_bytes(&bytes).unwrap();
+        assert_eq!(read_flags, opcode_flags);
+        assert_eq!(read_vk.to_bytes(SerdeFormat::RawBytes), vk.to_bytes(SerdeFormat::RawBytes));
+
+        assert!(halo2_vk_from_bytes(&bytes[..2]).is_err());
+    }
+
+    #[test]
+    fn test_aggregate_native() {
+        let translator = packed_circuit(7);
+        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+        let params = ParamsKZG::<Bn256>::setup(dimension.k(), OsRng);
+        let (pk, vk) = halo2_keygen(&translator, &params).unwrap();
+        let proof = halo2_prove_recursive(translator, &params, &pk, &[]).unwrap();
+        assert!(halo2_verify_recursive(&params, &vk, &proof, &[]).is_ok());
+
+        // the protocol and the proof survive their field encodings
+        let protocol = compile(&params, &vk, Config::kzg().with_num_instance(vec![0]));
+        let mut vk_fields = vk_to_fields(&vk);
+        vk_fields.extend(protocol_to_fields(&protocol));
+        let protocol = protocol_from_fields(&vk_fields, 0).unwrap();
+        let proof =
+            proof_from_fields(&elements_to_fields(&proof_elements(&proof).unwrap()), 0).unwrap();
+
+        let aggregation_object = aggregate_native(&protocol, &[], &proof, None).unwrap();
+        assert!(decide(&params, &aggregation_object));
+
+        // fold the aggregation object with the accumulator of another proof
+        let folded = aggregate_native(&protocol, &[], &proof, Some(&aggregation_object)).unwrap();
+        assert!(decide(&params, &folded));
+
+        // swapping the points of the accumulator fails the pairing check
+        let mut swapped = aggregation_object[8..].to_vec();
+        swapped.extend(&aggregation_object[..8]);
+        assert!(!decide(&params, &swapped));
+    }
+
+    #[test]
+    fn test_verify_rejects_private_aggregation_object() {
+        let input = |index: u32| FunctionInput { witness: Witness(index), num_bits: 254 };
+        let output_aggregation_object: Vec<Witness> =
+            (1..=AGGREGATION_OBJECT_LEN as u32).map(Witness).collect();
+        let circuit = NoirCircuit {
+            current_witness_index: AGGREGATION_OBJECT_LEN as u32 + 1,
+            opcodes: vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RecursiveAggregation {
+                verification_key: vec![],
+                proof: vec![],
+                public_inputs: vec![],
+                key_hash: input(AGGREGATION_OBJECT_LEN as u32 + 1),
+                input_aggregation_object: None,
+                output_aggregation_object: output_aggregation_object.clone(),
+            })],
+            ..Default::default()
+        };
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let mut crs = Vec::new();
+        params.write_custom(&mut crs, SerdeFormat::RawBytes).unwrap();
+
+        // the output aggregation object is not public, so its pairing check
+        // can't be run and the proof is rejected whatever its other inputs
+        let public_inputs = WitnessMap::from(BTreeMap::from_iter(
+            output_aggregation_object.iter().map(|witness| (*witness, FieldElement::one())),
+        ));
+        let backend = PseHalo2::default();
+        assert!(!backend.verify_with_vk(&crs, &[], public_inputs, &circuit, &[], true).unwrap());
+    }
+
+    #[test]
+    fn test_proof_elements() {
+        let translator = packed_circuit(7);
+        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+        let params = ParamsKZG::<Bn256>::setup(dimension.k(), OsRng);
+        let (pk, _) = halo2_keygen(&translator, &params).unwrap();
+
+        // proofs of both transcripts share their layout and thus their encoding
+        let proof = halo2_prove(translator.clone(), &params, &pk, &[]).unwrap();
+        let recursive_proof = halo2_prove_recursive(translator, &params, &pk, &[]).unwrap();
+        let elements = proof_elements(&proof).unwrap();
+        let recursive_elements = proof_elements(&recursive_proof).unwrap();
+        assert_eq!(elements.len(), recursive_elements.len());
+        for (element, recursive_element) in elements.iter().zip(&recursive_elements) {
+            assert_eq!(
+                matches!(element, ProofElement::Point(_)),
+                matches!(recursive_element, ProofElement::Point(_))
+            );
+        }
+        assert!(elements.iter().any(|element| matches!(element, ProofElement::Point(_))));
+        assert!(elements.iter().any(|element| matches!(element, ProofElement::Scalar(_))));
+
+        assert_eq!(fields_to_elements(&elements_to_fields(&elements)), elements);
+        assert!(proof_elements(&proof[..proof.len() - 1]).is_none());
+    }
+
+    #[test]
+    fn test_malformed_inputs_are_errors() {
+        let translator = packed_circuit(7);
+        let circuit = &translator.circuit;
+        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+        let params = ParamsKZG::<Bn256>::setup(dimension.k(), OsRng);
+        let mut crs = Vec::new();
+        params.write_custom(&mut crs, SerdeFormat::RawBytes).unwrap();
+
+        let backend = PseHalo2::default();
+        assert!(backend.preprocess(&crs[..crs.len() / 2], circuit).is_err());
+
+        let (pk, vk) = backend.preprocess(&crs, circuit).unwrap();
+        let witness_values = translator.witness_values.clone();
+        assert!(backend
+            .prove_with_pk(&crs, circuit, witness_values.clone(), &pk[..pk.len() / 2], false)
+            .is_err());
+        let proof = backend.prove_with_pk(&crs, circuit, witness_values, &pk, false).unwrap();
+        assert!(backend
+            .verify_with_vk(&crs, &proof, WitnessMap::new(), circuit, &vk[..vk.len() / 2], false)
+            .is_err());
+        assert!(backend
+            .verify_with_vk(&crs, &proof, WitnessMap::new(), circuit, &vk, false)
+            .unwrap());
+    }
 }
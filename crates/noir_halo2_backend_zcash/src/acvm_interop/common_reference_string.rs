@@ -1,7 +1,17 @@
-use crate::{halo2_params::constuct_halo2_ipa_params, ZcashHalo2};
-use acvm::{acir::circuit::Circuit, async_trait, CommonReferenceString, ProofSystemCompiler};
-use noir_halo2_backend_common::errors::BackendError;
-use zcash_halo2_proofs::{pasta::EqAffine, poly::commitment::Params};
+use crate::{
+    circuit_translator::NoirHalo2Translator, dimension_measure::DimensionMeasurement,
+    halo2_params::constuct_halo2_ipa_params, ZcashHalo2,
+};
+use acvm::{
+    acir::{circuit::Circuit, native_types::WitnessMap},
+    async_trait, CommonReferenceString,
+};
+use noir_halo2_backend_common::errors::{BackendError, Error};
+use std::marker::PhantomData;
+use zcash_halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    poly::commitment::Params,
+};
 
 // TODO(#185): Ensure CRS download works in JS
 #[async_trait(?Send)]
@@ -12,9 +22,15 @@ impl CommonReferenceString for ZcashHalo2 {
         &self,
         circuit: &Circuit,
     ) -> Result<Vec<u8>, Self::Error> {
+        let translator = NoirHalo2Translator::<Fp> {
+            circuit: circuit.clone(),
+            witness_values: WitnessMap::new(),
+            _marker: PhantomData::<Fp>,
+        };
+        let dimension = DimensionMeasurement::measure(&translator).map_err(Error::synthesis)?;
+
         let mut common_reference_string = Vec::new();
-        let params: Params<EqAffine> =
-            constuct_halo2_ipa_params(self.get_exact_circuit_size(circuit)?)?;
+        let params: Params<EqAffine> = constuct_halo2_ipa_params(dimension.k())?;
         params.write(&mut common_reference_string).unwrap();
         // Separated to have nicer coercion on error types
         Ok(common_reference_string)
@@ -26,9 +42,15 @@ impl CommonReferenceString for ZcashHalo2 {
         _common_reference_string: Vec<u8>,
         circuit: &Circuit,
     ) -> Result<Vec<u8>, Self::Error> {
+        let translator = NoirHalo2Translator::<Fp> {
+            circuit: circuit.clone(),
+            witness_values: WitnessMap::new(),
+            _marker: PhantomData::<Fp>,
+        };
+        let dimension = DimensionMeasurement::measure(&translator).map_err(Error::synthesis)?;
+
         let mut common_reference_string = Vec::new();
-        let params: Params<EqAffine> =
-            constuct_halo2_ipa_params(self.get_exact_circuit_size(circuit)?)?;
+        let params: Params<EqAffine> = constuct_halo2_ipa_params(dimension.k())?;
         params.write(&mut common_reference_string).unwrap();
 
         Ok(common_reference_string)
@@ -1,6 +1,12 @@
 use crate::{
     circuit_translator::NoirHalo2Translator,
-    halo2_plonk_api::{halo2_keygen, halo2_prove, halo2_verify},
+    constrains::noir_field_to_halo2_field,
+    dimension_measure::DimensionMeasurement,
+    halo2_params::halo2_ipa_params_k,
+    halo2_plonk_api::{
+        halo2_keygen, halo2_pk_from_bytes, halo2_pk_to_bytes, halo2_prove, halo2_verify,
+        halo2_vk_from_bytes, halo2_vk_to_bytes,
+    },
 };
 use acvm::{
     acir::{
@@ -10,7 +16,10 @@ use acvm::{
     },
     FieldElement, Language, ProofSystemCompiler,
 };
-use noir_halo2_backend_common::errors::BackendError;
+use noir_halo2_backend_common::{
+    errors::{BackendError, Error},
+    utils::ensure_opcodes_supported,
+};
 use std::marker::PhantomData;
 use zcash_halo2_proofs::{
     pasta::{EqAffine, Fp},
@@ -22,8 +31,18 @@ use crate::ZcashHalo2;
 impl ProofSystemCompiler for ZcashHalo2 {
     type Error = BackendError;
 
+    /// Get the size of the circuit
     fn get_exact_circuit_size(&self, circuit: &NoirCircuit) -> Result<u32, BackendError> {
-        Ok(circuit.opcodes.len() as u32)
+        let translator = NoirHalo2Translator::<Fp> {
+            circuit: circuit.clone(),
+            witness_values: WitnessMap::new(),
+            _marker: PhantomData::<Fp>,
+        };
+
+        let dimension = DimensionMeasurement::measure(&translator).map_err(Error::synthesis)?;
+        let k = dimension.k();
+
+        Ok(1 << k)
     }
 
     fn preprocess(
@@ -31,17 +50,19 @@ impl ProofSystemCompiler for ZcashHalo2 {
         mut common_reference_string: &[u8],
         circuit: &NoirCircuit,
     ) -> Result<(Vec<u8>, Vec<u8>), BackendError> {
+        ensure_opcodes_supported(circuit, |opcode| self.supports_opcode(opcode))?;
+
         let translator = NoirHalo2Translator::<Fp> {
             circuit: circuit.clone(),
             witness_values: WitnessMap::new(),
             _marker: PhantomData::<Fp>,
         };
 
-        let params = Params::<EqAffine>::read(&mut common_reference_string).unwrap();
-        let (_pk, _vk) = halo2_keygen(&translator, &params);
+        let params =
+            Params::<EqAffine>::read(&mut common_reference_string).map_err(Error::ReadCRS)?;
+        let (pk, vk) = halo2_keygen(&translator, &params).map_err(Error::synthesis)?;
 
-        // can't serialize pk vk to bytes
-        Ok((Vec::new(), Vec::new()))
+        Ok((halo2_pk_to_bytes(circuit, &pk), halo2_vk_to_bytes(circuit, &vk)))
     }
 
     fn prove_with_pk(
@@ -49,20 +70,40 @@ impl ProofSystemCompiler for ZcashHalo2 {
         mut common_reference_string: &[u8],
         circuit: &NoirCircuit,
         witness_values: WitnessMap,
-        _proving_key: &[u8],
+        proving_key: &[u8],
         _is_recursive: bool,
     ) -> Result<Vec<u8>, BackendError> {
+        ensure_opcodes_supported(circuit, |opcode| self.supports_opcode(opcode))?;
+
         let translator = NoirHalo2Translator::<Fp> {
             circuit: circuit.clone(),
             witness_values,
             _marker: PhantomData::<Fp>,
         };
 
-        let params = Params::<EqAffine>::read(&mut common_reference_string).unwrap();
+        let k = DimensionMeasurement::measure(&translator).map_err(Error::synthesis)?.k();
+        let params_k = halo2_ipa_params_k(common_reference_string)?;
+        if params_k < k {
+            return Err(Error::CRSTooSmall(params_k, k).into());
+        }
+
+        let params =
+            Params::<EqAffine>::read(&mut common_reference_string).map_err(Error::ReadCRS)?;
 
-        let (pk, _vk) = halo2_keygen(&translator, &params);
+        let pk = halo2_pk_from_bytes(proving_key, &translator, &params)?;
 
-        let proof = halo2_prove(translator, &params, &pk);
+        let instance: Vec<Fp> = circuit
+            .public_inputs()
+            .indices()
+            .iter()
+            .map(|index| match translator.witness_values.get_index(*index) {
+                Some(val) => noir_field_to_halo2_field(*val),
+                None => noir_field_to_halo2_field(FieldElement::zero()),
+            })
+            .collect();
+
+        let proof =
+            halo2_prove(translator, &params, &pk, &instance[..]).map_err(Error::synthesis)?;
 
         Ok(proof)
     }
@@ -73,20 +114,24 @@ impl ProofSystemCompiler for ZcashHalo2 {
         proof: &[u8],
         public_inputs: WitnessMap,
         circuit: &NoirCircuit,
-        _verification_key: &[u8],
+        verification_key: &[u8],
         _is_recursive: bool,
     ) -> Result<bool, BackendError> {
         let translator = NoirHalo2Translator::<Fp> {
             circuit: circuit.clone(),
-            witness_values: public_inputs,
+            witness_values: WitnessMap::new(),
             _marker: PhantomData::<Fp>,
         };
 
-        let params = Params::<EqAffine>::read(&mut common_reference_string).unwrap();
+        let params =
+            Params::<EqAffine>::read(&mut common_reference_string).map_err(Error::ReadCRS)?;
+
+        let vk = halo2_vk_from_bytes(verification_key, &translator, &params)?;
 
-        let (_, vk) = halo2_keygen(&translator, &params);
+        let instance: Vec<Fp> =
+            public_inputs.into_iter().map(|(_, el)| noir_field_to_halo2_field(el)).collect();
 
-        Ok(halo2_verify(&params, &vk, proof).is_ok())
+        Ok(halo2_verify(&params, &vk, proof, &instance[..]).is_ok())
     }
 
     fn np_language(&self) -> Language {
@@ -98,10 +143,9 @@ impl ProofSystemCompiler for ZcashHalo2 {
             Opcode::Arithmetic(_) => true,
             Opcode::Directive(_) | Opcode::Brillig(_) => true,
             Opcode::BlackBoxFuncCall(func) => match func.get_black_box_func() {
-                BlackBoxFunc::AND | BlackBoxFunc::RANGE => true,
+                BlackBoxFunc::AND | BlackBoxFunc::XOR | BlackBoxFunc::RANGE => true,
 
-                BlackBoxFunc::XOR
-                | BlackBoxFunc::SHA256
+                BlackBoxFunc::SHA256
                 | BlackBoxFunc::Blake2s
                 | BlackBoxFunc::Pedersen
                 | BlackBoxFunc::HashToField128Security
@@ -121,7 +165,7 @@ impl ProofSystemCompiler for ZcashHalo2 {
         _proof: &[u8],
         _public_inputs: WitnessMap,
     ) -> Result<Vec<FieldElement>, Self::Error> {
-        panic!("vk_as_fields not supported in this backend");
+        Err(Error::UnsupportedFeature("proof_as_fields").into())
     }
 
     fn vk_as_fields(
@@ -129,6 +173,6 @@ impl ProofSystemCompiler for ZcashHalo2 {
         _common_reference_string: &[u8],
         _verification_key: &[u8],
     ) -> Result<(Vec<FieldElement>, FieldElement), Self::Error> {
-        panic!("vk_as_fields not supported in this backend");
+        Err(Error::UnsupportedFeature("vk_as_fields").into())
     }
 }
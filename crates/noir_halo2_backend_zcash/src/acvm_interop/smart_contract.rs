@@ -1,6 +1,6 @@
 use crate::ZcashHalo2;
 use acvm::{acir::circuit::Circuit, SmartContract};
-use noir_halo2_backend_common::errors::BackendError;
+use noir_halo2_backend_common::errors::{BackendError, Error};
 
 impl SmartContract for ZcashHalo2 {
     type Error = BackendError;
@@ -10,6 +10,7 @@ impl SmartContract for ZcashHalo2 {
         _circuit: &Circuit,
         _verification_key: &[u8],
     ) -> Result<String, Self::Error> {
-        panic!("ethereum solidity verifier not supported for halo2-ipa")
+        // ethereum solidity verifiers need a pairing friendly curve, unlike halo2-ipa
+        Err(Error::UnsupportedFeature("eth_contract_from_vk").into())
     }
 }
@@ -0,0 +1,98 @@
+use crate::halo2_plonk_api::StandardCs;
+use acvm::acir::native_types::Witness;
+use std::{
+    collections::{btree_map, BTreeMap},
+    ops::Index,
+};
+use zcash_halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Cell, Layouter},
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct AssignedMap(BTreeMap<Witness, Vec<Cell>>);
+
+impl AssignedMap {
+    pub fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    #[allow(dead_code)]
+    pub fn get(&self, witness: &Witness) -> Option<&Vec<Cell>> {
+        self.0.get(witness)
+    }
+
+    pub fn get_index(&self, index: u32) -> Option<&Vec<Cell>> {
+        self.0.get(&index.into())
+    }
+
+    pub fn contains_key(&self, witness: &Witness) -> bool {
+        self.0.contains_key(witness)
+    }
+
+    pub fn insert(&mut self, key: Witness, value: Cell) {
+        match self.0.get_mut(&key) {
+            Some(vec) => vec.push(value),
+            None => {
+                self.0.insert(key, vec![value]);
+            }
+        };
+    }
+
+    /// Check if a given acir witness index needs a copy constraint when assigning a witness to a
+    /// halo2 cell. If so, perform an equality constraint on a given cell if a given witness
+    /// appears in the assignment map
+    //
+    // @param cs - the standard plonk chip copying the cells
+    // @param layouter - the layouter of the region the copy constraint is assigned in
+    // @param witness - the acir witness index to check for
+    // @param cell - the newly assigned cell to copy constrain with a cell stored in the assignment
+    // map @return - success if copy constraint operation succeeds
+    pub fn check_and_copy<F: Field>(
+        &self,
+        cs: &impl StandardCs<F>,
+        layouter: &mut impl Layouter<F>,
+        witness: u32,
+        cell: Cell,
+    ) -> Result<(), zcash_halo2_proofs::plonk::Error> {
+        if self.contains_key(&Witness(witness)) {
+            let witness_cell = self.get_index(witness).unwrap().last().unwrap();
+            cs.copy(layouter, *witness_cell, cell)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Index<&Witness> for AssignedMap {
+    type Output = Vec<Cell>;
+
+    fn index(&self, index: &Witness) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+pub struct IntoIter(btree_map::IntoIter<Witness, Vec<Cell>>);
+
+impl Iterator for IntoIter {
+    type Item = (Witness, Vec<Cell>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(witness, cell)| (witness, cell))
+    }
+}
+
+impl IntoIterator for AssignedMap {
+    type Item = (Witness, Vec<Cell>);
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.0.into_iter())
+    }
+}
+
+impl From<BTreeMap<Witness, Vec<Cell>>> for AssignedMap {
+    fn from(value: BTreeMap<Witness, Vec<Cell>>) -> Self {
+        Self(value)
+    }
+}
@@ -0,0 +1,237 @@
+use super::{decompose, running_sums};
+use zcash_halo2_proofs::{
+    circuit::{Cell, Layouter, Value},
+    pasta::Fp,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+/// Bit length of the limbs looked up in the bitwise tables, kept to a nibble
+/// so that the table of every pair of limbs holds no more than 2^8 rows
+pub(crate) const BITWISE_LIMB_BITS: usize = 4;
+
+/// Bitwise operations backed by the lookup tables
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BitwiseOp {
+    And,
+    Xor,
+}
+
+impl BitwiseOp {
+    fn apply(&self, lhs: u64, rhs: u64) -> u64 {
+        match self {
+            BitwiseOp::And => lhs & rhs,
+            BitwiseOp::Xor => lhs ^ rhs,
+        }
+    }
+}
+
+/// Configuration of the bitwise chip.
+///
+/// Each operand is decomposed into little-endian limbs of `BITWISE_LIMB_BITS` bits
+/// with a running sum, and each triple of limbs is looked up in a table
+/// holding every `(lhs, rhs, lhs & rhs, lhs ^ rhs)` combination.
+#[derive(Clone, Debug)]
+pub struct BitwiseConfig {
+    lhs: Column<Advice>,
+    rhs: Column<Advice>,
+    out: Column<Advice>,
+    lhs_limb: Column<Advice>,
+    rhs_limb: Column<Advice>,
+    out_limb: Column<Advice>,
+
+    q_running: Selector,
+    q_last: Selector,
+    q_and: Selector,
+    q_xor: Selector,
+
+    table_lhs: TableColumn,
+    table_rhs: TableColumn,
+    table_and: TableColumn,
+    table_xor: TableColumn,
+}
+
+#[derive(Clone, Debug)]
+pub struct BitwiseChip {
+    config: BitwiseConfig,
+}
+
+impl BitwiseChip {
+    pub fn new(config: BitwiseConfig) -> Self {
+        BitwiseChip { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<Fp>) -> BitwiseConfig {
+        let lhs = meta.advice_column();
+        let rhs = meta.advice_column();
+        let out = meta.advice_column();
+        let lhs_limb = meta.advice_column();
+        let rhs_limb = meta.advice_column();
+        let out_limb = meta.advice_column();
+
+        meta.enable_equality(lhs);
+        meta.enable_equality(rhs);
+        meta.enable_equality(out);
+
+        let q_running = meta.selector();
+        let q_last = meta.selector();
+        let q_and = meta.complex_selector();
+        let q_xor = meta.complex_selector();
+
+        let table_lhs = meta.lookup_table_column();
+        let table_rhs = meta.lookup_table_column();
+        let table_and = meta.lookup_table_column();
+        let table_xor = meta.lookup_table_column();
+
+        // acc_i = acc_{i+1} * 2^BITWISE_LIMB_BITS + limb_i, and the last
+        // accumulator is the most significant limb itself
+        meta.create_gate("bitwise limb decomposition", |meta| {
+            let q_running = meta.query_selector(q_running);
+            let q_last = meta.query_selector(q_last);
+            let radix = Expression::Constant(Fp::from(1u64 << BITWISE_LIMB_BITS));
+
+            let mut constraints = Vec::new();
+            for (acc, limb) in [(lhs, lhs_limb), (rhs, rhs_limb), (out, out_limb)] {
+                let acc_cur = meta.query_advice(acc, Rotation::cur());
+                let acc_next = meta.query_advice(acc, Rotation::next());
+                let limb = meta.query_advice(limb, Rotation::cur());
+
+                constraints.push(
+                    q_running.clone() * (acc_cur.clone() - acc_next * radix.clone() - limb.clone()),
+                );
+                constraints.push(q_last.clone() * (acc_cur - limb));
+            }
+            constraints
+        });
+
+        for (selector, table_out) in [(q_and, table_and), (q_xor, table_xor)] {
+            meta.lookup(|meta| {
+                let q = meta.query_selector(selector);
+                let lhs_limb = meta.query_advice(lhs_limb, Rotation::cur());
+                let rhs_limb = meta.query_advice(rhs_limb, Rotation::cur());
+                let out_limb = meta.query_advice(out_limb, Rotation::cur());
+
+                vec![
+                    (q.clone() * lhs_limb, table_lhs),
+                    (q.clone() * rhs_limb, table_rhs),
+                    (q * out_limb, table_out),
+                ]
+            });
+        }
+
+        BitwiseConfig {
+            lhs,
+            rhs,
+            out,
+            lhs_limb,
+            rhs_limb,
+            out_limb,
+            q_running,
+            q_last,
+            q_and,
+            q_xor,
+            table_lhs,
+            table_rhs,
+            table_and,
+            table_xor,
+        }
+    }
+
+    /// Assign `lhs op rhs` for operands of `num_bits` bits, returning the cells
+    /// holding the full lhs, rhs and output values so they can be copy
+    /// constrained to the acir witnesses
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<Fp>,
+        op: BitwiseOp,
+        lhs: Fp,
+        rhs: Fp,
+        num_bits: u32,
+    ) -> Result<(Cell, Cell, Cell), Error> {
+        let num_limbs = ((num_bits as usize + BITWISE_LIMB_BITS - 1) / BITWISE_LIMB_BITS).max(1);
+
+        let lhs_limbs = decompose(lhs, num_limbs, BITWISE_LIMB_BITS);
+        let rhs_limbs = decompose(rhs, num_limbs, BITWISE_LIMB_BITS);
+        let out_limbs: Vec<u64> =
+            lhs_limbs.iter().zip(rhs_limbs.iter()).map(|(l, r)| op.apply(*l, *r)).collect();
+
+        let lhs_accs = running_sums(&lhs_limbs, BITWISE_LIMB_BITS);
+        let rhs_accs = running_sums(&rhs_limbs, BITWISE_LIMB_BITS);
+        let out_accs = running_sums(&out_limbs, BITWISE_LIMB_BITS);
+
+        layouter.assign_region(
+            || "bitwise",
+            |mut region| {
+                let mut assigned = None;
+                for offset in 0..num_limbs {
+                    if offset == num_limbs - 1 {
+                        self.config.q_last.enable(&mut region, offset)?;
+                    } else {
+                        self.config.q_running.enable(&mut region, offset)?;
+                    }
+                    match op {
+                        BitwiseOp::And => self.config.q_and.enable(&mut region, offset)?,
+                        BitwiseOp::Xor => self.config.q_xor.enable(&mut region, offset)?,
+                    }
+
+                    let mut cells = Vec::with_capacity(3);
+                    for (name, acc, limb, accs, limbs) in [
+                        ("lhs", self.config.lhs, self.config.lhs_limb, &lhs_accs, &lhs_limbs),
+                        ("rhs", self.config.rhs, self.config.rhs_limb, &rhs_accs, &rhs_limbs),
+                        ("out", self.config.out, self.config.out_limb, &out_accs, &out_limbs),
+                    ] {
+                        let acc = region.assign_advice(
+                            || format!("{name} acc"),
+                            acc,
+                            offset,
+                            || Value::known(accs[offset]),
+                        )?;
+                        region.assign_advice(
+                            || format!("{name} limb"),
+                            limb,
+                            offset,
+                            || Value::known(Fp::from(limbs[offset])),
+                        )?;
+                        cells.push(acc.cell());
+                    }
+
+                    if offset == 0 {
+                        assigned = Some((cells[0], cells[1], cells[2]));
+                    }
+                }
+
+                Ok(assigned.expect("at least one limb is assigned"))
+            },
+        )
+    }
+
+    /// Load the `(lhs, rhs, lhs & rhs, lhs ^ rhs)` table for every pair of limbs
+    pub(crate) fn load_table(&self, layouter: &mut impl Layouter<Fp>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "bitwise table",
+            |mut table| {
+                let limb_range = 1u64 << BITWISE_LIMB_BITS;
+                let mut offset = 0;
+                for lhs in 0..limb_range {
+                    for rhs in 0..limb_range {
+                        for (name, column, value) in [
+                            ("lhs", self.config.table_lhs, lhs),
+                            ("rhs", self.config.table_rhs, rhs),
+                            ("and", self.config.table_and, BitwiseOp::And.apply(lhs, rhs)),
+                            ("xor", self.config.table_xor, BitwiseOp::Xor.apply(lhs, rhs)),
+                        ] {
+                            table.assign_cell(
+                                || name,
+                                column,
+                                offset,
+                                || Value::known(Fp::from(value)),
+                            )?;
+                        }
+                        offset += 1;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
@@ -0,0 +1,28 @@
+use zcash_halo2_proofs::{
+    arithmetic::Field,
+    pasta::{group::ff::PrimeField, Fp},
+};
+
+pub(crate) mod bitwise;
+pub(crate) mod range;
+
+/// Little-endian limbs of `limb_bits` bits
+fn decompose(value: Fp, num_limbs: usize, limb_bits: usize) -> Vec<u64> {
+    let repr = value.to_repr();
+    let bit = |i: usize| repr.as_ref().get(i / 8).map_or(0, |byte| (*byte as u64 >> (i % 8)) & 1);
+    (0..num_limbs)
+        .map(|limb| (0..limb_bits).map(|i| bit(limb * limb_bits + i) << i).sum())
+        .collect()
+}
+
+/// Running sums of the limbs, from the full value down to the most significant limb
+fn running_sums(limbs: &[u64], limb_bits: usize) -> Vec<Fp> {
+    let radix = Fp::from(1u64 << limb_bits);
+    let mut accs = vec![Fp::ZERO; limbs.len()];
+    let mut acc = Fp::ZERO;
+    for (i, limb) in limbs.iter().enumerate().rev() {
+        acc = acc * radix + Fp::from(*limb);
+        accs[i] = acc;
+    }
+    accs
+}
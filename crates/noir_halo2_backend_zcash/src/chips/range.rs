@@ -0,0 +1,165 @@
+use super::{decompose, running_sums};
+use zcash_halo2_proofs::{
+    circuit::{Cell, Layouter, Value},
+    pasta::Fp,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+/// Bit length of the limbs looked up in the range table
+pub(crate) const RANGE_LIMB_BITS: usize = 8;
+
+/// Configuration of the range chip.
+///
+/// A value is decomposed into little-endian limbs of `RANGE_LIMB_BITS` bits
+/// with a running sum, and each limb is looked up in a table holding every
+/// limb. The most significant limb is looked up again shifted by the bits
+/// it misses to a full limb, so that it holds no more than the bits left.
+#[derive(Clone, Debug)]
+pub struct RangeConfig {
+    value: Column<Advice>,
+    limb: Column<Advice>,
+    shift: Column<Fixed>,
+
+    q_running: Selector,
+    q_last: Selector,
+    q_lookup: Selector,
+
+    table: TableColumn,
+}
+
+#[derive(Clone, Debug)]
+pub struct RangeChip {
+    config: RangeConfig,
+}
+
+impl RangeChip {
+    pub fn new(config: RangeConfig) -> Self {
+        RangeChip { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<Fp>) -> RangeConfig {
+        let value = meta.advice_column();
+        let limb = meta.advice_column();
+        let shift = meta.fixed_column();
+
+        meta.enable_equality(value);
+
+        let q_running = meta.selector();
+        let q_last = meta.selector();
+        let q_lookup = meta.complex_selector();
+
+        let table = meta.lookup_table_column();
+
+        // acc_i = acc_{i+1} * 2^RANGE_LIMB_BITS + limb_i, and the last
+        // accumulator is the most significant limb itself
+        meta.create_gate("range limb decomposition", |meta| {
+            let q_running = meta.query_selector(q_running);
+            let q_last = meta.query_selector(q_last);
+            let radix = Expression::Constant(Fp::from(1u64 << RANGE_LIMB_BITS));
+
+            let acc_cur = meta.query_advice(value, Rotation::cur());
+            let acc_next = meta.query_advice(value, Rotation::next());
+            let limb = meta.query_advice(limb, Rotation::cur());
+
+            vec![
+                q_running * (acc_cur.clone() - acc_next * radix - limb.clone()),
+                q_last * (acc_cur - limb),
+            ]
+        });
+
+        meta.lookup(|meta| {
+            let q = meta.query_selector(q_lookup);
+            let limb = meta.query_advice(limb, Rotation::cur());
+
+            vec![(q * limb, table)]
+        });
+
+        meta.lookup(|meta| {
+            let q = meta.query_selector(q_lookup);
+            let limb = meta.query_advice(limb, Rotation::cur());
+            let shift = meta.query_fixed(shift);
+
+            vec![(q * limb * shift, table)]
+        });
+
+        RangeConfig { value, limb, shift, q_running, q_last, q_lookup, table }
+    }
+
+    /// Assign a value constrained to `num_bits` bits, returning the cell
+    /// holding it so it can be copy constrained to its acir witness
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<Fp>,
+        value: Fp,
+        num_bits: u32,
+    ) -> Result<Cell, Error> {
+        let num_bits = num_bits as usize;
+        let num_limbs = ((num_bits + RANGE_LIMB_BITS - 1) / RANGE_LIMB_BITS).max(1);
+        // bits the most significant limb misses to a full limb
+        let missing_bits = num_limbs * RANGE_LIMB_BITS - num_bits;
+
+        let limbs = decompose(value, num_limbs, RANGE_LIMB_BITS);
+        let accs = running_sums(&limbs, RANGE_LIMB_BITS);
+
+        layouter.assign_region(
+            || "range",
+            |mut region| {
+                let mut assigned = None;
+                for (offset, (acc, limb)) in accs.iter().zip(limbs.iter()).enumerate() {
+                    let last = offset == num_limbs - 1;
+                    if last {
+                        self.config.q_last.enable(&mut region, offset)?;
+                    } else {
+                        self.config.q_running.enable(&mut region, offset)?;
+                    }
+                    self.config.q_lookup.enable(&mut region, offset)?;
+
+                    let acc = region.assign_advice(
+                        || "acc",
+                        self.config.value,
+                        offset,
+                        || Value::known(*acc),
+                    )?;
+                    region.assign_advice(
+                        || "limb",
+                        self.config.limb,
+                        offset,
+                        || Value::known(Fp::from(*limb)),
+                    )?;
+                    let shift = if last { 1u64 << missing_bits } else { 1 };
+                    region.assign_fixed(
+                        || "shift",
+                        self.config.shift,
+                        offset,
+                        || Value::known(Fp::from(shift)),
+                    )?;
+
+                    if offset == 0 {
+                        assigned = Some(acc.cell());
+                    }
+                }
+
+                Ok(assigned.expect("at least one limb is assigned"))
+            },
+        )
+    }
+
+    /// Load the table of every limb
+    pub(crate) fn load_table(&self, layouter: &mut impl Layouter<Fp>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "range table",
+            |mut table| {
+                for limb in 0..1u64 << RANGE_LIMB_BITS {
+                    table.assign_cell(
+                        || "limb",
+                        self.config.table,
+                        limb as usize,
+                        || Value::known(Fp::from(limb)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
@@ -1,7 +1,15 @@
-use crate::halo2_plonk_api::{PlonkConfig, StandardPlonk};
+use crate::{
+    assigned_map::AssignedMap,
+    chips::{
+        bitwise::{BitwiseChip, BitwiseOp},
+        range::RangeChip,
+    },
+    halo2_plonk_api::{PlonkConfig, StandardPlonk},
+};
 use acvm::acir::{
     circuit::{opcodes::BlackBoxFuncCall, Circuit as NoirCircuit, Opcode},
     native_types::WitnessMap,
+    BlackBoxFunc,
 };
 use core::panic;
 use std::marker::PhantomData;
@@ -18,6 +26,16 @@ pub struct NoirHalo2Translator<Fr> {
     pub _marker: PhantomData<Fr>,
 }
 
+impl NoirHalo2Translator<Fp> {
+    /// Whether the program calls any of the given black box functions
+    fn uses_black_box_func(&self, funcs: &[BlackBoxFunc]) -> bool {
+        self.circuit.opcodes.iter().any(|opcode| match opcode {
+            Opcode::BlackBoxFuncCall(func) => funcs.contains(&func.get_black_box_func()),
+            _ => false,
+        })
+    }
+}
+
 impl Halo2PlonkCircuit<Fp> for NoirHalo2Translator<Fp> {
     type Config = PlonkConfig;
     type FloorPlanner = SimpleFloorPlanner;
@@ -37,30 +55,58 @@ impl Halo2PlonkCircuit<Fp> for NoirHalo2Translator<Fp> {
         config: Self::Config,
         mut layouter: impl Layouter<Fp>,
     ) -> Result<(), zcash_halo2_proofs::plonk::Error> {
+        let instance = config.instance;
+        let range_chip = RangeChip::new(config.range_config.clone());
+        let bitwise_chip = BitwiseChip::new(config.bitwise_config.clone());
         let cs: StandardPlonk<Fp> = StandardPlonk::new(config);
+        let mut witness_assignments = AssignedMap::new();
+
+        // the lookup tables are only loaded for the programs using them
+        if self.uses_black_box_func(&[BlackBoxFunc::RANGE]) {
+            range_chip.load_table(&mut layouter)?;
+        }
+        if self.uses_black_box_func(&[BlackBoxFunc::AND, BlackBoxFunc::XOR]) {
+            bitwise_chip.load_table(&mut layouter)?;
+        }
+
         for gate in self.circuit.opcodes.iter() {
             match gate {
-                Opcode::Arithmetic(expression) => {
-                    self.add_arithmetic_constrains(expression, &cs, &mut layouter)
-                }
+                Opcode::Arithmetic(expression) => self.add_arithmetic_constrains(
+                    expression,
+                    &cs,
+                    &mut layouter,
+                    &mut witness_assignments,
+                )?,
                 Opcode::BlackBoxFuncCall(gadget_call) => {
                     match gadget_call {
-                        BlackBoxFuncCall::RANGE { input: _ } => {
-                            panic!("range constraint has not yet been implemented")
-                        }
-                        BlackBoxFuncCall::AND { lhs, rhs, output: _ }
-                        | BlackBoxFuncCall::XOR { lhs, rhs, output: _ } => {
+                        BlackBoxFuncCall::RANGE { input } => self.add_range_constrain(
+                            input.witness,
+                            input.num_bits,
+                            &range_chip,
+                            &cs,
+                            &mut layouter,
+                            &mut witness_assignments,
+                        )?,
+                        BlackBoxFuncCall::AND { lhs, rhs, output }
+                        | BlackBoxFuncCall::XOR { lhs, rhs, output } => {
                             assert_eq!(lhs.num_bits, rhs.num_bits);
 
-                            match gadget_call {
-                                BlackBoxFuncCall::AND { .. } => {
-                                    panic!("and has not yet been implemented")
-                                }
-                                BlackBoxFuncCall::XOR { .. } => {
-                                    panic!("xor has not yet been implemented")
-                                }
+                            let op = match gadget_call {
+                                BlackBoxFuncCall::AND { .. } => BitwiseOp::And,
+                                BlackBoxFuncCall::XOR { .. } => BitwiseOp::Xor,
                                 _ => unreachable!("expected either an AND or XOR opcode"),
-                            }
+                            };
+                            self.add_bitwise_constrain(
+                                op,
+                                lhs.witness,
+                                rhs.witness,
+                                *output,
+                                lhs.num_bits,
+                                &bitwise_chip,
+                                &cs,
+                                &mut layouter,
+                                &mut witness_assignments,
+                            )?
                         }
                         BlackBoxFuncCall::SHA256 { .. } => {
                             panic!("sha256 has not yet been implemented")
@@ -119,6 +165,7 @@ impl Halo2PlonkCircuit<Fp> for NoirHalo2Translator<Fp> {
                 }
             }
         }
-        Ok(())
+
+        self.expose_public(instance, &cs, &mut layouter, &mut witness_assignments)
     }
 }
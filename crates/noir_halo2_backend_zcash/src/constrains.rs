@@ -1,13 +1,24 @@
 use super::halo2_plonk_api::NoirConstraint;
 use crate::{
+    assigned_map::AssignedMap,
+    chips::{
+        bitwise::{BitwiseChip, BitwiseOp},
+        range::RangeChip,
+    },
     circuit_translator::NoirHalo2Translator,
     halo2_plonk_api::{PolyTriple, StandardCs},
 };
-use acvm::{acir::native_types::Expression, FieldElement};
+use acvm::{
+    acir::native_types::{Expression, Witness},
+    FieldElement,
+};
 use zcash_halo2_proofs::{
     circuit::{Layouter, Value},
-    pasta::{group::ff::PrimeField, Fp},
-    plonk::Assigned,
+    pasta::{
+        group::ff::{Field, PrimeField},
+        Fp,
+    },
+    plonk::{Assigned, Column, Instance},
 };
 
 impl NoirHalo2Translator<Fp> {
@@ -17,7 +28,8 @@ impl NoirHalo2Translator<Fp> {
         gate: &Expression,
         cs: &impl StandardCs<Fp>,
         layouter: &mut impl Layouter<Fp>,
-    ) {
+        witness_assignments: &mut AssignedMap,
+    ) -> Result<(), zcash_halo2_proofs::plonk::Error> {
         let mut noir_cs = NoirConstraint::default();
         // check mul gate
         if !gate.mul_terms.is_empty() {
@@ -68,11 +80,113 @@ impl NoirHalo2Translator<Fp> {
         let poly_gate =
             PolyTriple::new(a, b, c, qm.into(), ql.into(), qr.into(), qo.into(), qc.into());
 
-        cs.raw_poly(layouter, || poly_gate).unwrap();
+        let (a, b, c) = cs.raw_poly(layouter, || poly_gate)?;
+
+        // copy the cells of the witnesses assigned by previous gates, storing each
+        // before checking the next so that a witness used twice in a gate is copied too
+        for (witness, cell) in [(noir_cs.a, a), (noir_cs.b, b), (noir_cs.c, c)] {
+            witness_assignments.check_and_copy(cs, layouter, witness as u32, cell)?;
+            witness_assignments.insert(Witness(witness as u32), cell);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn add_range_constrain(
+        &self,
+        witness: Witness,
+        num_bits: u32,
+        range_chip: &RangeChip,
+        cs: &impl StandardCs<Fp>,
+        layouter: &mut impl Layouter<Fp>,
+        witness_assignments: &mut AssignedMap,
+    ) -> Result<(), zcash_halo2_proofs::plonk::Error> {
+        let input = noir_field_to_halo2_field(
+            *self.witness_values.get(&witness).unwrap_or(&FieldElement::zero()),
+        );
+
+        let cell = range_chip.assign(layouter, input, num_bits)?;
+        witness_assignments.check_and_copy(cs, layouter, witness.0, cell)?;
+
+        // add to assignment map
+        witness_assignments.insert(witness, cell);
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn add_bitwise_constrain(
+        &self,
+        op: BitwiseOp,
+        lhs: Witness,
+        rhs: Witness,
+        output: Witness,
+        num_bits: u32,
+        bitwise_chip: &BitwiseChip,
+        cs: &impl StandardCs<Fp>,
+        layouter: &mut impl Layouter<Fp>,
+        witness_assignments: &mut AssignedMap,
+    ) -> Result<(), zcash_halo2_proofs::plonk::Error> {
+        let lhs_v = noir_field_to_halo2_field(
+            *self.witness_values.get(&lhs).unwrap_or(&FieldElement::zero()),
+        );
+
+        let rhs_v = noir_field_to_halo2_field(
+            *self.witness_values.get(&rhs).unwrap_or(&FieldElement::zero()),
+        );
+
+        let cells = bitwise_chip.assign(layouter, op, lhs_v, rhs_v, num_bits)?;
+
+        // copy and store each before checking the next, as the lhs and rhs may be the same witness
+        for (witness, cell) in [(lhs, cells.0), (rhs, cells.1), (output, cells.2)] {
+            witness_assignments.check_and_copy(cs, layouter, witness.0, cell)?;
+            witness_assignments.insert(witness, cell);
+        }
+
+        Ok(())
+    }
+
+    /// Copy the cells of the public witnesses to the instance column in the order
+    /// of their indices. A public witness no gate uses is assigned to a gate of
+    /// zero selectors, so that it has a cell to copy.
+    pub(crate) fn expose_public(
+        &self,
+        instance: Column<Instance>,
+        cs: &impl StandardCs<Fp>,
+        layouter: &mut impl Layouter<Fp>,
+        witness_assignments: &mut AssignedMap,
+    ) -> Result<(), zcash_halo2_proofs::plonk::Error> {
+        for (i, index) in self.circuit.public_inputs().indices().into_iter().enumerate() {
+            let cell = match witness_assignments.get_index(index) {
+                Some(cells) => *cells.last().unwrap(),
+                None => {
+                    let value: Value<Assigned<_>> = Value::known(noir_field_to_halo2_field(
+                        *self.witness_values.get_index(index).unwrap_or(&FieldElement::zero()),
+                    ))
+                    .into();
+                    let zero = Assigned::from(Fp::ZERO);
+                    let poly_gate = PolyTriple::new(
+                        value,
+                        Value::known(zero),
+                        Value::known(zero),
+                        zero,
+                        zero,
+                        zero,
+                        zero,
+                        zero,
+                    );
+                    let (cell, _, _) = cs.raw_poly(layouter, || poly_gate)?;
+                    witness_assignments.insert(Witness(index), cell);
+                    cell
+                }
+            };
+            layouter.constrain_instance(cell, instance, i)?;
+        }
+        Ok(())
     }
 }
 
-fn noir_field_to_halo2_field(noir_ele: FieldElement) -> Fp {
+pub(crate) fn noir_field_to_halo2_field(noir_ele: FieldElement) -> Fp {
     let mut bytes = noir_ele.to_be_bytes();
     bytes.reverse();
     let mut halo_ele: [u8; 32] = [0; 32];
@@ -1,6 +1,17 @@
 use noir_halo2_backend_common::errors::Error;
+use std::io;
 use zcash_halo2_proofs::{pasta::EqAffine, poly::commitment::Params};
 
-pub(crate) fn constuct_halo2_ipa_params(num_points: u32) -> Result<Params<EqAffine>, Error> {
-    Ok(Params::new(num_points))
+/// Constructs IPA parameters for circuits of `2^k` rows
+pub(crate) fn constuct_halo2_ipa_params(k: u32) -> Result<Params<EqAffine>, Error> {
+    Ok(Params::new(k))
+}
+
+/// Reads the `k` of the IPA parameters of a common reference string,
+/// which `Params::write` serializes before their points
+pub(crate) fn halo2_ipa_params_k(common_reference_string: &[u8]) -> Result<u32, Error> {
+    let k = common_reference_string
+        .get(..4)
+        .ok_or_else(|| Error::ReadCRS(io::ErrorKind::UnexpectedEof.into()))?;
+    Ok(u32::from_le_bytes(k.try_into().unwrap()))
 }
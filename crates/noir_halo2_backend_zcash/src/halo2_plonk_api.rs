@@ -1,15 +1,24 @@
-use crate::circuit_translator::NoirHalo2Translator;
-use acvm::FieldElement;
+use crate::{
+    chips::{
+        bitwise::{BitwiseChip, BitwiseConfig},
+        range::{RangeChip, RangeConfig},
+    },
+    circuit_translator::NoirHalo2Translator,
+};
+use acvm::{acir::circuit::Circuit as NoirCircuit, FieldElement};
+use blake2b_simd::Params as Blake2bParams;
+use noir_halo2_backend_common::errors::Error;
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
-use std::marker::PhantomData;
+use std::{io, marker::PhantomData};
 use zcash_halo2_proofs::{
     arithmetic::Field,
     circuit::{Cell, Layouter, Value},
     pasta::{EqAffine, Fp},
     plonk::{
         create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Assigned, Column,
-        ConstraintSystem, Fixed, ProvingKey, SingleVerifier, VerifyingKey,
+        ConstraintSystem, Error as Halo2Error, Fixed, Instance, ProvingKey, SingleVerifier,
+        VerifyingKey,
     },
     poly::{commitment::Params, Rotation},
     transcript::{Blake2bRead, Blake2bWrite, Challenge255},
@@ -18,47 +27,170 @@ use zcash_halo2_proofs::{
 pub fn halo2_keygen(
     circuit: &NoirHalo2Translator<Fp>,
     params: &Params<EqAffine>,
-) -> (ProvingKey<EqAffine>, VerifyingKey<EqAffine>) {
-    let vk = keygen_vk(params, circuit).expect("keygen_vk should not fail");
+) -> Result<(ProvingKey<EqAffine>, VerifyingKey<EqAffine>), Halo2Error> {
+    let vk = keygen_vk(params, circuit)?;
     let vk_return = vk.clone();
-    let pk = keygen_pk(params, vk, circuit).expect("keygen_pk should not fail");
-    (pk, vk_return)
+    let pk = keygen_pk(params, vk, circuit)?;
+    Ok((pk, vk_return))
 }
 
 pub fn halo2_prove(
     circuit: NoirHalo2Translator<Fp>,
     params: &Params<EqAffine>,
     pk: &ProvingKey<EqAffine>,
-) -> Vec<u8> {
+    public_inputs: &[Fp],
+) -> Result<Vec<u8>, Halo2Error> {
     let rng = OsRng;
     let mut transcript: Blake2bWrite<Vec<u8>, _, Challenge255<_>> =
         Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
-    create_proof(params, pk, &[circuit], &[&[]], rng, &mut transcript)
-        .expect("proof generation should not fail");
-    transcript.finalize()
+    create_proof(params, pk, &[circuit], &[&[public_inputs]], rng, &mut transcript)?;
+    Ok(transcript.finalize())
 }
 
 pub fn halo2_verify(
     params: &Params<EqAffine>,
     vk: &VerifyingKey<EqAffine>,
     proof: &[u8],
-) -> Result<(), zcash_halo2_proofs::plonk::Error> {
+    public_inputs: &[Fp],
+) -> Result<(), Halo2Error> {
     let strategy = SingleVerifier::new(params);
     let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
-    verify_proof(params, vk, strategy, &[&[]], &mut transcript)
+    verify_proof(params, vk, strategy, &[&[public_inputs]], &mut transcript)
+}
+
+/// Version of the encoding of the keys, to be bumped whenever
+/// the encoding or the layout of the circuits changes
+const KEY_VERSION: u8 = 2;
+
+/// Bytes of the digest of the program a key is generated for
+const CIRCUIT_DIGEST_LEN: usize = 32;
+
+/// Digest of the ACIR of a program, identifying the circuit of its keys
+fn circuit_digest(circuit: &NoirCircuit) -> [u8; CIRCUIT_DIGEST_LEN] {
+    let mut bytes = Vec::new();
+    circuit.write(&mut bytes).expect("circuit should serialize");
+    let digest = Blake2bParams::new()
+        .hash_length(CIRCUIT_DIGEST_LEN)
+        .personal(b"NoirHalo2Circuit")
+        .hash(&bytes);
+    digest.as_bytes().try_into().expect("digest should have the requested length")
+}
+
+/// Pinned data of a Verifying Key, its domain, constraint system and
+/// commitments, which halo2 hashes into the transcript the same way
+fn pinned_vk(vk: &VerifyingKey<EqAffine>) -> Vec<u8> {
+    format!("{:?}", vk.pinned()).into_bytes()
+}
+
+/// Serialize a Verifying Key as the version of the encoding, the digest of the
+/// program it was generated for and its pinned data. halo2 can't read keys
+/// back, so they are rebuilt from the program and checked against these bytes.
+pub fn halo2_vk_to_bytes(circuit: &NoirCircuit, vk: &VerifyingKey<EqAffine>) -> Vec<u8> {
+    let pinned = pinned_vk(vk);
+    let mut bytes = vec![KEY_VERSION];
+    bytes.extend(circuit_digest(circuit));
+    bytes.extend((pinned.len() as u32).to_le_bytes());
+    bytes.extend(pinned);
+    bytes
+}
+
+/// Serialize a Proving Key as its Verifying Key, the proving key
+/// being rebuilt from the same circuit once the latter is checked
+pub fn halo2_pk_to_bytes(circuit: &NoirCircuit, pk: &ProvingKey<EqAffine>) -> Vec<u8> {
+    halo2_vk_to_bytes(circuit, pk.get_vk())
 }
 
+/// Rebuild the Verifying Key serialized by `halo2_vk_to_bytes`. The version and
+/// the program are checked before the key is generated, and the generated key
+/// is checked against the pinned data, which covers the parameters.
+pub fn halo2_vk_from_bytes(
+    bytes: &[u8],
+    circuit: &NoirHalo2Translator<Fp>,
+    params: &Params<EqAffine>,
+) -> Result<VerifyingKey<EqAffine>, Error> {
+    rebuild_vk(bytes, circuit, params, Error::ReadVerificationKey)
+}
+
+/// Rebuild the Proving Key serialized by `halo2_pk_to_bytes`,
+/// from the Verifying Key it was serialized as
+pub fn halo2_pk_from_bytes(
+    bytes: &[u8],
+    circuit: &NoirHalo2Translator<Fp>,
+    params: &Params<EqAffine>,
+) -> Result<ProvingKey<EqAffine>, Error> {
+    let vk = rebuild_vk(bytes, circuit, params, Error::ReadProvingKey)?;
+    keygen_pk(params, vk, circuit).map_err(Error::synthesis)
+}
+
+fn rebuild_vk(
+    bytes: &[u8],
+    circuit: &NoirHalo2Translator<Fp>,
+    params: &Params<EqAffine>,
+    read_error: fn(io::Error) -> Error,
+) -> Result<VerifyingKey<EqAffine>, Error> {
+    let invalid = |msg: &str| read_error(io::Error::new(io::ErrorKind::InvalidData, msg));
+
+    let key = KeyBytes::read(bytes).map_err(read_error)?;
+    if key.version != KEY_VERSION {
+        return Err(invalid("unsupported key version"));
+    }
+    if key.circuit_digest != circuit_digest(&circuit.circuit).as_slice() {
+        return Err(invalid("key generated for another program"));
+    }
+
+    let vk = keygen_vk(params, circuit).map_err(Error::synthesis)?;
+    if pinned_vk(&vk) != key.pinned_vk {
+        return Err(invalid("key generated with other parameters"));
+    }
+    Ok(vk)
+}
+
+/// Fields of a key serialized by `halo2_vk_to_bytes`
+struct KeyBytes<'a> {
+    version: u8,
+    circuit_digest: &'a [u8],
+    pinned_vk: &'a [u8],
+}
+
+impl<'a> KeyBytes<'a> {
+    fn read(bytes: &'a [u8]) -> io::Result<Self> {
+        let mut bytes = bytes;
+        let version = take(&mut bytes, 1)?[0];
+        let circuit_digest = take(&mut bytes, CIRCUIT_DIGEST_LEN)?;
+        let len = u32::from_le_bytes(take(&mut bytes, 4)?.try_into().unwrap()) as usize;
+        let pinned_vk = take(&mut bytes, len)?;
+
+        Ok(KeyBytes { version, circuit_digest, pinned_vk })
+    }
+}
+
+/// Split the first `len` bytes off `bytes`
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if bytes.len() < len {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated key"));
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
+
+/// Columns of the standard plonk gate, along with the
+/// instance column the public inputs are copied to
 #[derive(Clone)]
 pub struct PlonkConfig {
     a: Column<Advice>,
     b: Column<Advice>,
     c: Column<Advice>,
+    pub(crate) instance: Column<Instance>,
 
     sl: Column<Fixed>,
     sr: Column<Fixed>,
     so: Column<Fixed>,
     sm: Column<Fixed>,
     sc: Column<Fixed>,
+
+    pub(crate) range_config: RangeConfig,
+    pub(crate) bitwise_config: BitwiseConfig,
 }
 
 impl PlonkConfig {
@@ -71,6 +203,9 @@ impl PlonkConfig {
         meta.enable_equality(b);
         meta.enable_equality(c);
 
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
         let sm = meta.fixed_column();
         let sl = meta.fixed_column();
         let sr = meta.fixed_column();
@@ -91,7 +226,10 @@ impl PlonkConfig {
             vec![a.clone() * sl + b.clone() * sr + a * b * sm + (c * so) + sc]
         });
 
-        PlonkConfig { a, b, c, sl, sr, so, sm, sc }
+        let range_config = RangeChip::configure(meta);
+        let bitwise_config = BitwiseChip::configure(meta);
+
+        PlonkConfig { a, b, c, instance, sl, sr, so, sm, sc, range_config, bitwise_config }
     }
 }
 #[allow(clippy::type_complexity)]
@@ -325,13 +463,7 @@ impl<FF: Field> StandardCs<FF> for StandardPlonk<FF> {
         layouter: &mut impl Layouter<FF>,
         left: Cell,
         right: Cell,
-    ) -> Result<(), zcash_halo2_proofs::plonk::Error> {
-        layouter.assign_region(
-            || "copy",
-            |mut region| {
-                region.constrain_equal(left, right)?;
-                region.constrain_equal(left, right)
-            },
-        )
+    ) -> Result<(), Halo2Error> {
+        layouter.assign_region(|| "copy", |mut region| region.constrain_equal(left, right))
     }
 }
@@ -1,9 +1,13 @@
 mod acvm_interop;
 
+mod assigned_map;
+mod chips;
 mod circuit_translator;
 mod constrains;
+mod dimension_measure;
 mod halo2_params;
 mod halo2_plonk_api;
+mod tests;
 
 #[derive(Debug)]
 pub struct ZcashHalo2;
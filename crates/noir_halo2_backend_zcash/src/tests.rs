@@ -0,0 +1,286 @@
+#[cfg(test)]
+mod test {
+    use crate::{
+        assigned_map::AssignedMap,
+        circuit_translator::NoirHalo2Translator,
+        dimension_measure::DimensionMeasurement,
+        halo2_plonk_api::{PlonkConfig, StandardPlonk},
+    };
+    use acvm::{
+        acir::{
+            circuit::{
+                opcodes::{BlackBoxFuncCall, FunctionInput},
+                Circuit as NoirCircuit, Opcode, PublicInputs,
+            },
+            native_types::{Expression, Witness, WitnessMap},
+        },
+        FieldElement,
+    };
+    use noir_halo2_backend_common::test_helpers::build_artifacts;
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        marker::PhantomData,
+    };
+    use zcash_halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+
+    /// w1 + w2 - w3 = 0 and w3 + w4 - w5 = 0 share w3 between two gates,
+    /// w5 being public
+    fn packed_circuit(values: [u128; 5]) -> NoirHalo2Translator<Fp> {
+        let gate = |a: u32, b: u32, c: u32| {
+            Opcode::Arithmetic(Expression {
+                mul_terms: vec![],
+                linear_combinations: vec![
+                    (FieldElement::one(), Witness(a)),
+                    (FieldElement::one(), Witness(b)),
+                    (-FieldElement::one(), Witness(c)),
+                ],
+                q_c: FieldElement::zero(),
+            })
+        };
+        let circuit = NoirCircuit {
+            current_witness_index: 5,
+            opcodes: vec![gate(1, 2, 3), gate(3, 4, 5)],
+            public_parameters: PublicInputs(BTreeSet::from([Witness(5)])),
+            ..Default::default()
+        };
+        let witness_values = WitnessMap::from(BTreeMap::from_iter(
+            values
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| (Witness(i as u32 + 1), FieldElement::from(value))),
+        ));
+        NoirHalo2Translator::<Fp> { circuit, witness_values, _marker: PhantomData::<Fp> }
+    }
+
+    /// A single black box function call, the values being those of w1, w2, ...
+    fn black_box_circuit(call: BlackBoxFuncCall, values: &[u128]) -> NoirHalo2Translator<Fp> {
+        let circuit = NoirCircuit {
+            current_witness_index: values.len() as u32,
+            opcodes: vec![Opcode::BlackBoxFuncCall(call)],
+            ..Default::default()
+        };
+        let witness_values = WitnessMap::from(BTreeMap::from_iter(
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| (Witness(i as u32 + 1), FieldElement::from(*value))),
+        ));
+        NoirHalo2Translator::<Fp> { circuit, witness_values, _marker: PhantomData::<Fp> }
+    }
+
+    /// Circuit constraining each gate of a program with the witnesses of its own
+    /// translator, all of them sharing the same assignments, as a prover assigning
+    /// different values to the cells of a witness would
+    #[derive(Clone, Default)]
+    struct InconsistentCircuit {
+        translators: Vec<NoirHalo2Translator<Fp>>,
+    }
+
+    impl Circuit<Fp> for InconsistentCircuit {
+        type Config = PlonkConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> PlonkConfig {
+            NoirHalo2Translator::<Fp>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: PlonkConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let cs: StandardPlonk<Fp> = StandardPlonk::new(config);
+            let mut witness_assignments = AssignedMap::new();
+            for (i, translator) in self.translators.iter().enumerate() {
+                if let Opcode::Arithmetic(expression) = &translator.circuit.opcodes[i] {
+                    translator.add_arithmetic_constrains(
+                        expression,
+                        &cs,
+                        &mut layouter,
+                        &mut witness_assignments,
+                    )?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_packed_circuit_success() {
+        let translator = packed_circuit([1, 2, 3, 4, 7]);
+        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+
+        // run mock prover expecting success
+        let prover = MockProver::run(dimension.k(), &translator, vec![vec![Fp::from(7)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_packed_circuit_measured_k() {
+        let translator = packed_circuit([1, 2, 3, 4, 7]);
+        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+
+        // run mock prover one k below the measured one expecting to run out of rows
+        let prover = MockProver::run(dimension.k() - 1, &translator, vec![vec![Fp::from(7)]]);
+        assert!(prover.is_err());
+    }
+
+    #[test]
+    fn test_packed_circuit_fail_instance() {
+        let translator = packed_circuit([1, 2, 3, 4, 7]);
+        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+
+        // run mock prover with incorrect instance expecting permutation failure
+        let prover = MockProver::run(dimension.k(), &translator, vec![vec![Fp::from(8)]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // run mock prover with no instance expecting permutation failure
+        let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_packed_circuit_fail_witness() {
+        // w5 should be 7
+        let translator = packed_circuit([1, 2, 3, 4, 8]);
+        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+
+        // run mock prover expecting the second gate to fail
+        let prover = MockProver::run(dimension.k(), &translator, vec![vec![Fp::from(8)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_inconsistent_witness_fail_copy() {
+        // each gate holds with its own witnesses, but w3 is 3 in the first and 4 in the second
+        let circuit = InconsistentCircuit {
+            translators: vec![packed_circuit([1, 2, 3, 4, 7]), packed_circuit([1, 2, 4, 3, 7])],
+        };
+        let dimension = DimensionMeasurement::measure(&circuit).unwrap();
+
+        // run mock prover expecting the copy of w3 to fail
+        let prover = MockProver::run(dimension.k(), &circuit, vec![vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_squared_witness_success() {
+        // w1 * w1 - w2 = 0 assigns w1 to two cells of the same gate
+        let circuit = NoirCircuit {
+            current_witness_index: 2,
+            opcodes: vec![Opcode::Arithmetic(Expression {
+                mul_terms: vec![(FieldElement::one(), Witness(1), Witness(1))],
+                linear_combinations: vec![(-FieldElement::one(), Witness(2))],
+                q_c: FieldElement::zero(),
+            })],
+            ..Default::default()
+        };
+        let witness_values = WitnessMap::from(BTreeMap::from_iter([
+            (Witness(1), FieldElement::from(3u128)),
+            (Witness(2), FieldElement::from(9u128)),
+        ]));
+        let translator =
+            NoirHalo2Translator::<Fp> { circuit, witness_values, _marker: PhantomData::<Fp> };
+        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+
+        // run mock prover expecting success
+        let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_range_success() {
+        // 7 fits in 3 bits and 300 in 9 bits, whose most significant limb has a single bit
+        for (value, num_bits) in [(7, 3), (300, 9), (255, 8)] {
+            let input = FunctionInput { witness: Witness(1), num_bits };
+            let translator = black_box_circuit(BlackBoxFuncCall::RANGE { input }, &[value]);
+            let dimension = DimensionMeasurement::measure(&translator).unwrap();
+
+            // run mock prover expecting success
+            let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_range_fail() {
+        // 8 overflows 3 bits, 512 overflows 9 bits and 256 overflows 8 bits
+        for (value, num_bits) in [(8, 3), (512, 9), (256, 8)] {
+            let input = FunctionInput { witness: Witness(1), num_bits };
+            let translator = black_box_circuit(BlackBoxFuncCall::RANGE { input }, &[value]);
+            let dimension = DimensionMeasurement::measure(&translator).unwrap();
+
+            // run mock prover expecting a lookup failure
+            let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_bitwise_success() {
+        let lhs = FunctionInput { witness: Witness(1), num_bits: 11 };
+        let rhs = FunctionInput { witness: Witness(2), num_bits: 11 };
+        let output = Witness(3);
+
+        let and = BlackBoxFuncCall::AND { lhs, rhs, output };
+        let translator = black_box_circuit(and, &[0x5a3, 0x3c6, 0x5a3 & 0x3c6]);
+        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+
+        // the table of every pair of nibbles fits in 2^9 rows
+        assert!(dimension.k() <= 9);
+
+        // run mock prover expecting success
+        let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let xor = BlackBoxFuncCall::XOR { lhs, rhs, output };
+        let translator = black_box_circuit(xor, &[0x5a3, 0x3c6, 0x5a3 ^ 0x3c6]);
+
+        // run mock prover expecting success
+        let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_bitwise_fail_output() {
+        let lhs = FunctionInput { witness: Witness(1), num_bits: 11 };
+        let rhs = FunctionInput { witness: Witness(2), num_bits: 11 };
+        let output = Witness(3);
+
+        // the output witness holds the xor of the operands instead of their and
+        let and = BlackBoxFuncCall::AND { lhs, rhs, output };
+        let translator = black_box_circuit(and, &[0x5a3, 0x3c6, 0x5a3 ^ 0x3c6]);
+        let dimension = DimensionMeasurement::measure(&translator).unwrap();
+
+        // run mock prover expecting the chip output to differ from the witness
+        let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_circuits_native() {
+        let test_dirs_names = vec!["5_over", "8_bit_and", "12_bit_xor"];
+        for program in test_dirs_names {
+            // get circuit, compiled by a backend of the same width 3 supporting the same opcodes
+            let (circuit, witness_values) = build_artifacts(program, "axiom_halo2_backend");
+
+            // instantiate halo2 circuit
+            let translator =
+                NoirHalo2Translator::<Fp> { circuit, witness_values, _marker: PhantomData::<Fp> };
+            let dimension = DimensionMeasurement::measure(&translator).unwrap();
+
+            // run mock prover expecting success
+            let prover = MockProver::run(dimension.k(), &translator, vec![vec![]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+    }
+}